@@ -5,9 +5,40 @@
 
 extern crate criterion;
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion, Throughput,
+};
+use wiserone::quotes::{
+    read_quotes_from_reader, Format, Quote, Quotes,
+};
+use wiserone::stream::stream_quotes_from_reader;
 use wiserone::{run, wiserone_join, wiserone_map, wiserone_vec};
 
+/// The number of quotes in the generated collections.
+const COLLECTION_SIZE: usize = 20_000;
+
+/// Writes a collection of `COLLECTION_SIZE` quotes in `format`.
+fn collection(format: Format) -> Vec<u8> {
+    let quotes = (0..COLLECTION_SIZE)
+        .map(|index| Quote {
+            quote_text: format!(
+                "Quote number {} of the collection.",
+                index
+            ),
+            author: format!("Author {}", index % 100),
+            date_added: Some("2024-01-01".parse().unwrap()),
+            image_url: format!("https://example.com/{}.jpg", index),
+            tags: vec!["wisdom".to_string()],
+            ..Default::default()
+        })
+        .collect();
+    let mut buffer = Vec::new();
+    Quotes::new(quotes).write_to(&mut buffer, format).unwrap();
+    buffer
+}
+
 fn wiserone_vec_benchmark(c: &mut Criterion) {
     c.bench_function("wiserone_vec_macro", |b| {
         b.iter(|| wiserone_vec![1, 2, 3, 4, 5])
@@ -38,6 +69,56 @@ fn wiserone_benchmark(c: &mut Criterion) {
     });
 }
 
+/// Compares loading a whole collection before using its quotes with
+/// streaming them one at a time.
+fn quote_loading_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("quote_loading");
+    let _ =
+        group.throughput(Throughput::Elements(COLLECTION_SIZE as u64));
+    let _ = group.sample_size(10);
+    for format in [Format::Json, Format::JsonLines, Format::Csv] {
+        let dataset = collection(format);
+        let _ = group.bench_with_input(
+            BenchmarkId::new("load", format),
+            &dataset,
+            |b, dataset| {
+                b.iter(|| {
+                    let quotes = read_quotes_from_reader(
+                        dataset.as_slice(),
+                        format,
+                    )
+                    .unwrap();
+                    quotes
+                        .select_all_quotes()
+                        .unwrap()
+                        .into_iter()
+                        .map(|quote| black_box(quote).quote_text.len())
+                        .sum::<usize>()
+                })
+            },
+        );
+        let _ = group.bench_with_input(
+            BenchmarkId::new("stream", format),
+            &dataset,
+            |b, dataset| {
+                b.iter(|| {
+                    stream_quotes_from_reader(
+                        dataset.as_slice(),
+                        format,
+                    )
+                    .unwrap()
+                    .map(|quote| {
+                        black_box(quote.unwrap()).quote_text.len()
+                    })
+                    .sum::<usize>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(quote_loading, quote_loading_benchmark);
 criterion_group!(
     wiserone_macros_benchmark,
     wiserone_vec_benchmark,
@@ -45,4 +126,4 @@ criterion_group!(
     wiserone_join_benchmark,
     wiserone_benchmark
 );
-criterion_main!(wiserone_macros_benchmark);
+criterion_main!(wiserone_macros_benchmark, quote_loading);
@@ -7,6 +7,8 @@ extern crate wiserone;
 // Importing necessary modules and traits from the standard library and `wiserone` crate.
 use serde_json::to_string_pretty;
 use std::error::Error;
+use std::path::Path;
+use wiserone::config::SiteConfig;
 use wiserone::html::generate_html_file;
 use wiserone::quotes::read_quotes_from_file;
 
@@ -17,7 +19,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     // The function `read_and_parse_quotes` is expected to read a JSON file,
     // parse it, and return a collection of quotes.
     println!("Reading and parsing quotes from a JSON file:");
-    let mut quotes = read_quotes_from_file("./quotes/01-quotes.json")?;
+    let quotes = read_quotes_from_file("./quotes/01-quotes.json")?;
     let json_string = to_string_pretty(&quotes).unwrap();
     println!("Quotes:\n{}\n", json_string);
 
@@ -28,12 +30,19 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("Random Quote: {}\n", json_string);
 
     // Generating an HTML file that displays the random quote.
-    // The function `generate_html_file` takes a filename and a quote,
-    // and creates an HTML file with the quote.
+    // The function `generate_html_file` takes a filename, a quote and
+    // the site configuration, and creates an HTML file with the quote
+    // in the output directory of the configuration.
     println!("Generating an HTML file for the random quote:");
-    let filename = "../examples/example_quote.html";
-    generate_html_file(filename, random_quote)?;
-    println!("Generated HTML file: {}\n", filename);
+    let mut site = SiteConfig::default();
+    site.paths.output = Path::new("target").join("example");
+    site.paths.log = site.paths.output.join("wiserone.log");
+    let filename = "example_quote.html";
+    generate_html_file(filename, random_quote, &site)?;
+    println!(
+        "Generated HTML file: {}\n",
+        site.paths.output.join(filename).display()
+    );
 
     // If everything executes successfully, return Ok.
     Ok(())
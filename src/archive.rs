@@ -0,0 +1,318 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::config::SiteConfig;
+use crate::dates::QuoteDate;
+use crate::error::WiseroneError;
+use crate::quotes::Quote;
+use crate::template::Templates;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory of the output holding the archive pages.
+pub const ARCHIVE_DIRECTORY: &str = "archive";
+
+/// The layout of the pages listing every quote.
+const ARCHIVE_LAYOUT: &str = "archive.html";
+
+/// The layout of the page of a year.
+const YEAR_LAYOUT: &str = "year.html";
+
+/// The layout of the page of a month.
+const MONTH_LAYOUT: &str = "month.html";
+
+/// A quote page listed in the archive.
+#[derive(Clone, Debug, Serialize)]
+pub struct ArchiveEntry {
+    /// The stable id of the quote.
+    pub id: String,
+    /// The text of the quote.
+    pub quote_text: String,
+    /// The author of the quote.
+    pub author: String,
+    /// The date the quote was added, which files it under its year and
+    /// month.
+    pub date_added: QuoteDate,
+    /// The address of the page of the quote.
+    pub url: String,
+}
+
+impl ArchiveEntry {
+    /// Describes the page written for `quote` at `filename` in the
+    /// output directory of `site`.
+    ///
+    /// Returns `None` for an unscheduled quote, which the archive
+    /// cannot file under a month.
+    pub fn new(
+        quote: &Quote,
+        filename: &str,
+        site: &SiteConfig,
+    ) -> Option<Self> {
+        Some(ArchiveEntry {
+            id: quote.stable_id(),
+            quote_text: quote.quote_text.clone(),
+            author: quote.author.clone(),
+            date_added: quote.date_added?,
+            url: site.page_url(filename),
+        })
+    }
+
+    /// Returns the year and month the entry is filed under, as `YYYY`
+    /// and `MM`.
+    fn year_and_month(&self) -> (String, String) {
+        let date = self.date_added.iso_date();
+        (date[..4].to_string(), date[5..7].to_string())
+    }
+}
+
+/// Generates the archive of the quote pages in `entries`.
+///
+/// The archive is written to the `archive` directory of
+/// `site.paths.output`, which is replaced on every run:
+///
+/// - `archive/index.html`, `archive/page/2.html`, … list every quote,
+///   newest first, `site.archive.page_size` to a page, from the
+///   `archive.html` layout;
+/// - `archive/<year>/index.html` lists the months and quotes of a year,
+///   from the `year.html` layout;
+/// - `archive/<year>/<month>/index.html` lists the quotes of a month,
+///   from the `month.html` layout.
+///
+/// # Returns
+///
+/// Returns the paths of the pages written, a `WiseroneError::Io` if a
+/// layout is missing or a page cannot be written, or a
+/// `WiseroneError::Template` if a layout cannot be rendered.
+pub fn generate_archive(
+    entries: &[ArchiveEntry],
+    site: &SiteConfig,
+) -> Result<Vec<PathBuf>, WiseroneError> {
+    let layouts = &site.paths.layouts;
+    let templates = Templates::load(layouts)?;
+    for layout in [ARCHIVE_LAYOUT, YEAR_LAYOUT, MONTH_LAYOUT] {
+        if !templates.contains(layout) {
+            return Err(WiseroneError::io(
+                layouts.join(layout),
+                std::io::ErrorKind::NotFound.into(),
+            ));
+        }
+    }
+
+    // Start afresh, so no page is left from a larger archive
+    let output = &site.paths.output;
+    let directory = output.join(ARCHIVE_DIRECTORY);
+    if directory.exists() {
+        fs::remove_dir_all(&directory)
+            .map_err(|err| WiseroneError::io(&directory, err))?;
+    }
+
+    let mut entries = entries.to_vec();
+    entries.sort_by(|a, b| {
+        b.date_added.cmp(&a.date_added).then_with(|| a.url.cmp(&b.url))
+    });
+
+    // File the entries under their year and month, newest first
+    let mut years: BTreeMap<
+        String,
+        BTreeMap<String, Vec<&ArchiveEntry>>,
+    > = BTreeMap::new();
+    for entry in &entries {
+        let (year, month) = entry.year_and_month();
+        years
+            .entry(year)
+            .or_default()
+            .entry(month)
+            .or_default()
+            .push(entry);
+    }
+    let year_list: Vec<Value> = years
+        .iter()
+        .rev()
+        .map(|(year, months)| {
+            json!({
+                "year": year,
+                "url": site.page_url(&year_path(year)),
+                "count": months.values().map(Vec::len).sum::<usize>(),
+            })
+        })
+        .collect();
+    let index = site.page_url(&archive_page_path(1));
+
+    let mut written = Vec::new();
+
+    // The paginated list of every quote
+    let page_size = site.archive.page_size;
+    let page_count = entries.len().div_ceil(page_size).max(1);
+    let pages: Vec<Value> = (1..=page_count)
+        .map(|number| {
+            json!({
+                "number": number,
+                "url": site.page_url(&archive_page_path(number)),
+            })
+        })
+        .collect();
+    for number in 1..=page_count {
+        let start = (number - 1) * page_size;
+        let quotes =
+            &entries[start..entries.len().min(start + page_size)];
+        let path = archive_page_path(number);
+        let title = if number == 1 {
+            "Archive".to_string()
+        } else {
+            format!("Archive, page {}", number)
+        };
+        let previous = (number > 1).then(|| &pages[number - 2]["url"]);
+        let next = pages.get(number).map(|next| &next["url"]);
+        let context = json!({
+            "site": site,
+            "page": page(site, &title, &path),
+            "archive": {
+                "index": index,
+                "quotes": quotes,
+                "years": year_list,
+                "pagination": {
+                    "number": number,
+                    "count": page_count,
+                    "previous": previous,
+                    "next": next,
+                    "pages": pages,
+                },
+            },
+        });
+        written.push(write_page(
+            &templates,
+            ARCHIVE_LAYOUT,
+            output,
+            &path,
+            &context,
+        )?);
+    }
+
+    // The pages of each year and month
+    for (year, months) in &years {
+        let path = year_path(year);
+        let month_list: Vec<Value> = months
+            .iter()
+            .rev()
+            .map(|(month, quotes)| {
+                json!({
+                    "month": month,
+                    "date_added": format!("{}-{}-01", year, month),
+                    "url": site.page_url(&month_path(year, month)),
+                    "count": quotes.len(),
+                })
+            })
+            .collect();
+        let quotes: Vec<&ArchiveEntry> =
+            months.values().rev().flatten().copied().collect();
+        let context = json!({
+            "site": site,
+            "page": page(site, &format!("Quotes of {}", year), &path),
+            "archive": {
+                "index": index,
+                "year": year,
+                "url": site.page_url(&path),
+                "months": month_list,
+                "quotes": quotes,
+                "years": year_list,
+            },
+        });
+        written.push(write_page(
+            &templates,
+            YEAR_LAYOUT,
+            output,
+            &path,
+            &context,
+        )?);
+
+        for (month, quotes) in months {
+            let path = month_path(year, month);
+            let date: QuoteDate =
+                format!("{}-{}-01", year, month).parse()?;
+            let name =
+                date.datetime().format("[month repr:long] [year]")?;
+            let context = json!({
+                "site": site,
+                "page": page(site, &format!("Quotes of {}", name), &path),
+                "archive": {
+                    "index": index,
+                    "year": year,
+                    "year_url": site.page_url(&year_path(year)),
+                    "month": month,
+                    "date_added": date,
+                    "url": site.page_url(&path),
+                    "quotes": quotes,
+                },
+            });
+            written.push(write_page(
+                &templates,
+                MONTH_LAYOUT,
+                output,
+                &path,
+                &context,
+            )?);
+        }
+    }
+
+    Ok(written)
+}
+
+/// Returns the path of a page of the list of every quote.
+fn archive_page_path(number: usize) -> String {
+    if number == 1 {
+        format!("{}/index.html", ARCHIVE_DIRECTORY)
+    } else {
+        format!("{}/page/{}.html", ARCHIVE_DIRECTORY, number)
+    }
+}
+
+/// Returns the path of the page of a year.
+fn year_path(year: &str) -> String {
+    format!("{}/{}/index.html", ARCHIVE_DIRECTORY, year)
+}
+
+/// Returns the path of the page of a month.
+fn month_path(year: &str, month: &str) -> String {
+    format!("{}/{}/{}/index.html", ARCHIVE_DIRECTORY, year, month)
+}
+
+/// Builds the `page` value of an archive page, in the language of the
+/// site.
+fn page(site: &SiteConfig, title: &str, path: &str) -> Value {
+    json!({
+        "alternates": [],
+        "author": site.name,
+        "home": site.home(),
+        "language": site.locale,
+        "locale": site.locale.replace('-', "_"),
+        "title": title,
+        "url": site.page_url(path),
+    })
+}
+
+/// Renders `layout` with `context` into `path`, relative to `output`.
+///
+/// # Returns
+///
+/// Returns the path of the page written.
+fn write_page(
+    templates: &Templates,
+    layout: &str,
+    output: &Path,
+    path: &str,
+    context: &Value,
+) -> Result<PathBuf, WiseroneError> {
+    let html = templates.render(layout, context)?;
+    let page_path = output.join(path);
+    if let Some(directory) = page_path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|err| WiseroneError::io(directory, err))?;
+    }
+    fs::write(&page_path, html)
+        .map_err(|err| WiseroneError::io(&page_path, err))?;
+    Ok(page_path)
+}
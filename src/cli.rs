@@ -2,20 +2,40 @@
 // Copyright © 2024 The Wiser One. All rights reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use clap::Parser;
-use std::error::Error;
+use clap::{Args, Parser};
+use std::fs;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
 
 use dtt::datetime::DateTime;
 use rlg::log_format::LogFormat;
 use rlg::log_level::LogLevel;
 use rlg::macro_log;
+use vrd::Random;
 
+use crate::archive::{generate_archive, ArchiveEntry};
 use crate::ascii::generate_ascii_art;
-use crate::html::generate_html_file;
-use crate::quotes::read_quotes_from_file;
+use crate::config::{SiteConfig, DEFAULT_CONFIG_FILE};
+use crate::dates::{slug, QuoteDate};
+use crate::dedupe::DEFAULT_THRESHOLD;
+use crate::error::WiseroneError;
+use crate::fortune::write_fortune_files;
+use crate::html::{generate_html_file, generate_html_files};
+use crate::loggers::create_log_file;
+use crate::query::{QuoteFilter, TagWeight};
+use crate::quotes::{
+    read_quotes_from_dir, read_quotes_from_file, read_quotes_from_glob,
+    read_quotes_from_reader, ConflictPolicy, Format, Quote, QuoteError,
+    Quotes,
+};
+use crate::rotation::{History, RotationPolicy};
+use crate::schedule::{unscheduled_from_str, SchedulePolicy};
+use crate::schema::quotes_schema;
 use crate::sitemap::generate_sitemap_file;
+use crate::stream::{stream_quotes_from_reader, QuoteStream};
+use crate::validate::{validate_str, Severity};
+use crate::writers::write_quotes_to_file;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -28,15 +48,285 @@ pub enum Command {
     /// Selects a random quote from the JSON or CSV file and creates an HTML
     /// file based on the quote.
     Random {
-        /// The name of the JSON or CSV file containing quotes.
+        /// The name of the JSON or CSV file containing quotes, a
+        /// directory or glob pattern of quote files to merge, or `-`
+        /// to read from standard input.
         filename: String,
+        /// The format of the quotes (`json`, `csv`, `yaml`, `toml`,
+        /// `jsonl` or `fortune`). Required when reading from standard
+        /// input, otherwise inferred from the file extension.
+        #[clap(long)]
+        format: Option<Format>,
+        /// How to resolve records from merged files that share a date
+        /// or an id: `error`, `first-wins` or `last-wins`.
+        #[clap(long, default_value = "error")]
+        on_conflict: ConflictPolicy,
+        /// Only consider the quotes matching these filters.
+        #[clap(flatten)]
+        filter: FilterArgs,
+        /// Draw quotes in proportion to their `weight` field.
+        #[clap(long, conflicts_with = "rotation")]
+        weighted: bool,
+        /// Multiply the weight of quotes with a tag, as `tag=weight`.
+        /// May be repeated; implies `--weighted`.
+        #[clap(long, conflicts_with = "rotation")]
+        tag_weight: Vec<TagWeight>,
+        /// Seed the random number generator, so the same seed always
+        /// selects the same quote.
+        #[clap(long)]
+        seed: Option<u64>,
+        /// Avoid repeats by rotating through the collection:
+        /// `shuffle-bag`, `least-recent` or `no-repeat:<days>`.
+        #[clap(long)]
+        rotation: Option<RotationPolicy>,
+        /// The file recording which quotes were published when, used
+        /// with `--rotation`.
+        #[clap(long, default_value = "./wiserone-history.json")]
+        history: String,
+        /// Name the generated page, and the archived pages of the
+        /// collection, after the quote ids instead of the dates.
+        #[clap(long)]
+        id_filenames: bool,
+        /// The site the pages are published on.
+        #[clap(flatten)]
+        site: SiteArgs,
     },
     /// Selects all quotes from the JSON or CSV file and creates an HTML file
     /// for each quote.
     All {
-        /// The name of the JSON file containing quotes.
+        /// The name of the JSON file containing quotes, a directory or
+        /// glob pattern of quote files to merge, or `-` to read from
+        /// standard input.
         filename: String,
+        /// The format of the quotes (`json`, `csv`, `yaml`, `toml`,
+        /// `jsonl` or `fortune`). Required when reading from standard
+        /// input, otherwise inferred from the file extension.
+        #[clap(long)]
+        format: Option<Format>,
+        /// How to resolve records from merged files that share a date
+        /// or an id: `error`, `first-wins` or `last-wins`.
+        #[clap(long, default_value = "error")]
+        on_conflict: ConflictPolicy,
+        /// Only generate pages for the quotes matching these filters.
+        #[clap(flatten)]
+        filter: FilterArgs,
+        /// Name the generated pages after the quote ids instead of
+        /// their dates.
+        #[clap(long)]
+        id_filenames: bool,
+        /// The site the pages are published on.
+        #[clap(flatten)]
+        site: SiteArgs,
     },
+    /// Selects the quote of the day and creates its HTML file.
+    ///
+    /// The quote scheduled for the day is used when there is one,
+    /// otherwise a quote is picked by a deterministic rotation, so
+    /// rebuilding the same day always publishes the same quote.
+    Today {
+        /// The name of the JSON or CSV file containing quotes, a
+        /// directory or glob pattern of quote files to merge, or `-`
+        /// to read from standard input.
+        filename: String,
+        /// The format of the quotes (`json`, `csv`, `yaml`, `toml`,
+        /// `jsonl` or `fortune`). Required when reading from standard
+        /// input, otherwise inferred from the file extension.
+        #[clap(long)]
+        format: Option<Format>,
+        /// How to resolve records from merged files that share a date
+        /// or an id: `error`, `first-wins` or `last-wins`.
+        #[clap(long, default_value = "error")]
+        on_conflict: ConflictPolicy,
+        /// Build the page for this day (`YYYY-MM-DD`) instead of today.
+        #[clap(long)]
+        date: Option<QuoteDate>,
+        /// The site the pages are published on.
+        #[clap(flatten)]
+        site: SiteArgs,
+    },
+    /// Converts a quotes file to another format, keeping every field.
+    Convert {
+        /// The name of the file containing quotes, or `-` to read from
+        /// standard input.
+        input: String,
+        /// The name of the file to write. Its extension selects the
+        /// format unless `--to` is given.
+        output: String,
+        /// The format of the input, required when reading from
+        /// standard input.
+        #[clap(long)]
+        from: Option<Format>,
+        /// The format of the output (`json`, `csv`, `yaml`, `toml`,
+        /// `jsonl` or `fortune`).
+        #[clap(long)]
+        to: Option<Format>,
+        /// Write JSON on a single line instead of pretty-printing it.
+        #[clap(long)]
+        compact: bool,
+    },
+    /// Reports duplicate and near-duplicate quotes, and optionally
+    /// writes a copy of the dataset without them.
+    Dedupe {
+        /// The name of the file containing quotes, a directory or glob
+        /// pattern of quote files to merge, or `-` to read from
+        /// standard input.
+        filename: String,
+        /// The format of the quotes, required when reading from
+        /// standard input.
+        #[clap(long)]
+        format: Option<Format>,
+        /// How to resolve records from merged files that share a date
+        /// or an id: `error`, `first-wins` or `last-wins`.
+        #[clap(long, default_value = "error")]
+        on_conflict: ConflictPolicy,
+        /// The similarity, from 0 to 1, at or above which two quotes
+        /// are near duplicates. Use 1 to only report exact duplicates.
+        #[clap(
+            long,
+            default_value_t = DEFAULT_THRESHOLD,
+            value_parser = parse_threshold
+        )]
+        threshold: f64,
+        /// Write the dataset without the duplicates to this file,
+        /// keeping the record listed first in each group.
+        #[clap(long)]
+        output: Option<String>,
+    },
+    /// Gives unscheduled quotes free days in a date range and writes
+    /// the updated dataset back.
+    Schedule {
+        /// The name of the file containing the quotes. Those without a
+        /// `date_added` are scheduled.
+        filename: String,
+        /// A file of quotes to schedule. Their `date_added` is ignored
+        /// and may be left out. The file is emptied once they are
+        /// scheduled.
+        #[clap(long)]
+        queue: Option<String>,
+        /// The first day that may be used (`YYYY-MM-DD`).
+        #[clap(long)]
+        from: QuoteDate,
+        /// The last day that may be used (`YYYY-MM-DD`).
+        #[clap(long)]
+        to: QuoteDate,
+        /// How to spread the quotes over the free days: `sequential`,
+        /// `round-robin` (by author) or `tag-spacing`.
+        #[clap(long, default_value = "sequential")]
+        policy: SchedulePolicy,
+        /// Write the dataset to this file instead of updating
+        /// `filename`.
+        #[clap(long)]
+        output: Option<String>,
+    },
+    /// Writes the JSON Schema of quote datasets, for editors and other
+    /// tools to check datasets against.
+    Schema {
+        /// The file to write the schema to.
+        #[clap(long, default_value = "schemas/quotes.schema.json")]
+        output: String,
+    },
+    /// Searches the quotes and prints the best matches with their
+    /// dates.
+    Search {
+        /// The words to look for. `word*` matches words starting with
+        /// `word` and `"two words"` matches a phrase.
+        terms: String,
+        /// The name of the file containing quotes, a directory or glob
+        /// pattern of quote files to merge, or `-` to read from
+        /// standard input.
+        filename: String,
+        /// The format of the quotes, required when reading from
+        /// standard input.
+        #[clap(long)]
+        format: Option<Format>,
+        /// How to resolve records from merged files that share a date
+        /// or an id: `error`, `first-wins` or `last-wins`.
+        #[clap(long, default_value = "error")]
+        on_conflict: ConflictPolicy,
+        /// The maximum number of matches to print.
+        #[clap(long, default_value = "10")]
+        limit: usize,
+    },
+    /// Checks a quotes file for problems and exits with a non-zero
+    /// status if any errors are found.
+    Validate {
+        /// The name of the file containing quotes, or `-` to read
+        /// from standard input.
+        filename: String,
+        /// The format of the quotes (`json`, `csv`, `yaml`, `toml`,
+        /// `jsonl` or `fortune`). Required when reading from standard
+        /// input, otherwise inferred from the file extension.
+        #[clap(long)]
+        format: Option<Format>,
+    },
+}
+
+/// The command line flags that narrow down which quotes are used.
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Only use quotes by this author.
+    #[clap(long)]
+    pub author: Option<String>,
+    /// Only use quotes with this tag. May be repeated to accept any of
+    /// several tags.
+    #[clap(long)]
+    pub tag: Vec<String>,
+    /// Only use quotes published on or after this day (`YYYY-MM-DD`).
+    #[clap(long)]
+    pub since: Option<QuoteDate>,
+    /// Only use quotes published on or before this day (`YYYY-MM-DD`).
+    #[clap(long)]
+    pub until: Option<QuoteDate>,
+}
+
+/// The command line flags that describe the site pages are built for.
+#[derive(Args, Debug)]
+pub struct SiteArgs {
+    /// The site configuration file, in TOML or YAML. Defaults to
+    /// `wiserone.toml` when it exists, and to The Wiser One's own
+    /// settings otherwise.
+    #[clap(long)]
+    pub config: Option<String>,
+    /// The directory holding the page layouts, overriding the one in
+    /// the site configuration.
+    #[clap(long)]
+    pub layouts: Option<String>,
+    /// The directory pages and the sitemap are written to, overriding
+    /// the one in the site configuration.
+    #[clap(long)]
+    pub output: Option<String>,
+}
+
+impl SiteArgs {
+    /// Reads the site configuration the flags point at.
+    pub fn load(&self) -> Result<SiteConfig, WiseroneError> {
+        let mut site = match &self.config {
+            Some(path) => SiteConfig::load(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                SiteConfig::load(DEFAULT_CONFIG_FILE)?
+            }
+            None => SiteConfig::default(),
+        };
+        if let Some(layouts) = &self.layouts {
+            site.paths.layouts = layouts.into();
+        }
+        if let Some(output) = &self.output {
+            site.paths.output = output.into();
+        }
+        Ok(site)
+    }
+}
+
+impl From<FilterArgs> for QuoteFilter {
+    fn from(args: FilterArgs) -> Self {
+        QuoteFilter {
+            author: args.author,
+            tags: args.tag,
+            since: args.since,
+            until: args.until,
+            ..QuoteFilter::default()
+        }
+    }
 }
 
 /// The entry point of the program.
@@ -49,17 +339,11 @@ pub enum Command {
 ///
 /// * `i32`: An exit code indicating the success or failure of the
 ///   program.
-pub fn run_cli() -> Result<(), Box<dyn Error>> {
-    // Open the log file for appending
-    let mut log_file = File::create("./wiserone.log")?;
-
+pub fn run_cli() -> Result<(), WiseroneError> {
     // Define date and time
     let dt = DateTime::new();
     let iso = dt.format_rfc3339()?;
-    let year = dt.year();
-    let month = &iso[5..7];
-    let day = dt.day();
-    let date = format!("{}_{}_{}", year, month, day);
+    let date = slug(&dt);
 
     // Generate a log entry
     let ascii_art_log = macro_log!(
@@ -76,47 +360,512 @@ pub fn run_cli() -> Result<(), Box<dyn Error>> {
         Err(e) => eprintln!("Error generating ASCII art: {:?}", e),
     }
 
-    // Write the log to both the console and the file
-    writeln!(log_file, "{}", ascii_art_log)?;
-
     // Parse the command line arguments using the `clap` crate.
     let command = Command::parse();
 
+    // Read the site configuration, which also says where to log
+    let site = match &command {
+        Command::Random { site, .. }
+        | Command::Today { site, .. }
+        | Command::All { site, .. } => site.load()?,
+        _ if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+            SiteConfig::load(DEFAULT_CONFIG_FILE)?
+        }
+        _ => SiteConfig::default(),
+    };
+
+    // Open the log file for appending
+    let log_path = &site.paths.log;
+    let mut log_file = create_log_file(log_path)?;
+
+    // Write the log to both the console and the file
+    writeln!(log_file, "{}", ascii_art_log)
+        .map_err(|err| WiseroneError::io(log_path, err))?;
+
     match command {
-        Command::Random { filename } => {
+        Command::Random {
+            filename,
+            format,
+            on_conflict,
+            filter,
+            weighted,
+            tag_weight,
+            seed,
+            rotation,
+            history,
+            id_filenames,
+            site: _,
+        } => {
             println!(
                 "- info:wiserone: begin generating a random quote"
             );
-            // Construct the HTML filename using `iso`
-            let html_filename = format!("{}.html", date);
-
             // Read and parse quotes, then select a random quote
-            let mut quotes = read_quotes_from_file(&filename)?;
-            let quote = quotes.select_random_quote()?;
-            generate_html_file(&html_filename, quote)?;
-            generate_sitemap_file("https://wiserone.com/")?;
+            let collection =
+                load_quotes(&filename, format, on_conflict)?;
+            let quotes = collection.filter(&filter.into());
+            let mut rng = match seed {
+                Some(seed) => Random::from_u64_seed(seed),
+                None => Random::new(),
+            };
+            let quote = match rotation {
+                Some(policy) => {
+                    let mut log = History::load(&history)?;
+                    let quote = quotes.next_in_rotation(
+                        &mut log,
+                        policy,
+                        QuoteDate::from(dt),
+                        &mut rng,
+                    )?;
+                    log.save(&history)?;
+                    quote
+                }
+                None if weighted || !tag_weight.is_empty() => quotes
+                    .select_weighted_with(&tag_weight, &mut rng)?,
+                None => quotes.select_random_quote_with(&mut rng)?,
+            };
+
+            // Construct the HTML filename using `iso` or the quote id
+            let html_filename = if id_filenames {
+                format!("{}.html", quote.stable_id())
+            } else {
+                format!("{}.html", date)
+            };
+            // An unscheduled quote is published on the day of its page
+            let mut quote = quote.clone();
+            let _ = quote.date_added.get_or_insert(QuoteDate::from(dt));
+            generate_html_file(&html_filename, &quote, &site)?;
+            archive_quotes(&collection, id_filenames, &site)?;
+            generate_sitemap_file(
+                &site.page_url(""),
+                &site.paths.output,
+                &site.locale,
+            )?;
         }
-        Command::All { filename } => {
+        Command::Today {
+            filename,
+            format,
+            on_conflict,
+            date,
+            site: _,
+        } => {
+            let day = date.unwrap_or_else(|| QuoteDate::from(dt));
+            println!(
+                "- info:wiserone: begin generating the quote for {}",
+                day.iso_date()
+            );
+            let quotes = load_quotes(&filename, format, on_conflict)?;
+            let mut quote = quotes.quote_for_date(&day)?.clone();
+            let _ = quote.date_added.get_or_insert(day);
+
+            let html_filename = format!("{}.html", day.slug());
+            generate_html_file(&html_filename, &quote, &site)?;
+            archive_quotes(&quotes, false, &site)?;
+            generate_sitemap_file(
+                &site.page_url(""),
+                &site.paths.output,
+                &site.locale,
+            )?;
+        }
+        Command::All {
+            filename,
+            format,
+            on_conflict,
+            filter,
+            id_filenames,
+            site: _,
+        } => {
             println!("- info:wiserone: begin generating all quotes");
-            // Read and parse all quotes
-            let quotes = read_quotes_from_file(&filename)?;
+            // Read the quotes one at a time and write each page as it
+            // is read
+            let filter: QuoteFilter = filter.into();
+            // The archive lists every quote, so a filtered run keeps
+            // the one built by the last unfiltered run
+            let archive = filter.is_empty();
+            if !archive {
+                println!(
+                    "- info:wiserone: keeping the archive, as only the \
+                     matching quotes are generated"
+                );
+            }
+            // Unscheduled quotes have no day to be published on yet
+            let mut unscheduled = 0;
+            let quotes = stream_quotes(&filename, format, on_conflict)?
+                .filter(|quote| match quote {
+                    Ok(quote) if quote.date_added.is_none() => {
+                        unscheduled += 1;
+                        false
+                    }
+                    Ok(quote) => filter.matches(quote),
+                    Err(_) => true,
+                });
 
             // Generate an HTML file for each quote
-            for quote in quotes.select_all_quotes()? {
-                // Split `date_added` at "T" and take the date part
-                let date_part =
-                    quote.date_added.split('T').next().unwrap_or("");
+            let written = generate_html_files(
+                quotes,
+                |quote| page_filename(quote, id_filenames),
+                archive,
+                &site,
+            )?;
+            if unscheduled > 0 {
+                println!(
+                    "- info:wiserone: skipped {} unscheduled quote(s); \
+                     run `schedule` to give them days",
+                    unscheduled
+                );
+            }
+            if written == 0 {
+                return Err(QuoteError::NoQuotesAvailable.into());
+            }
+            generate_sitemap_file(
+                &site.page_url(""),
+                &site.paths.output,
+                &site.locale,
+            )?;
+            println!("- info:wiserone: end generating all quotes\n\n");
+        }
+        Command::Convert { input, output, from, to, compact } => {
+            println!(
+                "- info:wiserone: converting `{}` to `{}`",
+                input, output
+            );
+            let quotes = if input == STDIN {
+                read_quotes_from_reader(
+                    io::stdin().lock(),
+                    stdin_format(from)?,
+                )?
+            } else {
+                let format = match from {
+                    Some(format) => format,
+                    None => Format::from_path(&input)?,
+                };
+                read_quotes_from_reader(open(&input)?, format)?
+            };
+
+            let format = match to {
+                Some(format) => format,
+                None => Format::from_path(&output)?,
+            };
+            if format == Format::Fortune {
+                // `fortune` also needs the strfile index of the file
+                write_fortune_files(&quotes, &output)?;
+            } else {
+                let writer =
+                    BufWriter::new(File::create(&output).map_err(
+                        |err| WiseroneError::io(&output, err),
+                    )?);
+                if compact {
+                    quotes.write_compact_to(writer, format)?;
+                } else {
+                    quotes.write_to(writer, format)?;
+                }
+            }
+            println!(
+                "- info:wiserone: wrote {} quote(s) as {}",
+                quotes.quotes.len(),
+                format
+            );
+        }
+        Command::Dedupe {
+            filename,
+            format,
+            on_conflict,
+            threshold,
+            output,
+        } => {
+            let quotes = load_quotes(&filename, format, on_conflict)?;
+            let (cleaned, duplicates) = quotes.dedupe(threshold);
+            for duplicate in &duplicates {
+                println!("{}", duplicate);
+            }
+            println!(
+                "- info:wiserone: {} duplicate(s) found",
+                duplicates.len()
+            );
+            if let Some(output) = output {
+                write_quotes_to_file(&cleaned, &output)?;
+                println!(
+                    "- info:wiserone: wrote {} quote(s) to `{}`",
+                    cleaned.quotes.len(),
+                    output
+                );
+            }
+        }
+        Command::Schedule {
+            filename,
+            queue,
+            from,
+            to,
+            policy,
+            output,
+        } => {
+            let mut quotes = read_quotes_from_file(&filename)?;
+            let mut unscheduled = quotes.take_unscheduled();
+            // A quote sharing its day with an earlier one is not
+            // really scheduled, so it gets a free day too
+            for quote in quotes.take_collisions() {
+                println!(
+                    "- warn:wiserone: `{}` shares {} with an earlier quote",
+                    quote.stable_id(),
+                    quote.day().unwrap_or_default()
+                );
+                unscheduled.push(quote);
+            }
+            if let Some(queue) = &queue {
+                let content = fs::read_to_string(queue)
+                    .map_err(|err| WiseroneError::io(queue, err))?;
+                unscheduled.extend(unscheduled_from_str(
+                    &content,
+                    Format::from_path(queue)?,
+                )?);
+            }
 
-                // Replace "-" with "_" to format as "YYYY_MM_DD"
-                let formatted_date = date_part.replace('-', "_");
+            let scheduled =
+                quotes.schedule(unscheduled, from, to, policy)?;
+            for quote in scheduled {
+                println!(
+                    "{}  {}\n    \"{}\" - {}",
+                    quote.day().unwrap_or_default(),
+                    quote.stable_id(),
+                    quote.quote_text,
+                    quote.author
+                );
+            }
+            println!(
+                "- info:wiserone: {} quote(s) scheduled",
+                scheduled.len()
+            );
 
-                let html_filename = format!("{}.html", formatted_date);
-                generate_html_file(&html_filename, quote)?;
-                generate_sitemap_file("https://wiserone.com/")?;
+            let output = output.unwrap_or(filename);
+            write_quotes_to_file(&quotes, &output)?;
+            println!(
+                "- info:wiserone: wrote {} quote(s) to `{}`",
+                quotes.quotes.len(),
+                output
+            );
+            // The queued quotes are in the dataset now, so empty the
+            // queue to avoid adding them again on the next run
+            if let Some(queue) = queue {
+                let file = File::create(&queue)
+                    .map_err(|err| WiseroneError::io(&queue, err))?;
+                Quotes::default().write_to(
+                    BufWriter::new(file),
+                    Format::from_path(&queue)?,
+                )?;
+                println!(
+                    "- info:wiserone: emptied the queue `{}`",
+                    queue
+                );
             }
-            println!("- info:wiserone: end generating all quotes\n\n");
         }
+        Command::Schema { output } => {
+            let mut schema =
+                serde_json::to_string_pretty(&quotes_schema())
+                    .map_err(QuoteError::from)?;
+            schema.push('\n');
+            fs::write(&output, schema)
+                .map_err(|err| WiseroneError::io(&output, err))?;
+            println!(
+                "- info:wiserone: wrote the schema to `{}`",
+                output
+            );
+        }
+        Command::Search {
+            terms,
+            filename,
+            format,
+            on_conflict,
+            limit,
+        } => {
+            let quotes = load_quotes(&filename, format, on_conflict)?;
+            let hits = quotes.search(&terms);
+            println!(
+                "- info:wiserone: {} quote(s) match `{}`",
+                hits.len(),
+                terms
+            );
+            for hit in hits.iter().take(limit) {
+                println!(
+                    "{}  {}  {:.2}\n    \"{}\" - {}",
+                    hit.quote
+                        .day()
+                        .unwrap_or_else(|| "unscheduled".to_string()),
+                    hit.quote.stable_id(),
+                    hit.score,
+                    hit.quote.quote_text,
+                    hit.quote.author
+                );
+            }
+        }
+        Command::Validate { filename, format } => {
+            println!("- info:wiserone: validating `{}`", filename);
+            let report = if filename == STDIN {
+                let mut content = String::new();
+                let _ = io::stdin()
+                    .read_to_string(&mut content)
+                    .map_err(|err| WiseroneError::io(STDIN, err))?;
+                validate_str(&content, stdin_format(format)?)?
+            } else {
+                let format = match format {
+                    Some(format) => format,
+                    None => Format::from_path(&filename)?,
+                };
+                let content = fs::read_to_string(&filename)
+                    .map_err(|err| WiseroneError::io(&filename, err))?;
+                validate_str(&content, format)?
+            };
+            for finding in &report.findings {
+                println!("{}", finding);
+            }
+
+            let errors = report.by_severity(Severity::Error).count();
+            let warnings =
+                report.by_severity(Severity::Warning).count();
+            println!(
+                "- info:wiserone: {} error(s), {} warning(s)",
+                errors, warnings
+            );
+            if report.has_errors() {
+                return Err(WiseroneError::ValidationFailed(
+                    filename, errors,
+                ));
+            }
+        }
+    }
+
+    // Generate a log entry
+    let quote_log = macro_log!(
+        "id",
+        &iso,
+        &LogLevel::INFO,
+        "process",
+        "Quote HTML file generated successfully.",
+        &LogFormat::CLF
+    );
+
+    // Write the log to both the console and the file
+    writeln!(log_file, "{}", quote_log)
+        .map_err(|err| WiseroneError::io(log_path, err))?;
+
+    Ok(())
+}
+
+/// The filename that stands for standard input.
+const STDIN: &str = "-";
+
+/// Returns the format of a dataset read from standard input, which
+/// cannot be inferred from an extension.
+fn stdin_format(
+    format: Option<Format>,
+) -> Result<Format, WiseroneError> {
+    format.ok_or_else(|| {
+        WiseroneError::Usage(
+            "reading quotes from standard input requires `--format`"
+                .into(),
+        )
+    })
+}
+
+/// Parses a similarity threshold, which must be from 0 to 1.
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(threshold) if (0.0..=1.0).contains(&threshold) => {
+            Ok(threshold)
+        }
+        Ok(_) => Err(format!("`{}` is not between 0 and 1", value)),
+        Err(err) => {
+            Err(format!("`{}` is not a number: {}", value, err))
+        }
+    }
+}
+
+/// Returns the name of the page `all` writes for `quote`: its date, or
+/// its stable id with `--id-filenames` or while it is unscheduled.
+fn page_filename(quote: &Quote, id_filenames: bool) -> String {
+    match quote.date_added {
+        Some(date) if !id_filenames => format!("{}.html", date.slug()),
+        _ => format!("{}.html", quote.stable_id()),
     }
+}
 
+/// Rebuilds the archive of every quote in `quotes`, linking to the
+/// pages `all` writes for them.
+fn archive_quotes(
+    quotes: &Quotes,
+    id_filenames: bool,
+    site: &SiteConfig,
+) -> Result<(), WiseroneError> {
+    let entries: Vec<ArchiveEntry> = quotes
+        .quotes
+        .iter()
+        .filter_map(|quote| {
+            let filename = page_filename(quote, id_filenames);
+            ArchiveEntry::new(quote, &filename, site)
+        })
+        .collect();
+    let _ = generate_archive(&entries, site)?;
     Ok(())
 }
+
+/// Opens a file for reading.
+fn open(path: &str) -> Result<File, WiseroneError> {
+    File::open(path).map_err(|err| WiseroneError::io(path, err))
+}
+
+/// Streams quotes from a single file or standard input, or merges
+/// every quote file in a directory or matching a glob pattern.
+///
+/// Merged files are loaded whole, since collisions between files can
+/// only be found once all of them are read.
+fn stream_quotes(
+    source: &str,
+    format: Option<Format>,
+    policy: ConflictPolicy,
+) -> Result<QuoteStream<'static>, WiseroneError> {
+    if source == STDIN {
+        return Ok(stream_quotes_from_reader(
+            io::stdin(),
+            stdin_format(format)?,
+        )?);
+    }
+    if Path::new(source).is_dir() || source.contains(['*', '?']) {
+        return Ok(load_quotes(source, None, policy)?.into());
+    }
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(source)?,
+    };
+    Ok(stream_quotes_from_reader(open(source)?, format)?)
+}
+
+/// Reads quotes from a single file or standard input, or merges every
+/// quote file in a directory or matching a glob pattern.
+///
+/// `format` overrides the format inferred from a file extension.
+/// Collisions resolved by `policy` are reported as warnings.
+fn load_quotes(
+    source: &str,
+    format: Option<Format>,
+    policy: ConflictPolicy,
+) -> Result<Quotes, WiseroneError> {
+    if source == STDIN {
+        return Ok(read_quotes_from_reader(
+            io::stdin().lock(),
+            stdin_format(format)?,
+        )?);
+    }
+
+    let merged = if Path::new(source).is_dir() {
+        read_quotes_from_dir(source, policy)?
+    } else if source.contains(['*', '?']) {
+        read_quotes_from_glob(source, policy)?
+    } else if let Some(format) = format {
+        return Ok(read_quotes_from_reader(open(source)?, format)?);
+    } else {
+        return Ok(read_quotes_from_file(source)?);
+    };
+
+    for collision in &merged.collisions {
+        println!("- warn:wiserone: {}", collision);
+    }
+    Ok(merged.quotes)
+}
@@ -0,0 +1,271 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::WiseroneError;
+use crate::quotes::{is_language_tag, DEFAULT_LANGUAGE};
+
+/// The site configuration file read when none is given.
+pub const DEFAULT_CONFIG_FILE: &str = "wiserone.toml";
+
+/// The settings of a quote site.
+///
+/// A site is described by a `wiserone.toml` file, or a YAML file with
+/// the same keys. Keys left out keep the values of The Wiser One's own
+/// site, so a configuration only needs what differs:
+///
+/// ```toml
+/// base_url = "https://quotes.example.com"
+/// name = "example"
+/// description = "A quote a day."
+/// cdn = "https://cdn.example.com"
+/// logo = "https://cdn.example.com/logo.webp"
+/// locale = "fr"
+///
+/// [analytics]
+/// measurement_id = "G-XXXXXXXXXX"
+///
+/// [social]
+/// twitter = "@example"
+///
+/// [archive]
+/// page_size = 30
+///
+/// [paths]
+/// layouts = "_layouts"
+/// output = "public"
+/// log = "wiserone.log"
+/// ```
+///
+/// The configuration, but for its `paths`, is available to layouts as
+/// `site`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SiteConfig {
+    /// The address the site is published at, such as
+    /// `https://wiserone.com`.
+    pub base_url: String,
+    /// The short name of the site, also used in asset paths on the
+    /// CDN.
+    pub name: String,
+    /// The description of the site, used in page metadata.
+    pub description: String,
+    /// The address of the CDN serving images and icons.
+    pub cdn: String,
+    /// The address of the site logo.
+    pub logo: String,
+    /// The language of quotes that do not set one, as a BCP 47
+    /// language tag such as `en-GB`.
+    pub locale: String,
+    /// The analytics service, if any.
+    pub analytics: Analytics,
+    /// The accounts of the site on social networks.
+    pub social: Social,
+    /// The archive pages listing every quote.
+    pub archive: Archive,
+    /// Where pages are read from and written to.
+    #[serde(skip_serializing)]
+    pub paths: Paths,
+}
+
+/// The analytics settings of a site.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Analytics {
+    /// The Google Analytics measurement ID, such as `G-XXXXXXXXXX`.
+    /// Pages include no analytics script without one.
+    pub measurement_id: Option<String>,
+}
+
+/// The accounts of a site on social networks.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Social {
+    /// The X (Twitter) handle credited in page cards, such as `@name`.
+    pub twitter: Option<String>,
+    /// The Mastodon account, such as `@name@instance.social`.
+    pub mastodon: Option<String>,
+    /// The GitHub user or organisation.
+    pub github: Option<String>,
+}
+
+/// The archive settings of a site.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Archive {
+    /// The number of quotes listed on each page of the archive.
+    pub page_size: usize,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Archive { page_size: 30 }
+    }
+}
+
+/// The directories and files a site is built from and into.
+///
+/// Relative paths in a configuration file are relative to the directory
+/// holding the file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Paths {
+    /// The directory holding the page layouts.
+    pub layouts: PathBuf,
+    /// The directory pages and the sitemap are written to.
+    pub output: PathBuf,
+    /// The file the log is written to.
+    pub log: PathBuf,
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Paths {
+            layouts: PathBuf::from("_layouts"),
+            output: PathBuf::from("docs"),
+            log: PathBuf::from("wiserone.log"),
+        }
+    }
+}
+
+impl Paths {
+    /// Makes relative paths relative to `directory`.
+    fn relative_to(self, directory: &Path) -> Self {
+        Paths {
+            layouts: directory.join(self.layouts),
+            output: directory.join(self.output),
+            log: directory.join(self.log),
+        }
+    }
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        SiteConfig {
+            base_url: "https://wiserone.com".to_string(),
+            name: "wiserone".to_string(),
+            description: "Daily nuggets of wisdom in a clean, minimalist design, inspiring deeper thought and personal growth with every visit.".to_string(),
+            cdn: "https://kura.pro".to_string(),
+            logo: "https://kura.pro/wiserone/images/logos/wiserone.webp"
+                .to_string(),
+            locale: DEFAULT_LANGUAGE.to_string(),
+            analytics: Analytics {
+                measurement_id: Some("G-4HKZ6N3QSC".to_string()),
+            },
+            social: Social {
+                twitter: Some("@wwdseb".to_string()),
+                ..Social::default()
+            },
+            archive: Archive::default(),
+            paths: Paths::default(),
+        }
+    }
+}
+
+impl SiteConfig {
+    /// Reads a site configuration file.
+    ///
+    /// Files ending in `.yaml` or `.yml` are read as YAML, and any
+    /// other file as TOML. Relative `paths` are made relative to the
+    /// directory holding the file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the configuration, `WiseroneError::Io` if the file
+    /// cannot be read, or `WiseroneError::Config` if it is malformed,
+    /// has an unknown key or holds an invalid value.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WiseroneError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|err| WiseroneError::io(path, err))?;
+        let is_yaml = path.extension().is_some_and(|extension| {
+            extension == "yaml" || extension == "yml"
+        });
+        let config = if is_yaml {
+            SiteConfig::from_yaml(&content)
+        } else {
+            SiteConfig::from_toml(&content)
+        };
+        let mut config = config.map_err(|err| match err {
+            WiseroneError::Config(message) => WiseroneError::Config(
+                format!("`{}`: {}", path.display(), message),
+            ),
+            other => other,
+        })?;
+        if let Some(directory) = path.parent() {
+            config.paths = config.paths.relative_to(directory);
+        }
+        Ok(config)
+    }
+
+    /// Reads a site configuration written in TOML.
+    pub fn from_toml(content: &str) -> Result<Self, WiseroneError> {
+        let config: SiteConfig = toml::from_str(content)
+            .map_err(|err| WiseroneError::Config(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads a site configuration written in YAML.
+    pub fn from_yaml(content: &str) -> Result<Self, WiseroneError> {
+        let config: SiteConfig = if content.trim().is_empty() {
+            SiteConfig::default()
+        } else {
+            serde_yaml::from_str(content)
+                .map_err(|err| WiseroneError::Config(err.to_string()))?
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns the address of the home page, without a trailing slash.
+    pub fn home(&self) -> &str {
+        self.base_url.trim_end_matches('/')
+    }
+
+    /// Returns the address of a path on the site, such as
+    /// `fr/index.html`.
+    pub fn page_url(&self, path: &str) -> String {
+        format!("{}/{}", self.home(), path.trim_start_matches('/'))
+    }
+
+    /// Checks the values that pages are built from.
+    fn validate(&self) -> Result<(), WiseroneError> {
+        for (key, value) in [
+            ("base_url", &self.base_url),
+            ("cdn", &self.cdn),
+            ("logo", &self.logo),
+        ] {
+            if !value.starts_with("https://")
+                && !value.starts_with("http://")
+            {
+                return Err(WiseroneError::Config(format!(
+                    "`{}` must be an http or https URL, found `{}`",
+                    key, value
+                )));
+            }
+        }
+        if self.name.trim().is_empty() {
+            return Err(WiseroneError::Config(
+                "`name` must not be empty".to_string(),
+            ));
+        }
+        if self.archive.page_size == 0 {
+            return Err(WiseroneError::Config(
+                "`archive.page_size` must be at least 1".to_string(),
+            ));
+        }
+        if !is_language_tag(&self.locale) {
+            return Err(WiseroneError::Config(format!(
+                "`locale` must be a language tag, found `{}`",
+                self.locale
+            )));
+        }
+        Ok(())
+    }
+}
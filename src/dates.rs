@@ -0,0 +1,192 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use dtt::datetime::DateTime;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::quotes::QuoteError;
+
+/// The date on which a quote is published.
+///
+/// Dates are parsed from RFC 3339 (`2024-01-01T06:06:06Z`) or plain ISO
+/// 8601 dates (`2024-01-01`, read as midnight UTC) and keep their UTC
+/// offset. Comparisons use the absolute instant, so quotes from
+/// different time zones sort chronologically, while the calendar day
+/// (and therefore the page slug) is the one written in the dataset.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QuoteDate {
+    datetime: DateTime,
+    date_only: bool,
+}
+
+impl QuoteDate {
+    /// Parses a quote date.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - An RFC 3339 date and time or an ISO 8601 date.
+    ///
+    /// # Returns
+    ///
+    /// Returns the parsed `QuoteDate`, or `QuoteError::InvalidDate` if
+    /// the value is not a valid date.
+    pub fn parse(value: &str) -> Result<Self, QuoteError> {
+        let value = value.trim();
+        let datetime = DateTime::parse(value)
+            .map_err(|_| QuoteError::InvalidDate(value.to_string()))?;
+        Ok(QuoteDate {
+            datetime,
+            date_only: !value.contains('T') && !value.contains('t'),
+        })
+    }
+
+    /// Returns the underlying date and time.
+    pub fn datetime(&self) -> DateTime {
+        self.datetime
+    }
+
+    /// Returns the page slug of the date, formatted as `YYYY_MM_DD`.
+    pub fn slug(&self) -> String {
+        slug(&self.datetime)
+    }
+
+    /// Returns the calendar day of the date, formatted as `YYYY-MM-DD`.
+    pub fn iso_date(&self) -> String {
+        iso_date(&self.datetime)
+    }
+
+    /// Returns the date `days` calendar days later, at the same time.
+    ///
+    /// # Returns
+    ///
+    /// Returns the new date, or `QuoteError::InvalidDate` if it is out
+    /// of range.
+    pub fn add_days(&self, days: i64) -> Result<Self, QuoteError> {
+        let datetime = self
+            .datetime
+            .add_days(days)
+            .map_err(|_| QuoteError::InvalidDate(self.to_string()))?;
+        Ok(QuoteDate { datetime, date_only: self.date_only })
+    }
+
+    /// Returns the number of calendar days from `earlier` to this date.
+    ///
+    /// Only the calendar days written in the dates are compared, so the
+    /// result is negative when `earlier` is in fact later.
+    pub fn days_since(&self, earlier: &QuoteDate) -> i64 {
+        self.day_number() - earlier.day_number()
+    }
+
+    /// Returns the number of days from the Unix epoch to the calendar
+    /// day of the date.
+    fn day_number(&self) -> i64 {
+        DateTime::parse(&self.iso_date())
+            .map(|midnight| {
+                midnight.unix_timestamp().div_euclid(86_400)
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl From<DateTime> for QuoteDate {
+    fn from(datetime: DateTime) -> Self {
+        QuoteDate { datetime, date_only: false }
+    }
+}
+
+impl FromStr for QuoteDate {
+    type Err = QuoteError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        QuoteDate::parse(value)
+    }
+}
+
+impl fmt::Display for QuoteDate {
+    /// Writes the date back in the form it was parsed from: a plain
+    /// ISO 8601 date, or an RFC 3339 date and time.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.date_only {
+            write!(f, "{}", self.iso_date())
+        } else {
+            let rfc3339 = self
+                .datetime
+                .format_rfc3339()
+                .map_err(|_| fmt::Error)?;
+            write!(f, "{}", rfc3339)
+        }
+    }
+}
+
+impl PartialEq for QuoteDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.datetime == other.datetime
+    }
+}
+
+impl Eq for QuoteDate {}
+
+impl PartialOrd for QuoteDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QuoteDate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.datetime.cmp(&other.datetime)
+    }
+}
+
+impl Hash for QuoteDate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.datetime.hash(state);
+    }
+}
+
+impl Serialize for QuoteDate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for QuoteDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        QuoteDate::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Returns the page slug of a date, formatted as `YYYY_MM_DD`.
+///
+/// This is the one place where page filenames are derived from dates.
+pub fn slug(datetime: &DateTime) -> String {
+    format!(
+        "{:04}_{:02}_{:02}",
+        datetime.year(),
+        u8::from(datetime.month()),
+        datetime.day()
+    )
+}
+
+/// Returns the calendar day of a date, formatted as `YYYY-MM-DD`.
+pub fn iso_date(datetime: &DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        datetime.year(),
+        u8::from(datetime.month()),
+        datetime.day()
+    )
+}
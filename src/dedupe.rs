@@ -0,0 +1,225 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::dates::QuoteDate;
+use crate::quotes::Quotes;
+use crate::search::fold;
+
+/// The similarity above which two quotes are reported as near
+/// duplicates by default.
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// The number of characters in each shingle.
+const SHINGLE_SIZE: usize = 3;
+
+/// How close two duplicate quotes are.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DuplicateKind {
+    /// The texts are the same once case, accents, punctuation and
+    /// whitespace are ignored.
+    Exact,
+    /// The texts are similar enough to be the same thought.
+    Near,
+}
+
+/// A quote that repeats an earlier one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    /// The index of the earlier record.
+    pub first: usize,
+    /// The publication date of the earlier record, if it is
+    /// scheduled.
+    pub first_date: Option<QuoteDate>,
+    /// The index of the later record.
+    pub second: usize,
+    /// The publication date of the later record, if it is scheduled.
+    pub second_date: Option<QuoteDate>,
+    /// Whether the texts are identical or only similar.
+    pub kind: DuplicateKind,
+    /// The Jaccard similarity of the texts, from 0 to 1.
+    pub similarity: f64,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record {} ({}) ",
+            self.second + 1,
+            day(self.second_date)
+        )?;
+        match self.kind {
+            DuplicateKind::Exact => write!(f, "repeats")?,
+            DuplicateKind::Near => write!(
+                f,
+                "is {:.0}% similar to",
+                self.similarity * 100.0
+            )?,
+        }
+        write!(
+            f,
+            " record {} ({})",
+            self.first + 1,
+            day(self.first_date)
+        )
+    }
+}
+
+/// Describes a publication date as `YYYY-MM-DD`, or `unscheduled`.
+fn day(date: Option<QuoteDate>) -> String {
+    date.map_or_else(
+        || "unscheduled".to_string(),
+        |date| date.iso_date(),
+    )
+}
+
+impl Quotes {
+    /// Finds quotes whose text repeats an earlier quote.
+    ///
+    /// Texts are compared after folding case and accents and dropping
+    /// punctuation (including smart quotes) and extra whitespace.
+    /// Identical texts are reported as exact duplicates; otherwise the
+    /// Jaccard similarity of their character shingles is compared with
+    /// `threshold`.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The similarity, from 0 to 1, at or above which
+    ///   two texts are near duplicates. Use 1 to only find exact
+    ///   duplicates.
+    ///
+    /// # Returns
+    ///
+    /// Returns every duplicate pair, ordered by the later record.
+    pub fn find_duplicates(&self, threshold: f64) -> Vec<Duplicate> {
+        let texts: Vec<String> = self
+            .quotes
+            .iter()
+            .map(|quote| normalize(&quote.quote_text))
+            .collect();
+        let shingles: Vec<HashSet<&str>> =
+            texts.iter().map(|text| shingle(text)).collect();
+
+        let mut duplicates = Vec::new();
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for second in 0..texts.len() {
+            if let Some(&first) = seen.get(texts[second].as_str()) {
+                duplicates.push(self.duplicate(
+                    first,
+                    second,
+                    DuplicateKind::Exact,
+                    1.0,
+                ));
+                continue;
+            }
+            let _ = seen.insert(&texts[second], second);
+
+            if threshold >= 1.0 {
+                continue;
+            }
+            for first in 0..second {
+                let (a, b) = (&shingles[first], &shingles[second]);
+                // Jaccard similarity can never exceed the size ratio.
+                let (small, large) =
+                    (a.len().min(b.len()), a.len().max(b.len()));
+                if large == 0
+                    || (small as f64) < threshold * large as f64
+                {
+                    continue;
+                }
+                let shared = a.intersection(b).count();
+                let similarity =
+                    shared as f64 / (a.len() + b.len() - shared) as f64;
+                if similarity >= threshold {
+                    duplicates.push(self.duplicate(
+                        first,
+                        second,
+                        DuplicateKind::Near,
+                        similarity,
+                    ));
+                    break;
+                }
+            }
+        }
+        duplicates
+    }
+
+    /// Returns a copy of the collection without the quotes that repeat
+    /// an earlier one, along with the duplicates that were dropped.
+    ///
+    /// The record listed first in each group is kept, whatever its
+    /// date.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - See [`Quotes::find_duplicates`].
+    pub fn dedupe(&self, threshold: f64) -> (Quotes, Vec<Duplicate>) {
+        let duplicates = self.find_duplicates(threshold);
+        let dropped: HashSet<usize> = duplicates
+            .iter()
+            .map(|duplicate| duplicate.second)
+            .collect();
+        let quotes = Quotes {
+            schema_version: self.schema_version,
+            quotes: self
+                .quotes
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !dropped.contains(index))
+                .map(|(_, quote)| quote.clone())
+                .collect(),
+        };
+        (quotes, duplicates)
+    }
+
+    /// Builds the report of a duplicate pair.
+    fn duplicate(
+        &self,
+        first: usize,
+        second: usize,
+        kind: DuplicateKind,
+        similarity: f64,
+    ) -> Duplicate {
+        Duplicate {
+            first,
+            first_date: self.quotes[first].date_added,
+            second,
+            second_date: self.quotes[second].date_added,
+            kind,
+            similarity,
+        }
+    }
+}
+
+/// Folds case and accents and keeps only words separated by single
+/// spaces.
+fn normalize(text: &str) -> String {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the character shingles of a normalized text. Texts shorter
+/// than a shingle are their own single shingle.
+fn shingle(text: &str) -> HashSet<&str> {
+    let bounds: Vec<usize> = text
+        .char_indices()
+        .map(|(at, _)| at)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    if bounds.len() <= SHINGLE_SIZE {
+        return std::iter::once(text)
+            .filter(|t| !t.is_empty())
+            .collect();
+    }
+    bounds
+        .windows(SHINGLE_SIZE + 1)
+        .map(|window| &text[window[0]..window[SHINGLE_SIZE]])
+        .collect()
+}
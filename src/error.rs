@@ -0,0 +1,122 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use dtt::error::DateTimeError;
+
+use crate::ascii::ArtError;
+use crate::quotes::QuoteError;
+
+/// Error type for every failure of the crate.
+///
+/// Library callers can match on the variant to tell, for example, a
+/// dataset that could not be parsed from a page that could not be
+/// written.
+#[derive(Debug)]
+pub enum WiseroneError {
+    /// Quotes could not be loaded, parsed, selected or written.
+    Quote(QuoteError),
+
+    /// A file or directory could not be read or written, with the path
+    /// involved.
+    Io(PathBuf, std::io::Error),
+
+    /// A page could not be rendered from its template.
+    Template(String),
+
+    /// The sitemap could not be generated.
+    Sitemap(String),
+
+    /// The site configuration could not be read.
+    Config(String),
+
+    /// The ASCII art banner could not be generated.
+    Art(ArtError),
+
+    /// The current date and time could not be read or formatted.
+    Date(DateTimeError),
+
+    /// The command line arguments cannot be used together.
+    Usage(String),
+
+    /// A dataset failed validation, with its name and the number of
+    /// errors found.
+    ValidationFailed(String, usize),
+}
+
+impl WiseroneError {
+    /// Builds a `WiseroneError::Io` for an error on `path`.
+    pub fn io(path: impl AsRef<Path>, error: std::io::Error) -> Self {
+        WiseroneError::Io(path.as_ref().to_path_buf(), error)
+    }
+}
+
+impl fmt::Display for WiseroneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WiseroneError::Quote(err) => write!(f, "{}", err),
+            WiseroneError::Io(path, err) => {
+                write!(f, "I/O Error: `{}`: {}", path.display(), err)
+            }
+            WiseroneError::Template(msg) => {
+                write!(f, "Template Error: {}", msg)
+            }
+            WiseroneError::Sitemap(msg) => {
+                write!(f, "Sitemap Error: {}", msg)
+            }
+            WiseroneError::Config(msg) => {
+                write!(f, "Config Error: {}", msg)
+            }
+            WiseroneError::Art(err) => {
+                write!(f, "ASCII Art Error: {}", err)
+            }
+            WiseroneError::Date(err) => {
+                write!(f, "Date Error: {}", err)
+            }
+            WiseroneError::Usage(msg) => write!(f, "{}", msg),
+            WiseroneError::ValidationFailed(name, errors) => write!(
+                f,
+                "`{}` failed validation with {} error(s)",
+                name, errors
+            ),
+        }
+    }
+}
+
+impl Error for WiseroneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WiseroneError::Quote(err) => Some(err),
+            WiseroneError::Io(_, err) => Some(err),
+            WiseroneError::Art(err) => Some(err),
+            WiseroneError::Date(err) => Some(err),
+            WiseroneError::Template(_)
+            | WiseroneError::Sitemap(_)
+            | WiseroneError::Config(_)
+            | WiseroneError::Usage(_)
+            | WiseroneError::ValidationFailed(_, _) => None,
+        }
+    }
+}
+
+impl From<QuoteError> for WiseroneError {
+    fn from(error: QuoteError) -> Self {
+        WiseroneError::Quote(error)
+    }
+}
+
+impl From<ArtError> for WiseroneError {
+    fn from(error: ArtError) -> Self {
+        WiseroneError::Art(error)
+    }
+}
+
+impl From<DateTimeError> for WiseroneError {
+    fn from(error: DateTimeError) -> Self {
+        WiseroneError::Date(error)
+    }
+}
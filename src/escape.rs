@@ -0,0 +1,481 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_json::Value;
+
+/// The attributes whose values are URLs.
+const URL_ATTRIBUTES: [&str; 12] = [
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "icon",
+    "longdesc",
+    "manifest",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// The URL schemes a value may start a URL with.
+const SAFE_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+
+/// What a URL with an unsafe scheme, such as `javascript:`, is replaced
+/// with.
+pub const INVALID_URL: &str = "about:invalid";
+
+/// Where a value is written in an HTML page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    /// The text of an element, or a comment.
+    Text,
+    /// A quoted attribute value.
+    Attribute,
+    /// A quoted attribute value holding a URL, such as `href`. `start`
+    /// is `true` when the value begins the URL, and so chooses its
+    /// scheme.
+    Url {
+        /// Whether the value begins the URL.
+        start: bool,
+    },
+    /// A `style` element or attribute.
+    Css,
+    /// A script, outside any string.
+    Script,
+    /// A string in a script, such as a value in JSON-LD.
+    ScriptString,
+}
+
+/// Writes a value escaped for `context`.
+///
+/// Strings are written as their text, `null` as nothing, and other
+/// values as JSON. In a script outside any string, every value is
+/// written as a JSON literal instead, so `{{ quote.tags }}` becomes an
+/// array.
+pub fn write(context: Context, value: &Value, output: &mut String) {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    match context {
+        Context::Text | Context::Attribute => html(&text, output),
+        Context::Url { start } => html(&url(&text, start), output),
+        Context::Css => css(&text, output),
+        Context::Script => {
+            script_string(&value.to_string(), false, output);
+        }
+        Context::ScriptString => script_string(&text, true, output),
+    }
+}
+
+/// Escapes text for HTML element content or a quoted attribute value.
+pub fn html(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+}
+
+/// Escapes text for a string in a script.
+///
+/// The result is valid in a JSON string and in any JavaScript string,
+/// including a template literal, and can never close the `<script>`
+/// element. With `quotes` set to `false`, `"` and `\` are kept, for
+/// writing a JSON literal whose strings are already escaped.
+pub fn script_string(text: &str, quotes: bool, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '"' if quotes => output.push_str("\\u0022"),
+            '\\' if quotes => output.push_str("\\\\"),
+            '\'' => output.push_str("\\u0027"),
+            '`' => output.push_str("\\u0060"),
+            '$' => output.push_str("\\u0024"),
+            '<' => output.push_str("\\u003c"),
+            '>' => output.push_str("\\u003e"),
+            '&' => output.push_str("\\u0026"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{2028}' | '\u{2029}' => {
+                output.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c if c.is_control() => {
+                output.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => output.push(c),
+        }
+    }
+}
+
+/// Escapes text for a style sheet or a `style` attribute.
+///
+/// Every character that could end a string, a `url()` or a
+/// declaration is written as a CSS hex escape.
+pub fn css(text: &str, output: &mut String) {
+    for c in text.chars() {
+        if c.is_alphanumeric() || "-_./:?=#%~,+".contains(c) {
+            output.push(c);
+        } else {
+            output.push_str(&format!("\\{:x} ", c as u32));
+        }
+    }
+}
+
+/// Makes text safe to write in a URL.
+///
+/// Characters that cannot appear in a URL are percent-encoded. When
+/// `start` is `true` the text begins the URL, and one with a scheme
+/// other than `http`, `https`, `mailto` or `tel` is replaced with
+/// [`INVALID_URL`].
+pub fn url(text: &str, start: bool) -> String {
+    if start && !has_safe_scheme(text) {
+        return INVALID_URL.to_string();
+    }
+    let mut output = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~:/?#[]@!$&()*+,;=%".contains(&byte)
+        {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    output
+}
+
+/// Returns `true` if a URL is relative, or has a scheme in
+/// [`SAFE_SCHEMES`].
+fn has_safe_scheme(text: &str) -> bool {
+    match text.find([':', '/', '?', '#']) {
+        Some(end) if text[end..].starts_with(':') => {
+            let scheme = text[..end].to_ascii_lowercase();
+            SAFE_SCHEMES.contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+/// The elements whose content is not HTML.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Element {
+    Script,
+    Style,
+    Other,
+}
+
+/// The kinds of attribute value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Attribute {
+    Plain,
+    Url,
+    Style,
+    /// An event handler, such as `onclick`.
+    Handler,
+}
+
+/// Where a script is being read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Code {
+    /// Code outside any string or comment.
+    Plain,
+    /// A string opened by this quote, and whether the last character
+    /// was a backslash.
+    String(u8, bool),
+    /// After a `/`, which may start a comment.
+    Slash,
+    LineComment,
+    /// A block comment, and whether the last character was a `*`.
+    BlockComment(bool),
+}
+
+/// Where an HTML page is being read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum State {
+    Text,
+    /// After `<`.
+    TagOpen,
+    /// Reading the name of a start tag, or of an end tag.
+    TagName(String, bool),
+    /// Between the attributes of a tag.
+    Tag(Element),
+    AttributeName(Element, String),
+    AfterAttributeName(Element, String),
+    BeforeValue(Element, String),
+    /// A quoted attribute value, with its quote and the bytes read.
+    Value(Element, Attribute, u8, usize),
+    UnquotedValue(Element),
+    /// After `<!`, with the number of dashes that followed.
+    Declaration(usize),
+    /// A comment, with the number of dashes just read.
+    Comment(usize),
+    /// The content of a `<script>` element, with the number of bytes
+    /// of `</script` just read.
+    Script(Code, usize),
+    /// The content of a `<style>` element, with the number of bytes of
+    /// `</style` just read.
+    Style(usize),
+}
+
+/// Follows an HTML page as it is written, to tell where the next value
+/// goes.
+///
+/// The scanner understands as much HTML as layouts need: elements,
+/// quoted and unquoted attributes, comments, and the strings and
+/// comments of scripts.
+#[derive(Debug)]
+pub(crate) struct HtmlScanner {
+    state: State,
+    /// How many bytes of the page have been read.
+    scanned: usize,
+}
+
+impl Default for HtmlScanner {
+    fn default() -> Self {
+        HtmlScanner { state: State::Text, scanned: 0 }
+    }
+}
+
+impl HtmlScanner {
+    /// Reads what was written to `page` since the last call, and
+    /// returns where a value written next would go.
+    ///
+    /// # Returns
+    ///
+    /// Returns the context, or a message if a value cannot be written
+    /// safely there, such as in a tag name or an event handler.
+    pub(crate) fn context(
+        &mut self,
+        page: &str,
+    ) -> Result<Context, String> {
+        for &byte in &page.as_bytes()[self.scanned..] {
+            self.feed(byte);
+        }
+        self.scanned = page.len();
+
+        match &self.state {
+            State::Text | State::Comment(_) => Ok(Context::Text),
+            State::Value(_, Attribute::Plain, _, _) => {
+                Ok(Context::Attribute)
+            }
+            State::Value(_, Attribute::Url, _, length) => {
+                Ok(Context::Url { start: *length == 0 })
+            }
+            State::Value(_, Attribute::Style, _, _)
+            | State::Style(_) => Ok(Context::Css),
+            State::Value(_, Attribute::Handler, _, _) => {
+                Err("values cannot be written in event handlers".into())
+            }
+            State::Script(Code::String(_, _), _) => {
+                Ok(Context::ScriptString)
+            }
+            State::Script(Code::Plain | Code::Slash, _) => {
+                Ok(Context::Script)
+            }
+            State::Script(_, _) => {
+                Err("values cannot be written in script comments"
+                    .into())
+            }
+            _ => Err(
+                "values in a tag must be in a quoted attribute value"
+                    .into(),
+            ),
+        }
+    }
+
+    /// Reads one byte of the page.
+    fn feed(&mut self, byte: u8) {
+        let state = std::mem::replace(&mut self.state, State::Text);
+        self.state = match state {
+            State::Text => match byte {
+                b'<' => State::TagOpen,
+                _ => State::Text,
+            },
+            State::TagOpen => match byte {
+                b'!' => State::Declaration(0),
+                b'/' => State::TagName(String::new(), true),
+                b if b.is_ascii_alphabetic() => State::TagName(
+                    (b.to_ascii_lowercase() as char).into(),
+                    false,
+                ),
+                _ => State::Text,
+            },
+            State::TagName(mut name, end) => match byte {
+                b'>' => open_element(element(&name, end)),
+                b if b.is_ascii_whitespace() || b == b'/' => {
+                    State::Tag(element(&name, end))
+                }
+                b => {
+                    name.push(b.to_ascii_lowercase() as char);
+                    State::TagName(name, end)
+                }
+            },
+            State::Tag(element) => match byte {
+                b'>' => open_element(element),
+                b if b.is_ascii_whitespace() || b == b'/' => {
+                    State::Tag(element)
+                }
+                b => State::AttributeName(
+                    element,
+                    (b.to_ascii_lowercase() as char).into(),
+                ),
+            },
+            State::AttributeName(element, mut name) => match byte {
+                b'>' => open_element(element),
+                b'=' => State::BeforeValue(element, name),
+                b if b.is_ascii_whitespace() || b == b'/' => {
+                    State::AfterAttributeName(element, name)
+                }
+                b => {
+                    name.push(b.to_ascii_lowercase() as char);
+                    State::AttributeName(element, name)
+                }
+            },
+            State::AfterAttributeName(element, name) => match byte {
+                b'>' => open_element(element),
+                b'=' => State::BeforeValue(element, name),
+                b if b.is_ascii_whitespace() || b == b'/' => {
+                    State::AfterAttributeName(element, name)
+                }
+                b => State::AttributeName(
+                    element,
+                    (b.to_ascii_lowercase() as char).into(),
+                ),
+            },
+            State::BeforeValue(element, name) => match byte {
+                b'"' | b'\'' => {
+                    State::Value(element, attribute(&name), byte, 0)
+                }
+                b'>' => open_element(element),
+                b if b.is_ascii_whitespace() => {
+                    State::BeforeValue(element, name)
+                }
+                _ => State::UnquotedValue(element),
+            },
+            State::Value(element, kind, quote, length) => {
+                if byte == quote {
+                    State::Tag(element)
+                } else {
+                    State::Value(element, kind, quote, length + 1)
+                }
+            }
+            State::UnquotedValue(element) => match byte {
+                b'>' => open_element(element),
+                b if b.is_ascii_whitespace() => State::Tag(element),
+                _ => State::UnquotedValue(element),
+            },
+            State::Declaration(dashes) => match (byte, dashes) {
+                (b'-', 0) => State::Declaration(1),
+                (b'-', 1) => State::Comment(0),
+                (b'>', _) => State::Text,
+                _ => State::Declaration(2),
+            },
+            State::Comment(dashes) => match byte {
+                b'-' => State::Comment(dashes + 1),
+                b'>' if dashes >= 2 => State::Text,
+                _ => State::Comment(0),
+            },
+            State::Script(code, matched) => {
+                match end_tag(b"</script", matched, byte) {
+                    Some(8) => State::Tag(Element::Other),
+                    Some(matched) => {
+                        State::Script(read_code(code, byte), matched)
+                    }
+                    None => State::Script(read_code(code, byte), 0),
+                }
+            }
+            State::Style(matched) => {
+                match end_tag(b"</style", matched, byte) {
+                    Some(7) => State::Tag(Element::Other),
+                    Some(matched) => State::Style(matched),
+                    None => State::Style(0),
+                }
+            }
+        };
+    }
+}
+
+/// Returns the element a tag opens, if its content is not HTML.
+fn element(name: &str, end: bool) -> Element {
+    match name {
+        "script" if !end => Element::Script,
+        "style" if !end => Element::Style,
+        _ => Element::Other,
+    }
+}
+
+/// Returns the state after the `>` of a start tag.
+fn open_element(element: Element) -> State {
+    match element {
+        Element::Script => State::Script(Code::Plain, 0),
+        Element::Style => State::Style(0),
+        Element::Other => State::Text,
+    }
+}
+
+/// Returns the kind of the attribute called `name`.
+fn attribute(name: &str) -> Attribute {
+    if URL_ATTRIBUTES.contains(&name) {
+        Attribute::Url
+    } else if name == "style" {
+        Attribute::Style
+    } else if name.starts_with("on") {
+        Attribute::Handler
+    } else {
+        Attribute::Plain
+    }
+}
+
+/// Follows the end tag `tag` of a raw text element.
+///
+/// # Returns
+///
+/// Returns how many bytes of `tag` have now been read, or `None` if
+/// `byte` does not continue it.
+fn end_tag(tag: &[u8], matched: usize, byte: u8) -> Option<usize> {
+    if tag[matched] == byte.to_ascii_lowercase() {
+        Some(matched + 1)
+    } else if byte == b'<' {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Follows the strings and comments of a script.
+fn read_code(code: Code, byte: u8) -> Code {
+    match code {
+        Code::Plain | Code::Slash => match byte {
+            b'"' | b'\'' | b'`' => Code::String(byte, false),
+            b'/' if code == Code::Slash => Code::LineComment,
+            b'*' if code == Code::Slash => Code::BlockComment(false),
+            b'/' => Code::Slash,
+            _ => Code::Plain,
+        },
+        Code::String(quote, escaped) => match byte {
+            _ if escaped => Code::String(quote, false),
+            b'\\' => Code::String(quote, true),
+            b if b == quote => Code::Plain,
+            _ => Code::String(quote, false),
+        },
+        Code::LineComment => match byte {
+            b'\n' => Code::Plain,
+            _ => Code::LineComment,
+        },
+        Code::BlockComment(star) => match byte {
+            b'/' if star => Code::Plain,
+            b'*' => Code::BlockComment(true),
+            _ => Code::BlockComment(false),
+        },
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::io::Write;
+
+use crate::dates::QuoteDate;
+use crate::quotes::{Quote, QuoteError, Quotes};
+
+/// The line that separates two fortunes.
+const DELIMITER: &str = "%";
+
+/// The version of the strfile(8) index format written.
+const STRFILE_VERSION: u32 = 2;
+
+/// A fortune as written in a fortune file.
+#[derive(Debug)]
+pub(crate) struct Fortune {
+    /// The one-based line on which the fortune starts.
+    pub(crate) line: usize,
+    /// The text of the fortune.
+    pub(crate) text: String,
+    /// The attribution, without its leading `--`.
+    pub(crate) author: String,
+}
+
+/// Splits a fortune file into its fortunes.
+///
+/// Fortunes are separated by lines holding a single `%`, and lines
+/// starting with `%%` are comments. A last line starting with `--` (or
+/// a dash) is the attribution. Blank fortunes are skipped.
+pub(crate) fn fortunes(content: &str) -> Vec<Fortune> {
+    let mut fortunes = Vec::new();
+    let mut start = 1;
+    let mut lines: Vec<&str> = Vec::new();
+    let mut flush = |start: usize, lines: &mut Vec<&str>| {
+        let text_lines = std::mem::take(lines);
+        if let Some(fortune) = fortune(start, &text_lines) {
+            fortunes.push(fortune);
+        }
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line == DELIMITER {
+            flush(start, &mut lines);
+            start = index + 2;
+        } else if !line.starts_with("%%") {
+            if lines.is_empty() && line.trim().is_empty() {
+                start = index + 2;
+                continue;
+            }
+            lines.push(line);
+        }
+    }
+    flush(start, &mut lines);
+    fortunes
+}
+
+/// Builds a fortune from its lines, splitting off the attribution.
+fn fortune(line: usize, lines: &[&str]) -> Option<Fortune> {
+    let mut lines: Vec<&str> = lines.to_vec();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        let _ = lines.pop();
+    }
+    let author = lines
+        .last()
+        .and_then(|last| {
+            let last = last.trim();
+            ["--", "—", "―"]
+                .into_iter()
+                .find_map(|dash| last.strip_prefix(dash))
+        })
+        .map(|author| author.trim().to_string());
+    if author.is_some() {
+        let _ = lines.pop();
+    }
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some(Fortune { line, text, author: author.unwrap_or_default() })
+}
+
+/// Parses quotes from a fortune(6) cookie file.
+///
+/// Fortune files only carry the text and the attribution of each
+/// quote, so the quotes have no image, and are dated on consecutive
+/// days from `first_day` or left unscheduled.
+///
+/// # Arguments
+///
+/// * `content` - The fortune file.
+/// * `first_day` - The date of the first quote, or `None` to leave the
+///   quotes without a date for [`Quotes::schedule`] to fill in.
+///
+/// # Returns
+///
+/// Returns the quotes, or `QuoteError::InvalidDate` if the dates run
+/// out of range.
+pub fn quotes_from_fortune(
+    content: &str,
+    first_day: Option<QuoteDate>,
+) -> Result<Quotes, QuoteError> {
+    let mut quotes = Vec::new();
+    for (day, fortune) in fortunes(content).into_iter().enumerate() {
+        let date_added = match first_day {
+            Some(first_day) => Some(first_day.add_days(day as i64)?),
+            None => None,
+        };
+        quotes.push(Quote {
+            quote_text: fortune.text,
+            author: fortune.author,
+            date_added,
+            ..Default::default()
+        });
+    }
+    Ok(Quotes::unmarked(quotes))
+}
+
+/// Writes quotes as a fortune(6) cookie file.
+///
+/// Each quote is followed by a `-- Author` line, when it has an author,
+/// and a `%` line.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful, or `QuoteError::WriteError` if a
+/// quote holds a line that would be read as a delimiter.
+pub(crate) fn write_fortune(
+    quotes: &[Quote],
+    mut writer: impl Write,
+) -> Result<(), QuoteError> {
+    for quote in quotes {
+        let text = quote.quote_text.trim();
+        if text
+            .lines()
+            .any(|line| line == DELIMITER || line.starts_with("%%"))
+        {
+            return Err(QuoteError::WriteError(format!(
+                "`{}` has a line starting with `%`",
+                quote.stable_id()
+            )));
+        }
+        writeln!(writer, "{}", text)?;
+        let author = quote.author.trim();
+        if !author.is_empty() {
+            writeln!(writer, "\t\t-- {}", author)?;
+        }
+        writeln!(writer, "{}", DELIMITER)?;
+    }
+    Ok(())
+}
+
+/// Builds the strfile(8) index of a fortune file.
+///
+/// The index lets `fortune` pick a cookie without reading the whole
+/// file: a header of five big-endian 32-bit numbers (version, number of
+/// fortunes, longest and shortest length, flags) and the delimiter,
+/// then the offset of each fortune and of the end of the file. It is
+/// identical to the one written by `strfile` without options.
+///
+/// # Arguments
+///
+/// * `fortune` - The contents of the fortune file.
+pub fn strfile_index(fortune: &[u8]) -> Vec<u8> {
+    let mut offsets = vec![0_u32];
+    // Like strfile, an empty file keeps the initial shortest length.
+    let (mut longest, mut shortest) = (0_u32, u32::MAX);
+    let mut last = 0;
+    let mut position = 0;
+    for line in fortune.split_inclusive(|&byte| byte == b'\n') {
+        position += line.len();
+        let is_delimiter = line == b"%\n";
+        if !is_delimiter && position < fortune.len() {
+            continue;
+        }
+        let length =
+            position - last - if is_delimiter { line.len() } else { 0 };
+        last = position;
+        if length == 0 {
+            continue;
+        }
+        let length = length as u32;
+        offsets.push(position as u32);
+        longest = longest.max(length);
+        shortest = shortest.min(length);
+    }
+    let count = offsets.len() as u32 - 1;
+
+    let mut index = Vec::with_capacity(24 + 4 * offsets.len());
+    for value in [STRFILE_VERSION, count, longest, shortest, 0] {
+        index.extend_from_slice(&value.to_be_bytes());
+    }
+    index.extend_from_slice(&[b'%', 0, 0, 0]);
+    for offset in offsets {
+        index.extend_from_slice(&offset.to_be_bytes());
+    }
+    index
+}
+
+/// Writes quotes as a fortune(6) cookie file at `file_path`, along
+/// with its strfile(8) index at `<file_path>.dat`.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful, or an error if either file cannot
+/// be written.
+pub fn write_fortune_files(
+    quotes: &Quotes,
+    file_path: &str,
+) -> Result<(), QuoteError> {
+    let mut fortune = Vec::new();
+    write_fortune(&quotes.quotes, &mut fortune)?;
+    fs::write(file_path, &fortune)?;
+    fs::write(format!("{}.dat", file_path), strfile_index(&fortune))?;
+    Ok(())
+}
@@ -2,95 +2,89 @@
 // Copyright © 2024 The Wiser One. All rights reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::quotes::Quote;
+use crate::archive::{generate_archive, ArchiveEntry};
+use crate::config::SiteConfig;
+use crate::dates::slug;
+use crate::error::WiseroneError;
+use crate::loggers::create_log_file;
+use crate::quotes::{is_language_tag, is_slug, Quote, QuoteError};
+use crate::template::Templates;
 use dtt::datetime::DateTime;
 use rlg::log_format::LogFormat;
 use rlg::log_level::LogLevel;
 use rlg::macro_log;
-use std::fmt::format;
+use serde_json::{json, Value};
 use std::{
-    error::Error,
-    fs::{self, File},
+    collections::BTreeSet,
+    fs,
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
 };
-use uuid::Uuid;
+
+/// The layout of a quote page.
+const QUOTE_LAYOUT: &str = "quote.html";
+
+/// One language version of a page.
+#[derive(Debug)]
+struct Version {
+    /// The quote as written in this language.
+    quote: Quote,
+    /// The directory holding the page.
+    directory: PathBuf,
+    /// The address of the site in this language.
+    home: String,
+    /// The address of the page.
+    url: String,
+}
 
 /// Creates an HTML file based on the provided quote.
 ///
+/// The page is rendered from the layouts in `site.paths.layouts` and
+/// written in the quote's primary language at `<output>/<filename>`,
+/// and each translation at `<output>/<language>/<filename>`, where
+/// `<output>` is `site.paths.output`. Every version links to the others
+/// with `<link rel="alternate" hreflang>` tags, and the primary page is
+/// the `x-default`. The files written are logged to `site.paths.log`.
+///
 /// # Arguments
 ///
 /// * `filename` - The name of the file to be created.
 /// * `quote` - A reference to the quote to be used.
+/// * `site` - The site the page is published on.
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the file is successfully created, or an error
-/// otherwise.
+/// Returns `Ok(())` if the files are successfully created, a
+/// `WiseroneError::Template` if a translation is keyed by something
+/// other than a language tag, or a `WiseroneError::Io` naming the file
+/// that could not be read or written.
 pub fn generate_html_file(
     filename: &str,
     quote: &Quote,
-) -> Result<(), Box<dyn Error>> {
-    let mut layout = fs::read_to_string("_layouts/quote.html")?;
+    site: &SiteConfig,
+) -> Result<(), WiseroneError> {
+    let templates = read_layouts(&site.paths.layouts)?;
 
     // Define date and time
     let dt = DateTime::new();
     let iso = dt.format_rfc3339()?;
-    let year = dt.year();
-    let month = &iso[5..7];
-    let day = dt.day();
-
-    // Determine if the date matches today
-    let is_today = year == dt.year()
-        && month == {
-            let res =
-                format(format_args!("{:02}", u8::from(dt.month())));
-            res
-        }
-        && day == dt.day();
-
-    let date = format!("{}_{}_{}", year, month, day);
-    let prefix = if is_today {
-        "https://wiserone.com/index.html".to_string() // If the date is today
-    } else {
-        format!("https://wiserone.com/{}.html", date) // For any other date
-    };
-
-    println!("Prefix: {}", prefix);
-
-    // Replace the placeholders with values from the quote
-    layout = layout.replace("{{apple_touch_icon_sizes}}", "192x192");
-    layout = layout.replace("{{author}}", &quote.author);
-    layout = layout.replace("{{banner}}", &quote.image_url);
-    layout = layout.replace("{{cdn}}", "https://kura.pro");
-    layout = layout.replace("{{charset}}", "utf-8");
-    layout = layout.replace("{{description}}", "Daily nuggets of wisdom in a clean, minimalist design, inspiring deeper thought and personal growth with every visit.");
-    layout = layout.replace("{{hreflang}}", "en");
-    layout = layout.replace("{{item_pub_date}}", &quote.date_added);
-    layout = layout.replace(
-        "{{date}}",
-        quote.date_added.split('T').next().unwrap_or(""),
-    );
-    layout = layout.replace(
-        "{{logo}}",
-        "https://kura.pro/wiserone/images/logos/wiserone.webp",
-    );
-    layout = layout.replace("{{measurementID}}", "G-4HKZ6N3QSC");
-    layout = layout.replace("{{name}}", "wiserone");
-    layout = layout.replace("{{title}}", &quote.quote_text);
-    layout = layout.replace("{{url}}", "https://wiserone.com");
-    layout = layout.replace("{{canonical}}", &prefix);
+    let today = slug(&dt);
+
+    let docs = site.paths.output.as_path();
+    let directories =
+        write_pages(&templates, site, filename, quote, &today)?;
 
-    fs::create_dir_all("./docs")?;
-    let path = Path::new("./docs").join(filename);
-    let mut file = fs::File::create(&path)?;
-    file.write_all(layout.as_bytes())?;
+    // The stable quote id identifies the page and its log entries
+    let quote_id = quote.stable_id();
 
     // Open the log file for appending
-    let mut log_file = File::create("./wiserone.log")?;
+    let log_path = &site.paths.log;
+    let log_error = |err| WiseroneError::io(log_path, err);
+    let mut log_file = create_log_file(log_path)?;
 
     // Collect filenames into a vector, exclude .DS_Store, and sort them alphabetically
-    let mut filenames: Vec<_> = fs::read_dir("./docs")?
+    let mut filenames: Vec<_> = fs::read_dir(docs)
+        .map_err(|err| WiseroneError::io(docs, err))?
         .filter_map(|entry| {
             entry.ok().map(|e| {
                 let path = e.path();
@@ -105,62 +99,301 @@ pub fn generate_html_file(
 
     // Iterate over sorted filenames and log each one
     for filename in &filenames {
-        let uuid = Uuid::new_v4();
-
         // Write the log to both the console and the file
         let file_log = macro_log!(
-            &uuid.to_string(),
+            &quote_id,
             &iso,
             &LogLevel::INFO,
             "process",
             &format!("The HTML File is created at `{}`.", filename),
             &LogFormat::CLF
         );
-        writeln!(log_file, "{}", file_log)?;
-
-        // Assuming year, month, and day are already defined correctly
-        let today_formatted = format!(
-            "{year}_{month:02}_{day:02}",
-            year = year,
-            month = month,
-            day = day
+        writeln!(log_file, "{}", file_log).map_err(log_error)?;
+    }
+
+    // Point the index of every language at the page published today
+    for directory in &directories {
+        let message = update_index(directory, &today)?;
+
+        // Write the log to both the console and the file
+        let file_log = macro_log!(
+            &quote_id,
+            &iso,
+            &LogLevel::INFO,
+            "process",
+            &message,
+            &LogFormat::CLF
         );
+        writeln!(log_file, "{}", file_log).map_err(log_error)?;
+    }
+    println!(
+        "- info:wiserone: add file at `{}`",
+        docs.join(filename).display()
+    );
+    Ok(())
+}
+
+/// Creates the HTML files of a stream of quotes.
+///
+/// Each quote gets the pages [`generate_html_file`] would write, but
+/// the layout is read once, the log gets one entry per page, and the
+/// index pages are pointed at today's page once all quotes are written.
+/// Quotes are written as they arrive, so a
+/// [`QuoteStream`](crate::stream::QuoteStream) is never held in memory
+/// and the time taken grows linearly with the number of quotes. When
+/// `archive` is set, only the text, author, date and address of each
+/// quote are kept, to rebuild the [archive](generate_archive) from them
+/// once all pages are written.
+///
+/// # Arguments
+///
+/// * `quotes` - The quotes, or the error that stops the stream.
+/// * `filename` - Returns the name of the file created for a quote.
+/// * `archive` - Whether `quotes` is the whole collection, so the
+///   archive should be rebuilt to list them. Leave it unset when only
+///   some quotes are written, to keep the archive of every quote.
+/// * `site` - The site the pages are published on.
+///
+/// # Returns
+///
+/// Returns the number of quotes written, or the first error met.
+pub fn generate_html_files<I, F>(
+    quotes: I,
+    filename: F,
+    archive: bool,
+    site: &SiteConfig,
+) -> Result<usize, WiseroneError>
+where
+    I: IntoIterator<Item = Result<Quote, QuoteError>>,
+    F: Fn(&Quote) -> String,
+{
+    let templates = read_layouts(&site.paths.layouts)?;
 
-        // Create the file path for the current day's file if it doesn't already exist
-        let today_file_path =
-            format!("./docs/{}.html", today_formatted);
+    let dt = DateTime::new();
+    let iso = dt.format_rfc3339()?;
+    let today = slug(&dt);
 
-        if Path::new(&today_file_path).exists() {
-            let content = fs::read_to_string(&today_file_path)?;
-            let index_path = Path::new("./docs/index.html");
-            fs::write(index_path, content.as_bytes())?;
+    let log_path = &site.paths.log;
+    let log_error = |err| WiseroneError::io(log_path, err);
+    let mut log_file = create_log_file(log_path)?;
 
-            // Write the log to both the console and the file
+    let mut directories = BTreeSet::new();
+    let mut entries = Vec::new();
+    let mut written = 0;
+    for quote in quotes {
+        let quote = quote?;
+        let filename = filename(&quote);
+        for directory in
+            write_pages(&templates, site, &filename, &quote, &today)?
+        {
             let file_log = macro_log!(
-                &Uuid::new_v4().to_string(),
+                &quote.stable_id(),
                 &iso,
                 &LogLevel::INFO,
                 "process",
                 &format!(
-                    "index.html updated with content from {}",
-                    today_file_path
+                    "The HTML File is created at `{}`.",
+                    directory.join(&filename).display()
                 ),
                 &LogFormat::CLF
             );
-            writeln!(log_file, "{}", file_log)?;
-        } else {
-            // Write the log to both the console and the file
+            writeln!(log_file, "{}", file_log).map_err(log_error)?;
+            let _ = directories.insert(directory);
+        }
+        if archive {
+            entries.extend(ArchiveEntry::new(&quote, &filename, site));
+        }
+        written += 1;
+    }
+
+    for directory in &directories {
+        let message = update_index(directory, &today)?;
+        let file_log = macro_log!(
+            "index",
+            &iso,
+            &LogLevel::INFO,
+            "process",
+            &message,
+            &LogFormat::CLF
+        );
+        writeln!(log_file, "{}", file_log).map_err(log_error)?;
+    }
+
+    if archive {
+        for page in generate_archive(&entries, site)? {
             let file_log = macro_log!(
-                &Uuid::new_v4().to_string(),
+                "archive",
                 &iso,
                 &LogLevel::INFO,
                 "process",
-                &format!("No file found at {}", today_file_path),
+                &format!(
+                    "The archive page is created at `{}`.",
+                    page.display()
+                ),
                 &LogFormat::CLF
             );
-            writeln!(log_file, "{}", file_log)?;
+            writeln!(log_file, "{}", file_log).map_err(log_error)?;
         }
     }
-    println!("- info:wiserone: add file at `{}`", path.display());
-    Ok(())
+    Ok(written)
+}
+
+/// Reads the page layouts in `directory`, and checks that the quote
+/// layout is there.
+fn read_layouts(directory: &Path) -> Result<Templates, WiseroneError> {
+    let templates = Templates::load(directory)?;
+    if !templates.contains(QUOTE_LAYOUT) {
+        let path = directory.join(QUOTE_LAYOUT);
+        return Err(WiseroneError::io(
+            &path,
+            std::io::ErrorKind::NotFound.into(),
+        ));
+    }
+    Ok(templates)
+}
+
+/// Writes every language version of the page for `quote`.
+///
+/// # Returns
+///
+/// Returns the directories the versions were written to, the primary
+/// one first.
+fn write_pages(
+    templates: &Templates,
+    site: &SiteConfig,
+    filename: &str,
+    quote: &Quote,
+    today: &str,
+) -> Result<Vec<PathBuf>, WiseroneError> {
+    // The page is named after a date or an id, never a path
+    if !filename.strip_suffix(".html").is_some_and(is_slug) {
+        return Err(WiseroneError::Template(format!(
+            "`{}` is not a safe page name",
+            filename
+        )));
+    }
+
+    // Determine if the page is the one published today
+    let is_today = filename == format!("{}.html", today);
+    let page = if is_today { "index.html" } else { filename };
+
+    // Quotes without a language are in the language of the site
+    let mut quote = quote.clone();
+    if quote.language.as_deref().map_or(true, |l| l.trim().is_empty()) {
+        quote.language = Some(site.locale.clone());
+    }
+
+    let docs = site.paths.output.as_path();
+    let mut versions = vec![Version {
+        quote: quote.clone(),
+        directory: docs.to_path_buf(),
+        home: site.home().to_string(),
+        url: site.page_url(page),
+    }];
+    for language in quote.translations.keys() {
+        if !is_language_tag(language) {
+            return Err(WiseroneError::Template(format!(
+                "`{}` is not a language tag",
+                language
+            )));
+        }
+        if let Some(translated) = quote.localized(language) {
+            versions.push(Version {
+                quote: translated,
+                directory: docs.join(language),
+                home: site.page_url(language),
+                url: site.page_url(&format!("{}/{}", language, page)),
+            });
+        }
+    }
+
+    // Link every version to the others, with the primary page as the
+    // default for other languages
+    let mut alternates: Vec<Value> = versions
+        .iter()
+        .map(|version| {
+            json!({
+                "hreflang": version.quote.primary_language(),
+                "url": version.url,
+            })
+        })
+        .collect();
+    alternates
+        .push(json!({"hreflang": "x-default", "url": versions[0].url}));
+
+    for version in &versions {
+        let directory = &version.directory;
+        fs::create_dir_all(directory)
+            .map_err(|err| WiseroneError::io(directory, err))?;
+        let page_path = directory.join(filename);
+        let page_error = |err| WiseroneError::io(&page_path, err);
+        let html = templates.render(
+            QUOTE_LAYOUT,
+            &page_context(site, version, &alternates)?,
+        )?;
+        let mut file =
+            fs::File::create(&page_path).map_err(page_error)?;
+        file.write_all(html.as_bytes()).map_err(page_error)?;
+    }
+
+    Ok(versions.into_iter().map(|version| version.directory).collect())
+}
+
+/// Copies the page published today, if there is one, to the
+/// `index.html` of `directory`.
+///
+/// # Returns
+///
+/// Returns the message to log.
+fn update_index(
+    directory: &Path,
+    today: &str,
+) -> Result<String, WiseroneError> {
+    let today_file_path = directory.join(format!("{}.html", today));
+    if !today_file_path.exists() {
+        return Ok(format!(
+            "No file found at {}",
+            today_file_path.display()
+        ));
+    }
+    let content = fs::read_to_string(&today_file_path)
+        .map_err(|err| WiseroneError::io(&today_file_path, err))?;
+    let index_path = directory.join("index.html");
+    fs::write(&index_path, content.as_bytes())
+        .map_err(|err| WiseroneError::io(&index_path, err))?;
+    Ok(format!(
+        "index.html updated with content from {}",
+        today_file_path.display()
+    ))
+}
+
+/// Builds the values the layouts can use for one language version of
+/// a page: `site` for the site configuration, `page` for this page, and
+/// `quote` for the quote as written in this language, with its stable
+/// `id`.
+fn page_context(
+    site: &SiteConfig,
+    version: &Version,
+    alternates: &[Value],
+) -> Result<Value, WiseroneError> {
+    let quote = &version.quote;
+    let language = quote.primary_language();
+
+    let mut quote_value = serde_json::to_value(quote)
+        .map_err(|err| WiseroneError::Template(err.to_string()))?;
+    quote_value["id"] = Value::String(quote.stable_id());
+
+    Ok(json!({
+        "site": site,
+        "page": {
+            "alternates": alternates,
+            "author": quote.author,
+            "home": version.home,
+            "language": language,
+            "locale": language.replace('-', "_"),
+            "title": quote.quote_text,
+            "url": version.url,
+        },
+        "quote": quote_value,
+    }))
 }
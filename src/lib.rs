@@ -18,17 +18,13 @@
 )]
 
 // Import necessary dependencies
-use std::error::Error;
-use std::fs::File;
-use std::io::Write;
-
-use dtt::datetime::DateTime;
-use rlg::log_format::LogFormat;
-use rlg::log_level::LogLevel;
-use rlg::macro_log;
-
+use crate::error::WiseroneError;
 use crate::loggers::init_logger;
 
+/// The `archive` module contains the functions that generate the
+/// archive pages listing every quote by year and month.
+pub mod archive;
+
 /// The `ascii` module contains functions for generating ASCII art.
 pub mod ascii;
 
@@ -36,6 +32,30 @@ pub mod ascii;
 /// input.
 pub mod cli;
 
+/// The `config` module contains the site configuration read from
+/// `wiserone.toml`.
+pub mod config;
+
+/// The `dates` module contains the typed quote date and the canonical
+/// date formatting helpers.
+pub mod dates;
+
+/// The `dedupe` module contains functions for finding duplicate and
+/// near-duplicate quotes.
+pub mod dedupe;
+
+/// The `error` module contains the error type shared by the whole
+/// crate.
+pub mod error;
+
+/// The `escape` module contains the functions that escape values for
+/// the place a layout writes them in a page.
+pub mod escape;
+
+/// The `fortune` module contains functions for reading and writing
+/// fortune(6) cookie files and their strfile(8) index.
+pub mod fortune;
+
 /// The `html` module contains functions for generating HTML files.
 pub mod html;
 
@@ -43,9 +63,44 @@ pub mod html;
 /// quotes.
 pub mod quotes;
 
+/// The `query` module contains functions for filtering quotes and for
+/// weighted selection.
+pub mod query;
+
+/// The `rotation` module contains the rotation policies and the
+/// selection history that keep quotes from repeating.
+pub mod rotation;
+
+/// The `schedule` module contains functions for assigning publishing
+/// dates to unscheduled quotes.
+pub mod schedule;
+
+/// The `schema` module contains the JSON Schema of quote datasets and
+/// the checks made against it while loading.
+pub mod schema;
+
+/// The `search` module contains functions for full-text search over
+/// quotes.
+pub mod search;
+
 /// The `sitemap` module contains functions for generating a sitemap.xml
 pub mod sitemap;
 
+/// The `stream` module contains iterators that read quotes one record
+/// at a time, for collections too large to load whole.
+pub mod stream;
+
+/// The `template` module contains the engine that renders the layouts
+/// in `_layouts`.
+pub mod template;
+
+/// The `validate` module contains functions for checking quote
+/// datasets before pages are generated.
+pub mod validate;
+
+/// The `writers` module contains functions for writing quote datasets.
+pub mod writers;
+
 /// The `loggers` module contains the loggers for the library.
 pub mod loggers;
 
@@ -57,32 +112,12 @@ pub mod macros;
 /// # Returns
 ///
 /// Returns `Ok(())` if successful, or an error if an operation fails.
-pub fn run() -> Result<(), Box<dyn Error>> {
+pub fn run() -> Result<(), WiseroneError> {
     // Initialize the logger using the `env_logger` crate
     init_logger(None)?;
 
-    // Define date and time
-    let date = DateTime::new();
-    let iso = date.format_rfc3339()?;
-
-    // Open the log file for appending
-    let mut log_file = File::create("./wiserone.log")?;
-
     // Call the `run_cli()` function from the `cli` module
     cli::run_cli()?;
 
-    // Generate a log entry
-    let quote_log = macro_log!(
-        "id",
-        &iso,
-        &LogLevel::INFO,
-        "process",
-        "Quote HTML file generated successfully.",
-        &LogFormat::CLF
-    );
-
-    // Write the log to both the console and the file
-    writeln!(log_file, "{}", quote_log)?;
-
     Ok(())
 }
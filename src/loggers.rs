@@ -3,11 +3,15 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 //! Application logging functionality
+use std::fs::{self, File};
 use std::io::Write;
+use std::path::Path;
 
 use env_logger::Env;
 use rlg::log_level::LogLevel;
 
+use crate::error::WiseroneError;
+
 /// Initializes the logging system.
 ///
 /// This function sets up the logging system using the `env_logger`
@@ -27,7 +31,7 @@ use rlg::log_level::LogLevel;
 /// ```
 pub fn init_logger(
     default_log_level: Option<LogLevel>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), WiseroneError> {
     let env = Env::default().default_filter_or(
         default_log_level.unwrap_or(LogLevel::INFO).to_string(),
     );
@@ -40,3 +44,20 @@ pub fn init_logger(
 
     Ok(())
 }
+
+/// Creates the log file at `path`, and the directories holding it.
+///
+/// # Returns
+///
+/// Returns the empty log file, or a `WiseroneError::Io` naming the path
+/// that could not be created.
+pub fn create_log_file(path: &Path) -> Result<File, WiseroneError> {
+    if let Some(directory) = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+    {
+        fs::create_dir_all(directory)
+            .map_err(|err| WiseroneError::io(directory, err))?;
+    }
+    File::create(path).map_err(|err| WiseroneError::io(path, err))
+}
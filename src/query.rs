@@ -0,0 +1,205 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::str::FromStr;
+
+use vrd::Random;
+
+use crate::dates::QuoteDate;
+use crate::quotes::{Quote, QuoteError, Quotes};
+
+/// The criteria a quote must meet to be selected.
+///
+/// Every criterion left unset matches all quotes, so the default filter
+/// keeps the whole collection. Text comparisons ignore case and
+/// surrounding whitespace.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QuoteFilter {
+    /// Keep quotes by this author.
+    pub author: Option<String>,
+    /// Keep quotes with at least one of these tags.
+    pub tags: Vec<String>,
+    /// Keep quotes in this language. `en` also matches `en-GB`.
+    pub language: Option<String>,
+    /// Keep quotes published on or after this day.
+    pub since: Option<QuoteDate>,
+    /// Keep quotes published on or before this day.
+    pub until: Option<QuoteDate>,
+    /// Keep quotes with at least this many characters.
+    pub min_length: Option<usize>,
+    /// Keep quotes with at most this many characters.
+    pub max_length: Option<usize>,
+}
+
+impl QuoteFilter {
+    /// Returns `true` if the filter sets no criterion, and so keeps
+    /// every quote.
+    pub fn is_empty(&self) -> bool {
+        self == &QuoteFilter::default()
+    }
+
+    /// Returns `true` if `quote` meets every criterion of the filter.
+    pub fn matches(&self, quote: &Quote) -> bool {
+        let length = quote.quote_text.trim().chars().count();
+        self.author
+            .as_ref()
+            .map_or(true, |author| same_text(&quote.author, author))
+            && (self.tags.is_empty()
+                || self.tags.iter().any(|wanted| {
+                    quote.tags.iter().any(|tag| same_text(tag, wanted))
+                }))
+            && self.language.as_ref().map_or(true, |wanted| {
+                quote.language.as_deref().is_some_and(|language| {
+                    language_matches(language, wanted)
+                })
+            })
+            && self.since.map_or(true, |since| {
+                quote
+                    .date_added
+                    .is_some_and(|date| date.days_since(&since) >= 0)
+            })
+            && self.until.map_or(true, |until| {
+                quote
+                    .date_added
+                    .is_some_and(|date| until.days_since(&date) >= 0)
+            })
+            && self.min_length.map_or(true, |min| length >= min)
+            && self.max_length.map_or(true, |max| length <= max)
+    }
+}
+
+/// Returns `true` if two texts are equal, ignoring case and
+/// surrounding whitespace.
+fn same_text(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Returns `true` if the language tag `language` is `wanted` or one of
+/// its regional variants.
+fn language_matches(language: &str, wanted: &str) -> bool {
+    let language = language.trim().to_lowercase();
+    let wanted = wanted.trim().to_lowercase();
+    language == wanted
+        || language
+            .strip_prefix(wanted.as_str())
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// A weight given to every quote carrying a tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagWeight {
+    /// The tag.
+    pub tag: String,
+    /// The weight multiplier of quotes with the tag.
+    pub weight: f64,
+}
+
+impl FromStr for TagWeight {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (tag, weight) = value.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid tag weight `{}` (expected tag=weight)",
+                value
+            )
+        })?;
+        let weight: f64 = weight.trim().parse().map_err(|_| {
+            format!("invalid weight `{}` for tag `{}`", weight, tag)
+        })?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!(
+                "weight for tag `{}` must be zero or more",
+                tag
+            ));
+        }
+        Ok(TagWeight { tag: tag.trim().to_string(), weight })
+    }
+}
+
+/// Returns the selection weight of a quote.
+///
+/// This is the quote's own `weight` (1 when unset), multiplied by the
+/// weight of each of its tags listed in `tag_weights`. Invalid weights
+/// count as zero.
+pub fn quote_weight(quote: &Quote, tag_weights: &[TagWeight]) -> f64 {
+    let mut weight = quote.weight.unwrap_or(1.0);
+    for tag_weight in tag_weights {
+        if quote.tags.iter().any(|tag| same_text(tag, &tag_weight.tag))
+        {
+            weight *= tag_weight.weight;
+        }
+    }
+    if weight.is_finite() && weight > 0.0 {
+        weight
+    } else {
+        0.0
+    }
+}
+
+impl Quotes {
+    /// Returns the quotes that match `filter`, in their original order.
+    pub fn matching<'a>(
+        &'a self,
+        filter: &'a QuoteFilter,
+    ) -> impl Iterator<Item = &'a Quote> + 'a {
+        self.quotes.iter().filter(move |quote| filter.matches(quote))
+    }
+
+    /// Returns a new collection holding only the quotes that match
+    /// `filter`.
+    ///
+    /// The result can be used with every selection API, e.g. to pick a
+    /// random quote by one author.
+    pub fn filter(&self, filter: &QuoteFilter) -> Quotes {
+        Quotes {
+            schema_version: self.schema_version,
+            quotes: self.matching(filter).cloned().collect(),
+        }
+    }
+
+    /// Selects a random quote, with each quote drawn in proportion to
+    /// its weight (see [`quote_weight`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `tag_weights` - Extra weights given to quotes by tag.
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Returns
+    ///
+    /// Returns the selected quote, or `QuoteError::NoQuotesAvailable` if
+    /// no quote has a positive weight.
+    pub fn select_weighted_with(
+        &self,
+        tag_weights: &[TagWeight],
+        rng: &mut Random,
+    ) -> Result<&Quote, QuoteError> {
+        let weights: Vec<f64> = self
+            .quotes
+            .iter()
+            .map(|quote| quote_weight(quote, tag_weights))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(QuoteError::NoQuotesAvailable);
+        }
+
+        let mut target = rng.f64() * total;
+        for (quote, weight) in self.quotes.iter().zip(&weights) {
+            if *weight > 0.0 && target < *weight {
+                return Ok(quote);
+            }
+            target -= weight;
+        }
+        // Rounding can leave `target` just above the last weight.
+        self.quotes
+            .iter()
+            .zip(&weights)
+            .rev()
+            .find(|(_, weight)| **weight > 0.0)
+            .map(|(quote, _)| quote)
+            .ok_or(QuoteError::NoQuotesAvailable)
+    }
+}
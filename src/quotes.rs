@@ -2,12 +2,37 @@
 // Copyright © 2024 The Wiser One. All rights reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::dates::QuoteDate;
+use crate::fortune::quotes_from_fortune;
+use crate::schema::{require_valid, SchemaError};
+use crate::stream::{csv_records, json_line_records};
 use csv;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::{error::Error, fmt, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use vrd::Random;
 
+/// The version of the quote dataset schema written by this crate.
+///
+/// Version 1 is the original four-field layout; version 2 adds the
+/// optional `id`, `tags`, `source`, `language`, `image_alt`, `notes`,
+/// `weight`, `translations` and free-form extra fields. Datasets without
+/// a `schema_version` marker get the oldest version that holds the
+/// fields they use.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The schema version of datasets that only use the original fields.
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 /// Struct representing a single quote.
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Quote {
@@ -15,66 +40,429 @@ pub struct Quote {
     pub quote_text: String,
     /// The author of the quote.
     pub author: String,
-    /// The date when the quote was added to the JSON file.
-    pub date_added: String,
+    /// The date on which the quote is published, or `None` while it
+    /// waits for [`Quotes::schedule`] to give it a day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_added: Option<QuoteDate>,
     /// The URL of the image associated with the quote.
     pub image_url: String,
+    /// An optional identifier for the quote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Free-form tags used to group quotes by theme.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where the quote comes from (book, speech, web page, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// The language of the quote text, as a BCP 47 tag (e.g. `en`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Alternative text describing the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_alt: Option<String>,
+    /// Editorial notes that are not published.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// How likely the quote is to be drawn by weighted selection,
+    /// relative to the default of 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    /// Translations of the quote, keyed by BCP 47 language tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, Translation>,
+    /// Any other fields present in the dataset, kept as-is.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
 }
 
-/// Struct representing a collection of quotes.
+impl Quote {
+    /// Returns the oldest schema version that holds every field the
+    /// quote sets: 1 if it only sets the original four fields, or
+    /// [`SCHEMA_VERSION`] otherwise. Version 1 has no unscheduled
+    /// quotes, so a quote without a date is version 2 too.
+    pub fn schema_version(&self) -> u32 {
+        let extended = self.date_added.is_none()
+            || self.id.is_some()
+            || !self.tags.is_empty()
+            || self.source.is_some()
+            || self.language.is_some()
+            || self.image_alt.is_some()
+            || self.notes.is_some()
+            || self.weight.is_some()
+            || !self.translations.is_empty()
+            || !self.extra.is_empty();
+        if extended {
+            SCHEMA_VERSION
+        } else {
+            legacy_schema_version()
+        }
+    }
+
+    /// Returns the stable identifier of the quote.
+    ///
+    /// This is the explicit `id` when the dataset sets one. Otherwise
+    /// the identifier is derived from a hash of the quote text and
+    /// author, so it survives edits to the date or image and is the
+    /// same on every run and every machine.
+    pub fn stable_id(&self) -> String {
+        match &self.id {
+            Some(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => {
+                let content = format!(
+                    "{}\u{1f}{}",
+                    self.quote_text.trim(),
+                    self.author.trim()
+                );
+                format!("q-{:016x}", fnv1a_64(content.as_bytes()))
+            }
+        }
+    }
+
+    /// Returns the day the quote is published on, as `YYYY-MM-DD`, or
+    /// `None` while it is unscheduled.
+    pub fn day(&self) -> Option<String> {
+        self.date_added.map(|date| date.iso_date())
+    }
+
+    /// Returns the language of the quote text, or
+    /// [`DEFAULT_LANGUAGE`] when the dataset does not set one.
+    pub fn primary_language(&self) -> &str {
+        match self.language.as_deref().map(str::trim) {
+            Some(language) if !language.is_empty() => language,
+            _ => DEFAULT_LANGUAGE,
+        }
+    }
+
+    /// Returns the quote as written in `language`.
+    ///
+    /// The primary language returns a copy of the quote itself. A
+    /// translation replaces the text, and the author and image
+    /// description when it has them, and sets `language`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the localized quote, or `None` if there is no
+    /// translation for `language`.
+    pub fn localized(&self, language: &str) -> Option<Quote> {
+        if language == self.primary_language() {
+            return Some(self.clone());
+        }
+        let translation = self.translations.get(language)?;
+        let mut quote = self.clone();
+        quote.quote_text = translation.quote_text.clone();
+        if let Some(author) = &translation.author {
+            quote.author = author.clone();
+        }
+        if translation.image_alt.is_some() {
+            quote.image_alt = translation.image_alt.clone();
+        }
+        quote.language = Some(language.to_string());
+        Some(quote)
+    }
+}
+
+/// The language of quotes that do not set one.
+pub const DEFAULT_LANGUAGE: &str = "en-GB";
+
+/// Returns whether `tag` looks like a BCP 47 language tag: subtags of
+/// one to eight ASCII letters or digits separated by hyphens, such as
+/// `fr` or `pt-BR`.
+///
+/// Language tags name page directories, so anything else (including
+/// `..` or a path separator) is rejected.
+pub fn is_language_tag(tag: &str) -> bool {
+    tag.split('-').all(|subtag| {
+        (1..=8).contains(&subtag.len())
+            && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// Returns whether `id` can identify a quote: one or more ASCII
+/// letters, digits, hyphens or underscores, such as `socrates-1`.
+///
+/// Ids can name page files, so anything else (including `..` or a path
+/// separator) is rejected.
+pub fn is_slug(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Rejects a quote whose explicit `id` is not a slug.
+pub(crate) fn check_id(quote: Quote) -> Result<Quote, QuoteError> {
+    match &quote.id {
+        Some(id) if !is_slug(id) => {
+            Err(QuoteError::InvalidId(id.clone()))
+        }
+        _ => Ok(quote),
+    }
+}
+
+/// A quote translated into another language.
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Translation {
+    /// The translated text of the quote.
+    pub quote_text: String,
+    /// The author's name, when it is written differently in the
+    /// language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// The translated description of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_alt: Option<String>,
+}
+
+/// Computes the 64-bit FNV-1a hash of `bytes`.
+///
+/// Unlike `std::collections::hash_map::DefaultHasher`, the result is
+/// specified and never changes between Rust releases, which makes it
+/// suitable for identifiers that are published.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Struct representing where a quote comes from.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Source {
+    /// The kind of source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<SourceKind>,
+    /// The title of the book, speech or page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A URL where the source can be found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// The kinds of source a quote can come from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// A book.
+    Book,
+    /// A speech or talk.
+    Speech,
+    /// An article, essay or letter.
+    Article,
+    /// An interview.
+    Interview,
+    /// A web page.
+    Web,
+    /// Anything else.
+    Other,
+}
+
+/// Struct representing a collection of quotes.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Quotes {
+    /// The schema version of the dataset.
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     /// Vector of `Quote` structs.
     pub quotes: Vec<Quote>,
 }
 
+impl Default for Quotes {
+    fn default() -> Self {
+        Quotes::new(Vec::new())
+    }
+}
+
 impl Quotes {
-    /// Initializes a new `Quotes` struct with the provided quotes.
+    /// Initializes a new `Quotes` struct with the provided quotes, at
+    /// the current schema version.
     ///
     /// # Arguments
     ///
     /// * `quotes` - A vector of `Quote` structs.
     pub fn new(quotes: Vec<Quote>) -> Self {
-        Quotes { quotes }
+        Quotes { schema_version: SCHEMA_VERSION, quotes }
+    }
+
+    /// Wraps quotes read from a dataset without a `schema_version`
+    /// marker, such as a CSV file, at the oldest version that holds
+    /// their fields: version 1, unless a quote sets a version 2 field.
+    pub(crate) fn unmarked(quotes: Vec<Quote>) -> Self {
+        let schema_version = quotes
+            .iter()
+            .map(Quote::schema_version)
+            .max()
+            .unwrap_or_else(legacy_schema_version);
+        Quotes { schema_version, quotes }
+    }
+
+    /// Looks up a quote by its stable identifier.
+    ///
+    /// Both explicit ids and ids derived from the quote content (see
+    /// [`Quote::stable_id`]) are matched.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching `Quote`, or `None` if no quote has that id.
+    pub fn get(&self, id: &str) -> Option<&Quote> {
+        self.quotes.iter().find(|quote| quote.stable_id() == id)
+    }
+
+    /// Returns the quote of the day for `date`.
+    ///
+    /// A quote whose `date_added` falls on the same calendar day is
+    /// returned when there is one. Otherwise a quote is picked by
+    /// rotation: the quotes are ordered by stable id and indexed by a
+    /// hash of the day, so the same date always yields the same quote
+    /// for a given collection, whatever order the records are in.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The day to pick a quote for.
+    ///
+    /// # Returns
+    ///
+    /// Returns the quote for that day, or
+    /// `QuoteError::NoQuotesAvailable` if the collection is empty.
+    pub fn quote_for_date(
+        &self,
+        date: &QuoteDate,
+    ) -> Result<&Quote, QuoteError> {
+        let day = date.iso_date();
+        if let Some(scheduled) = self
+            .quotes
+            .iter()
+            .find(|quote| quote.day().as_ref() == Some(&day))
+        {
+            return Ok(scheduled);
+        }
+
+        let mut rotation: Vec<(String, &Quote)> = self
+            .quotes
+            .iter()
+            .map(|quote| (quote.stable_id(), quote))
+            .collect();
+        if rotation.is_empty() {
+            return Err(QuoteError::NoQuotesAvailable);
+        }
+        rotation.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let index = fnv1a_64(day.as_bytes()) % rotation.len() as u64;
+        Ok(rotation[index as usize].1)
     }
 
     /// Selects a random quote.
     ///
+    /// The generator is seeded from the operating system, so each call
+    /// may return a different quote. Use
+    /// [`select_random_quote_seeded`](Quotes::select_random_quote_seeded)
+    /// or [`select_random_quote_with`](Quotes::select_random_quote_with)
+    /// for reproducible results.
+    ///
+    /// # Returns
+    ///
+    /// Returns a reference to a randomly selected `Quote`, or
+    /// `QuoteError::NoQuotesAvailable` if the collection is empty.
+    pub fn select_random_quote(&self) -> Result<&Quote, QuoteError> {
+        self.select_random_quote_with(&mut Random::new())
+    }
+
+    /// Selects a random quote using the given random number generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to draw from.
+    ///
     /// # Returns
     ///
-    /// Returns a reference to a randomly selected `Quote` or an error
-    /// if there are no quotes available.
-    pub fn select_random_quote(
-        &mut self,
-    ) -> Result<&Quote, Box<dyn Error>> {
+    /// Returns a reference to a randomly selected `Quote`, or
+    /// `QuoteError::NoQuotesAvailable` if the collection is empty.
+    pub fn select_random_quote_with(
+        &self,
+        rng: &mut Random,
+    ) -> Result<&Quote, QuoteError> {
         if self.quotes.is_empty() {
-            return Err("No available quotes".into());
+            return Err(QuoteError::NoQuotesAvailable);
         }
+        let index = rng.bounded(self.quotes.len() as u32) as usize;
+        Ok(&self.quotes[index])
+    }
 
-        // Random number generator.
-        let mut rng = Random::new();
-        // Random index selection.
-        let rand_index =
-            rng.int(0, self.quotes.len() as i32 - 1) as usize;
+    /// Selects a random quote reproducibly from a seed.
+    ///
+    /// The same seed always selects the same quote from the same
+    /// collection, which makes a selection easy to replay in a test or
+    /// a bug report.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the random number generator.
+    ///
+    /// # Returns
+    ///
+    /// Returns a reference to the selected `Quote`, or
+    /// `QuoteError::NoQuotesAvailable` if the collection is empty.
+    pub fn select_random_quote_seeded(
+        &self,
+        seed: u64,
+    ) -> Result<&Quote, QuoteError> {
+        self.select_random_quote_with(&mut Random::from_u64_seed(seed))
+    }
+
+    /// Draws up to `n` distinct quotes at random.
+    ///
+    /// Quotes are drawn without replacement, so no quote appears twice.
+    /// If `n` is larger than the collection, every quote is returned in
+    /// random order.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of quotes to draw.
+    /// * `rng` - The random number generator to draw from.
+    pub fn sample(&self, n: usize, rng: &mut Random) -> Vec<&Quote> {
+        let mut pool: Vec<&Quote> = self.quotes.iter().collect();
+        let n = n.min(pool.len());
+        // Partial Fisher-Yates: only the first `n` slots are settled.
+        for i in 0..n {
+            let j = i + rng.bounded((pool.len() - i) as u32) as usize;
+            pool.swap(i, j);
+        }
+        pool.truncate(n);
+        pool
+    }
 
-        Ok(&self.quotes[rand_index])
+    /// Returns an iterator over every quote in random order.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator used for the shuffle.
+    pub fn shuffled(
+        &self,
+        rng: &mut Random,
+    ) -> impl Iterator<Item = &Quote> {
+        let mut order: Vec<&Quote> = self.quotes.iter().collect();
+        rng.shuffle(&mut order);
+        order.into_iter()
     }
 
-    /// Selects all quotes, sorted by the date added.
+    /// Selects all quotes, sorted by the date added, with unscheduled
+    /// quotes first.
     ///
     /// # Returns
     ///
-    /// Returns all quotes or an error if no quotes are available.
-    pub fn select_all_quotes(
-        &self,
-    ) -> Result<Vec<&Quote>, Box<dyn Error>> {
+    /// Returns all quotes, or `QuoteError::NoQuotesAvailable` if the
+    /// collection is empty.
+    pub fn select_all_quotes(&self) -> Result<Vec<&Quote>, QuoteError> {
         if self.quotes.is_empty() {
-            return Err("No available quotes".into());
+            return Err(QuoteError::NoQuotesAvailable);
         }
 
         let mut sorted_quotes =
             self.quotes.iter().collect::<Vec<&Quote>>();
-        sorted_quotes.sort_by_key(|quote| &quote.date_added);
+        sorted_quotes.sort_by_key(|quote| quote.date_added);
 
         Ok(sorted_quotes)
     }
@@ -91,6 +479,32 @@ pub enum QuoteError {
 
     /// Error variant for when no quotes are available.
     NoQuotesAvailable,
+
+    /// Error variant for a date that is neither RFC 3339 nor ISO 8601.
+    InvalidDate(String),
+
+    /// Error variant for an explicit id that is not a slug.
+    InvalidId(String),
+
+    /// Error variant for datasets written with a newer schema version
+    /// than this crate understands.
+    UnsupportedSchemaVersion(u32),
+
+    /// Error variant for two records from merged files that share a
+    /// date or an id.
+    Conflict(Collision),
+
+    /// Error variant for quotes that cannot be written in the requested
+    /// format.
+    WriteError(String),
+
+    /// Error variant for more quotes to schedule than free days, with
+    /// the number of each.
+    NotEnoughDays(usize, usize),
+
+    /// Error variant for datasets that do not match the JSON Schema,
+    /// with every place that does not.
+    SchemaViolation(Vec<SchemaError>),
 }
 
 impl fmt::Display for QuoteError {
@@ -103,6 +517,47 @@ impl fmt::Display for QuoteError {
             QuoteError::NoQuotesAvailable => {
                 write!(f, "No Quotes Available")
             }
+            QuoteError::InvalidDate(value) => write!(
+                f,
+                "Invalid Date: `{}` is not an RFC 3339 or ISO 8601 date",
+                value
+            ),
+            QuoteError::InvalidId(id) => write!(
+                f,
+                "Invalid Id: `{}` may only hold ASCII letters, digits, \
+                 `-` and `_`",
+                id
+            ),
+            QuoteError::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "Unsupported schema version {} (expected at most {})",
+                version, SCHEMA_VERSION
+            ),
+            QuoteError::Conflict(collision) => {
+                write!(f, "Conflict: {}", collision)
+            }
+            QuoteError::WriteError(msg) => {
+                write!(f, "Write Error: {}", msg)
+            }
+            QuoteError::NotEnoughDays(quotes, days) => write!(
+                f,
+                "Not Enough Days: {} quote(s) to schedule but {} free \
+                 day(s)",
+                quotes, days
+            ),
+            QuoteError::SchemaViolation(errors) => {
+                write!(f, "Schema Error: ")?;
+                match errors.split_first() {
+                    Some((first, [])) => write!(f, "{}", first),
+                    Some((first, rest)) => write!(
+                        f,
+                        "{} (and {} more)",
+                        first,
+                        rest.len()
+                    ),
+                    None => write!(f, "invalid dataset"),
+                }
+            }
         }
     }
 }
@@ -113,6 +568,13 @@ impl Error for QuoteError {
             QuoteError::IOError(err) => Some(err),
             QuoteError::ParseError(_) => None,
             QuoteError::NoQuotesAvailable => None,
+            QuoteError::InvalidDate(_) => None,
+            QuoteError::InvalidId(_) => None,
+            QuoteError::UnsupportedSchemaVersion(_) => None,
+            QuoteError::Conflict(_) => None,
+            QuoteError::WriteError(_) => None,
+            QuoteError::NotEnoughDays(_, _) => None,
+            QuoteError::SchemaViolation(_) => None,
         }
     }
 }
@@ -125,21 +587,194 @@ impl From<std::io::Error> for QuoteError {
 
 impl From<serde_json::Error> for QuoteError {
     fn from(error: serde_json::Error) -> Self {
-        QuoteError::ParseError(error.to_string())
+        QuoteError::ParseError(format!("invalid JSON: {}", error))
     }
 }
 
 impl From<csv::Error> for QuoteError {
     fn from(error: csv::Error) -> Self {
-        QuoteError::ParseError(error.to_string())
+        QuoteError::ParseError(format!("invalid CSV: {}", error))
+    }
+}
+
+impl From<serde_yaml::Error> for QuoteError {
+    fn from(error: serde_yaml::Error) -> Self {
+        QuoteError::ParseError(format!("invalid YAML: {}", error))
+    }
+}
+
+impl From<toml::de::Error> for QuoteError {
+    fn from(error: toml::de::Error) -> Self {
+        QuoteError::ParseError(format!("invalid TOML: {}", error))
+    }
+}
+
+/// The formats a quote dataset can be written in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Format {
+    /// JSON, with a top-level `quotes` list.
+    Json,
+    /// CSV, with one quote per row and a header row.
+    Csv,
+    /// YAML, with the same shape as the JSON format.
+    Yaml,
+    /// TOML, with one `[[quotes]]` table per quote.
+    Toml,
+    /// JSON Lines, with one JSON quote object per line.
+    JsonLines,
+    /// A fortune(6) cookie file, with `%`-delimited quotes and
+    /// `-- Author` attribution lines.
+    Fortune,
+}
+
+impl Format {
+    /// Returns the format of a file extension, if it is supported.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The extension without the dot, e.g. `yml`.
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            "jsonl" | "ndjson" => Some(Format::JsonLines),
+            "fortune" => Some(Format::Fortune),
+            _ => None,
+        }
+    }
+
+    /// Returns the format of a file, inferred from its extension.
+    ///
+    /// # Returns
+    ///
+    /// Returns the format, or `QuoteError::ParseError` if the extension
+    /// is missing or not supported.
+    pub fn from_path(
+        path: impl AsRef<Path>,
+    ) -> Result<Format, QuoteError> {
+        path.as_ref()
+            .extension()
+            .and_then(|s| s.to_str())
+            .and_then(Format::from_extension)
+            .ok_or_else(|| {
+                QuoteError::ParseError("Unsupported file format".into())
+            })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Format::from_extension(value).ok_or_else(|| {
+            format!(
+                "unknown format `{}` (expected json, csv, yaml, toml, \
+                 jsonl or fortune)",
+                value
+            )
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Csv => write!(f, "csv"),
+            Format::Yaml => write!(f, "yaml"),
+            Format::Toml => write!(f, "toml"),
+            Format::JsonLines => write!(f, "jsonl"),
+            Format::Fortune => write!(f, "fortune"),
+        }
+    }
+}
+
+impl Quotes {
+    /// Parses quotes from a string in the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The dataset.
+    /// * `format` - The format the dataset is written in. Fortune files
+    ///   carry no dates, so their quotes are left unscheduled; use
+    ///   [`quotes_from_fortune`] to date them from a first day.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Quotes` struct if successful, or an error if the
+    /// dataset cannot be parsed or uses a newer schema version. JSON,
+    /// YAML and TOML datasets are checked against the
+    /// [JSON Schema](crate::schema::quotes_schema) first, and return
+    /// `QuoteError::SchemaViolation` if they do not match it.
+    pub fn from_str(
+        content: &str,
+        format: Format,
+    ) -> Result<Quotes, QuoteError> {
+        let quotes = match format {
+            Format::Json => {
+                quotes_from_value(serde_json::from_str(content)?)?
+            }
+            Format::Csv => quotes_from_csv(content)?,
+            Format::Yaml => {
+                quotes_from_value(serde_yaml::from_str(content)?)?
+            }
+            Format::Toml => {
+                quotes_from_value(toml::from_str(content)?)?
+            }
+            Format::JsonLines => quotes_from_json_lines(content)?,
+            Format::Fortune => quotes_from_fortune(content, None)?,
+        };
+        check_schema_version(quotes)
+    }
+}
+
+/// Decodes a JSON, YAML or TOML dataset once it matches the schema.
+///
+/// The schema version is checked first, so that datasets from a newer
+/// crate report their version rather than the fields they added.
+/// Datasets without a marker get the version of the fields they use,
+/// as in [`Quotes::unmarked`].
+fn quotes_from_value(
+    dataset: serde_json::Value,
+) -> Result<Quotes, QuoteError> {
+    let version =
+        dataset.get("schema_version").and_then(|v| v.as_u64());
+    if let Some(version) = version {
+        if version > u64::from(SCHEMA_VERSION) {
+            return Err(QuoteError::UnsupportedSchemaVersion(
+                u32::try_from(version).unwrap_or(u32::MAX),
+            ));
+        }
+    }
+    require_valid(&dataset)?;
+    let quotes: Quotes = serde_json::from_value(dataset)?;
+    Ok(match version {
+        Some(_) => quotes,
+        None => Quotes::unmarked(quotes.quotes),
+    })
+}
+
+/// Rejects datasets written with a newer schema than this crate knows.
+fn check_schema_version(quotes: Quotes) -> Result<Quotes, QuoteError> {
+    if quotes.schema_version > SCHEMA_VERSION {
+        return Err(QuoteError::UnsupportedSchemaVersion(
+            quotes.schema_version,
+        ));
     }
+    Ok(quotes)
 }
 
-/// Reads and parses quotes from a file (either JSON or CSV).
+/// Reads and parses quotes from a file (JSON, CSV, YAML, TOML, JSON
+/// Lines or fortune).
+///
+/// The format is inferred from the file extension: `.json`, `.csv`,
+/// `.yaml`/`.yml`, `.toml`, `.jsonl`/`.ndjson` or `.fortune`.
 ///
 /// # Arguments
 ///
-/// * `file_path` - Path to the file (JSON or CSV) containing quotes.
+/// * `file_path` - Path to the file containing quotes.
 ///
 /// # Returns
 ///
@@ -148,30 +783,491 @@ impl From<csv::Error> for QuoteError {
 pub fn read_quotes_from_file(
     file_path: &str,
 ) -> Result<Quotes, QuoteError> {
-    let path = Path::new(file_path);
-    match path.extension().and_then(|s| s.to_str()) {
-        Some("json") => read_quotes_from_json(file_path),
-        Some("csv") => read_quotes_from_csv(file_path),
-        _ => Err(QuoteError::ParseError(
-            "Unsupported file format".into(),
-        )),
-    }
+    let format = Format::from_path(file_path)?;
+    read_quotes_from_reader(fs::File::open(file_path)?, format)
 }
 
-/// Reads and parses quotes from a JSON file.
-fn read_quotes_from_json(
-    file_path: &str,
+/// Reads and parses quotes from any reader, such as standard input or
+/// a dataset embedded in a binary.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the dataset.
+/// * `format` - The format the dataset is written in.
+///
+/// # Returns
+///
+/// Returns a `Quotes` struct if successful, or an error if the dataset
+/// cannot be read or parsed.
+pub fn read_quotes_from_reader(
+    mut reader: impl Read,
+    format: Format,
 ) -> Result<Quotes, QuoteError> {
-    let file_content = fs::read_to_string(file_path)?;
-    let quotes: Quotes = serde_json::from_str(&file_content)?;
-    Ok(quotes)
+    let mut content = String::new();
+    let _ = reader.read_to_string(&mut content)?;
+    Quotes::from_str(&content, format)
+}
+
+/// Parses quotes from JSON Lines, skipping blank lines.
+fn quotes_from_json_lines(content: &str) -> Result<Quotes, QuoteError> {
+    let quotes = json_line_records(content.as_bytes())
+        .collect::<Result<_, _>>()?;
+    Ok(Quotes::unmarked(quotes))
+}
+
+/// Parses quotes from CSV.
+///
+/// CSV cannot nest values, so the richer fields are flattened into
+/// columns: `tags` is a `;`-separated list, `source` is split into
+/// `source_kind`, `source_title` and `source_url`, and any unknown
+/// column is kept in `extra`. Empty cells are treated as missing.
+fn quotes_from_csv(content: &str) -> Result<Quotes, QuoteError> {
+    let quotes =
+        csv_records(content.as_bytes()).collect::<Result<_, _>>()?;
+    Ok(Quotes::unmarked(quotes))
+}
+
+/// Converts a CSV row, keyed by column name, into a `Quote`.
+pub(crate) fn quote_from_csv_row(
+    mut row: BTreeMap<String, String>,
+) -> Result<Quote, QuoteError> {
+    let translations = translations_from_csv_row(&mut row)?;
+    // A missing or empty date leaves the quote unscheduled
+    let date_added = match row.remove("date_added") {
+        Some(date) if !date.trim().is_empty() => {
+            Some(QuoteDate::parse(date.trim())?)
+        }
+        _ => None,
+    };
+    let mut required = |column: &str| {
+        row.remove(column).ok_or_else(|| {
+            QuoteError::ParseError(format!(
+                "invalid CSV: missing field `{}`",
+                column
+            ))
+        })
+    };
+    let quote_text = required("quote_text")?;
+    let author = required("author")?;
+    let image_url = required("image_url")?;
+
+    let mut take = |column: &str| {
+        row.remove(column).filter(|value| !value.is_empty())
+    };
+
+    let source_kind = match take("source_kind") {
+        Some(kind) => Some(
+            serde_json::from_value(serde_json::Value::String(kind))
+                .map_err(|err| {
+                    QuoteError::ParseError(format!(
+                        "invalid CSV: source_kind: {}",
+                        err
+                    ))
+                })?,
+        ),
+        None => None,
+    };
+    let source_title = take("source_title");
+    let source_url = take("source_url");
+    let source = if source_kind.is_some()
+        || source_title.is_some()
+        || source_url.is_some()
+    {
+        Some(Source {
+            kind: source_kind,
+            title: source_title,
+            url: source_url,
+        })
+    } else {
+        None
+    };
+
+    let tags = take("tags")
+        .map(|tags| {
+            tags.split(';')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let weight = match take("weight") {
+        Some(weight) => Some(weight.trim().parse().map_err(|_| {
+            QuoteError::ParseError(format!(
+                "invalid CSV: weight: `{}` is not a number",
+                weight
+            ))
+        })?),
+        None => None,
+    };
+
+    check_id(Quote {
+        quote_text,
+        author,
+        date_added,
+        image_url,
+        id: take("id"),
+        tags,
+        source,
+        language: take("language"),
+        image_alt: take("image_alt"),
+        notes: take("notes"),
+        weight,
+        translations,
+        extra: row
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| (key, serde_json::Value::String(value)))
+            .collect(),
+    })
+}
+
+/// Takes the translation columns out of a CSV row.
+///
+/// Translations are written as one column per field and language:
+/// `quote_text.fr`, `author.fr` and `image_alt.fr`.
+fn translations_from_csv_row(
+    row: &mut BTreeMap<String, String>,
+) -> Result<BTreeMap<String, Translation>, QuoteError> {
+    let columns: Vec<String> = row
+        .keys()
+        .filter(|column| {
+            column.split_once('.').is_some_and(|(field, _)| {
+                matches!(field, "quote_text" | "author" | "image_alt")
+            })
+        })
+        .cloned()
+        .collect();
+
+    let mut translations: BTreeMap<String, Translation> =
+        BTreeMap::new();
+    for column in columns {
+        let value = row.remove(&column).unwrap_or_default();
+        if value.is_empty() {
+            continue;
+        }
+        let (field, language) =
+            column.split_once('.').unwrap_or_default();
+        let translation =
+            translations.entry(language.to_string()).or_default();
+        match field {
+            "quote_text" => translation.quote_text = value,
+            "author" => translation.author = Some(value),
+            _ => translation.image_alt = Some(value),
+        }
+    }
+
+    if let Some((language, _)) = translations
+        .iter()
+        .find(|(_, translation)| translation.quote_text.is_empty())
+    {
+        return Err(QuoteError::ParseError(format!(
+            "invalid CSV: missing field `quote_text.{}`",
+            language
+        )));
+    }
+    Ok(translations)
+}
+
+/// What to do when two merged records share a date or an id.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConflictPolicy {
+    /// Fail with `QuoteError::Conflict`.
+    #[default]
+    Error,
+    /// Keep the record that was read first.
+    FirstWins,
+    /// Keep the record that was read last.
+    LastWins,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "error" => Ok(ConflictPolicy::Error),
+            "first-wins" => Ok(ConflictPolicy::FirstWins),
+            "last-wins" => Ok(ConflictPolicy::LastWins),
+            _ => Err(format!(
+                "unknown conflict policy `{}` (expected error, \
+                 first-wins or last-wins)",
+                value
+            )),
+        }
+    }
+}
+
+/// Two records from merged files that share a date or an id.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Collision {
+    /// What the records share, e.g. `date 2024-01-01` or `id q-...`.
+    pub key: String,
+    /// The file of the record that was read first.
+    pub first: PathBuf,
+    /// The file of the record that was read last.
+    pub second: PathBuf,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} appears in both `{}` and `{}`",
+            self.key,
+            self.first.display(),
+            self.second.display()
+        )
+    }
+}
+
+/// The result of merging several quote files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MergedQuotes {
+    /// The merged quotes.
+    pub quotes: Quotes,
+    /// The collisions that were resolved by the conflict policy.
+    pub collisions: Vec<Collision>,
+}
+
+/// Merges quotes read from several files into one collection.
+///
+/// Records are considered the same entry when they share a publishing
+/// day or a stable id. Identical records (such as the JSON and CSV
+/// mirrors of one dataset) are merged silently; any other overlap is
+/// resolved with `policy` and reported in the result. The merged
+/// collection has the highest schema version of the files it came from,
+/// so it can hold every field they use.
+///
+/// # Arguments
+///
+/// * `sources` - The quotes of each file, with the file they came from,
+///   in the order they were read.
+/// * `policy` - What to do when two different records collide.
+///
+/// # Returns
+///
+/// Returns the merged quotes and the resolved collisions, or
+/// `QuoteError::Conflict` under `ConflictPolicy::Error`.
+pub fn merge_quotes(
+    sources: Vec<(PathBuf, Quotes)>,
+    policy: ConflictPolicy,
+) -> Result<MergedQuotes, QuoteError> {
+    // Removed entries are left as `None` so indices stay valid.
+    let mut merged: Vec<Option<(PathBuf, Quote)>> = Vec::new();
+    let mut by_day: HashMap<String, usize> = HashMap::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut collisions = Vec::new();
+    let schema_version = sources
+        .iter()
+        .map(|(_, quotes)| quotes.schema_version)
+        .max()
+        .unwrap_or(SCHEMA_VERSION);
+
+    for (path, quotes) in sources {
+        for quote in quotes.quotes {
+            let day = quote.day();
+            let id = quote.stable_id();
+
+            // Records already merged that share the day or the id,
+            // and the subset of them that differ from this one.
+            let mut matches: Vec<usize> = Vec::new();
+            let mut clashes = Vec::new();
+            // Unscheduled quotes have no day to share
+            for (key, index) in [
+                day.as_ref().map(|day| {
+                    (format!("date {}", day), by_day.get(day))
+                }),
+                Some((format!("id {}", id), by_id.get(&id))),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                let Some(&index) = index else { continue };
+                if matches.contains(&index) {
+                    continue;
+                }
+                matches.push(index);
+                if let Some((first, existing)) = &merged[index] {
+                    if existing != &quote {
+                        clashes.push(Collision {
+                            key,
+                            first: first.clone(),
+                            second: path.clone(),
+                        });
+                    }
+                }
+            }
+
+            if !matches.is_empty() && clashes.is_empty() {
+                // An identical copy of a record we already have.
+                continue;
+            }
+
+            match policy {
+                ConflictPolicy::Error => {
+                    if let Some(collision) = clashes.into_iter().next()
+                    {
+                        return Err(QuoteError::Conflict(collision));
+                    }
+                }
+                ConflictPolicy::FirstWins => {
+                    if !clashes.is_empty() {
+                        collisions.extend(clashes);
+                        continue;
+                    }
+                }
+                ConflictPolicy::LastWins => {
+                    for index in matches {
+                        if let Some((_, old)) = merged[index].take() {
+                            if let Some(day) = old.day() {
+                                let _ = by_day.remove(&day);
+                            }
+                            let _ = by_id.remove(&old.stable_id());
+                        }
+                    }
+                    collisions.extend(clashes);
+                }
+            }
+
+            if let Some(day) = day {
+                let _ = by_day.insert(day, merged.len());
+            }
+            let _ = by_id.insert(id, merged.len());
+            merged.push(Some((path.clone(), quote)));
+        }
+    }
+
+    Ok(MergedQuotes {
+        quotes: Quotes {
+            schema_version,
+            quotes: merged
+                .into_iter()
+                .flatten()
+                .map(|(_, quote)| quote)
+                .collect(),
+        },
+        collisions,
+    })
+}
+
+/// Reads and merges every supported quote file in a directory.
+///
+/// Files are read in name order and merged with [`merge_quotes`].
+/// Files with an extension other than `.json`, `.csv`, `.yaml`, `.yml`,
+/// `.toml`, `.jsonl`, `.ndjson` or `.fortune` are ignored.
+///
+/// # Arguments
+///
+/// * `dir_path` - Path to the directory containing quote files.
+/// * `policy` - What to do when two different records collide.
+///
+/// # Returns
+///
+/// Returns the merged quotes and the resolved collisions, or an error
+/// if a file cannot be read or parsed or a conflict is not allowed.
+pub fn read_quotes_from_dir(
+    dir_path: &str,
+    policy: ConflictPolicy,
+) -> Result<MergedQuotes, QuoteError> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir_path)? {
+        let path = entry?.path();
+        let supported = Format::from_path(&path).is_ok();
+        if path.is_file() && supported {
+            paths.push(path);
+        }
+    }
+    read_and_merge(paths, policy)
+}
+
+/// Reads and merges every quote file matching a glob pattern.
+///
+/// Only the file name may contain wildcards: `*` matches any run of
+/// characters and `?` matches a single character, as in
+/// `quotes/*-quotes.json`. Matching files are read in name order and
+/// merged with [`merge_quotes`].
+///
+/// # Arguments
+///
+/// * `pattern` - The glob pattern of the files to read.
+/// * `policy` - What to do when two different records collide.
+///
+/// # Returns
+///
+/// Returns the merged quotes and the resolved collisions, or an error
+/// if a file cannot be read or parsed or a conflict is not allowed.
+pub fn read_quotes_from_glob(
+    pattern: &str,
+    policy: ConflictPolicy,
+) -> Result<MergedQuotes, QuoteError> {
+    let pattern_path = Path::new(pattern);
+    let dir = match pattern_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_pattern = pattern_path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|s| s.to_str());
+        if path.is_file()
+            && name
+                .is_some_and(|name| wildcard_match(file_pattern, name))
+        {
+            paths.push(path);
+        }
+    }
+    read_and_merge(paths, policy)
 }
 
-/// Reads and parses quotes from a CSV file.
-fn read_quotes_from_csv(file_path: &str) -> Result<Quotes, QuoteError> {
-    let mut rdr = csv::Reader::from_path(file_path)?;
-    let quotes = rdr
-        .deserialize()
-        .collect::<Result<Vec<Quote>, csv::Error>>()?;
-    Ok(Quotes::new(quotes))
+/// Reads each file in name order and merges the results.
+fn read_and_merge(
+    mut paths: Vec<PathBuf>,
+    policy: ConflictPolicy,
+) -> Result<MergedQuotes, QuoteError> {
+    paths.sort();
+    let mut sources = Vec::with_capacity(paths.len());
+    for path in paths {
+        let quotes = read_quotes_from_file(&path.to_string_lossy())?;
+        sources.push((path, quotes));
+    }
+    merge_quotes(sources, policy)
+}
+
+/// Matches `name` against a pattern where `*` matches any run of
+/// characters and `?` matches exactly one.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it resumed from.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, resume)) => {
+                    p = star + 1;
+                    n = resume + 1;
+                    backtrack = Some((star, resume + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
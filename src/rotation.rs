@@ -0,0 +1,240 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::{HashMap, HashSet};
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use vrd::Random;
+
+use crate::dates::QuoteDate;
+use crate::quotes::{Quote, QuoteError, Quotes};
+
+/// How the next quote is chosen from the selection history.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RotationPolicy {
+    /// Draw every quote once, in random order, before any repeats.
+    #[default]
+    ShuffleBag,
+    /// Pick a quote that has never been shown, or else the one shown
+    /// the longest time ago.
+    LeastRecentlyShown,
+    /// Pick at random among the quotes not shown in the last `n` days,
+    /// falling back to the least recently shown quote when every quote
+    /// has been shown within the window.
+    NoRepeatWithin(u32),
+}
+
+impl FromStr for RotationPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "shuffle-bag" => Ok(RotationPolicy::ShuffleBag),
+            "least-recent" => Ok(RotationPolicy::LeastRecentlyShown),
+            _ => value
+                .strip_prefix("no-repeat:")
+                .and_then(|days| days.parse().ok())
+                .map(RotationPolicy::NoRepeatWithin)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown rotation policy `{}` (expected \
+                         shuffle-bag, least-recent or no-repeat:<days>)",
+                        value
+                    )
+                }),
+        }
+    }
+}
+
+impl fmt::Display for RotationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotationPolicy::ShuffleBag => write!(f, "shuffle-bag"),
+            RotationPolicy::LeastRecentlyShown => {
+                write!(f, "least-recent")
+            }
+            RotationPolicy::NoRepeatWithin(days) => {
+                write!(f, "no-repeat:{}", days)
+            }
+        }
+    }
+}
+
+/// A quote that was published, and when.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Publication {
+    /// The stable id of the quote.
+    pub id: String,
+    /// The day the quote was published.
+    pub date: QuoteDate,
+}
+
+/// The record of which quotes were published when.
+///
+/// The history is kept in a small JSON file between runs, so that
+/// repeated runs walk through the whole collection before repeating.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct History {
+    /// Every publication, oldest first.
+    #[serde(default)]
+    pub published: Vec<Publication>,
+    /// The ids still to be drawn by the shuffle-bag policy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bag: Vec<String>,
+}
+
+impl History {
+    /// Loads the history from a JSON file.
+    ///
+    /// A missing file is not an error: it yields an empty history, as
+    /// on the very first run.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the history file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, QuoteError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Ok(History::default())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the history to a JSON file, replacing its content.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the history file.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), QuoteError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    /// Records that the quote with `id` was published on `date`.
+    pub fn record(&mut self, id: String, date: QuoteDate) {
+        self.published.push(Publication { id, date });
+    }
+
+    /// Returns the day each quote id was last published.
+    pub fn last_shown(&self) -> HashMap<&str, QuoteDate> {
+        let mut last = HashMap::new();
+        for publication in &self.published {
+            let day = last
+                .entry(publication.id.as_str())
+                .or_insert(publication.date);
+            if publication.date > *day {
+                *day = publication.date;
+            }
+        }
+        last
+    }
+}
+
+impl Quotes {
+    /// Picks the next quote according to a rotation policy, and records
+    /// it in `history` as published on `date`.
+    ///
+    /// Quotes are identified by their stable id, so the history stays
+    /// valid when records are reordered or new quotes are added. Ids in
+    /// the history that are no longer in the collection are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The selection history, updated in place.
+    /// * `policy` - How to choose among the quotes.
+    /// * `date` - The day the quote is published.
+    /// * `rng` - The random number generator to draw from.
+    ///
+    /// # Returns
+    ///
+    /// Returns the selected quote, or `QuoteError::NoQuotesAvailable` if
+    /// the collection is empty.
+    pub fn next_in_rotation(
+        &self,
+        history: &mut History,
+        policy: RotationPolicy,
+        date: QuoteDate,
+        rng: &mut Random,
+    ) -> Result<&Quote, QuoteError> {
+        if self.quotes.is_empty() {
+            return Err(QuoteError::NoQuotesAvailable);
+        }
+        let ids: Vec<String> =
+            self.quotes.iter().map(Quote::stable_id).collect();
+
+        let index = match policy {
+            RotationPolicy::ShuffleBag => {
+                draw_from_bag(&ids, history, rng)
+            }
+            RotationPolicy::LeastRecentlyShown => {
+                least_recently_shown(&ids, history, rng)
+            }
+            RotationPolicy::NoRepeatWithin(days) => {
+                let last = history.last_shown();
+                let fresh: Vec<usize> = (0..ids.len())
+                    .filter(|&i| match last.get(ids[i].as_str()) {
+                        Some(shown) => {
+                            date.days_since(shown) >= i64::from(days)
+                        }
+                        None => true,
+                    })
+                    .collect();
+                if fresh.is_empty() {
+                    least_recently_shown(&ids, history, rng)
+                } else {
+                    fresh[rng.bounded(fresh.len() as u32) as usize]
+                }
+            }
+        };
+
+        history.record(ids[index].clone(), date);
+        Ok(&self.quotes[index])
+    }
+}
+
+/// Draws the next id from the shuffle bag, refilling it with every id
+/// in random order once it is empty.
+fn draw_from_bag(
+    ids: &[String],
+    history: &mut History,
+    rng: &mut Random,
+) -> usize {
+    let known: HashSet<&str> = ids.iter().map(String::as_str).collect();
+    history.bag.retain(|id| known.contains(id.as_str()));
+    if history.bag.is_empty() {
+        let mut bag: Vec<String> =
+            known.iter().map(|id| id.to_string()).collect();
+        // Sort first so that the shuffle alone decides the order.
+        bag.sort();
+        rng.shuffle(&mut bag);
+        history.bag = bag;
+    }
+
+    let id = history.bag.remove(0);
+    ids.iter()
+        .position(|candidate| *candidate == id)
+        .unwrap_or_default()
+}
+
+/// Returns the index of a quote that was never shown, or else of the
+/// quote shown the longest time ago. Ties are broken at random.
+fn least_recently_shown(
+    ids: &[String],
+    history: &History,
+    rng: &mut Random,
+) -> usize {
+    let last = history.last_shown();
+    let oldest = ids.iter().map(|id| last.get(id.as_str())).min();
+    let candidates: Vec<usize> = (0..ids.len())
+        .filter(|&i| Some(last.get(ids[i].as_str())) == oldest)
+        .collect();
+    candidates[rng.bounded(candidates.len() as u32) as usize]
+}
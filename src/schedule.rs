@@ -0,0 +1,309 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::{fmt, str::FromStr};
+
+use crate::dates::QuoteDate;
+use crate::fortune::quotes_from_fortune;
+use crate::quotes::{
+    check_id, quote_from_csv_row, Format, Quote, QuoteError, Quotes,
+    SCHEMA_VERSION,
+};
+
+/// How unscheduled quotes are spread over the free days.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SchedulePolicy {
+    /// Give the free days to the quotes in the order they are listed.
+    #[default]
+    Sequential,
+    /// Take one quote from each author in turn, so the same author
+    /// does not fill several days in a row.
+    RoundRobinByAuthor,
+    /// Give each free day to the quote whose tags were last used the
+    /// longest time before or after it.
+    TagSpacing,
+}
+
+impl FromStr for SchedulePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sequential" => Ok(SchedulePolicy::Sequential),
+            "round-robin" => Ok(SchedulePolicy::RoundRobinByAuthor),
+            "tag-spacing" => Ok(SchedulePolicy::TagSpacing),
+            _ => Err(format!(
+                "unknown schedule policy `{}` (expected sequential, \
+                 round-robin or tag-spacing)",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SchedulePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulePolicy::Sequential => write!(f, "sequential"),
+            SchedulePolicy::RoundRobinByAuthor => {
+                write!(f, "round-robin")
+            }
+            SchedulePolicy::TagSpacing => write!(f, "tag-spacing"),
+        }
+    }
+}
+
+impl Quotes {
+    /// Returns the days from `from` to `to`, inclusive, on which no
+    /// quote is published.
+    ///
+    /// Days are compared by calendar day, and keep the time of `from`.
+    pub fn free_days(
+        &self,
+        from: QuoteDate,
+        to: QuoteDate,
+    ) -> Result<Vec<QuoteDate>, QuoteError> {
+        let taken: HashSet<String> =
+            self.quotes.iter().filter_map(Quote::day).collect();
+        let mut free = Vec::new();
+        for day in 0..=to.days_since(&from) {
+            let date = from.add_days(day)?;
+            if !taken.contains(&date.iso_date()) {
+                free.push(date);
+            }
+        }
+        Ok(free)
+    }
+
+    /// Removes and returns the quotes that have no day yet, so they
+    /// can be scheduled.
+    pub fn take_unscheduled(&mut self) -> Vec<Quote> {
+        let (unscheduled, scheduled) = std::mem::take(&mut self.quotes)
+            .into_iter()
+            .partition(|quote| quote.date_added.is_none());
+        self.quotes = scheduled;
+        unscheduled
+    }
+
+    /// Removes and returns the quotes published on a day already taken
+    /// by an earlier quote in the collection, so they can be scheduled
+    /// again.
+    pub fn take_collisions(&mut self) -> Vec<Quote> {
+        let mut days = HashSet::new();
+        let mut collisions = Vec::new();
+        self.quotes.retain(|quote| {
+            if quote.day().map_or(true, |day| days.insert(day)) {
+                true
+            } else {
+                collisions.push(quote.clone());
+                false
+            }
+        });
+        collisions
+    }
+
+    /// Gives each unscheduled quote a free day between `from` and `to`
+    /// and adds it to the collection.
+    ///
+    /// Quotes already in the collection keep their dates; take those
+    /// without one out first with [`Quotes::take_unscheduled`]. The
+    /// dates of `unscheduled` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `unscheduled` - The quotes to schedule.
+    /// * `from` - The first day that may be used.
+    /// * `to` - The last day that may be used.
+    /// * `policy` - The order in which quotes take the free days.
+    ///
+    /// # Returns
+    ///
+    /// Returns the newly scheduled quotes, in date order, or
+    /// `QuoteError::NotEnoughDays` (leaving the collection unchanged)
+    /// if there are more quotes than free days.
+    pub fn schedule(
+        &mut self,
+        unscheduled: Vec<Quote>,
+        from: QuoteDate,
+        to: QuoteDate,
+        policy: SchedulePolicy,
+    ) -> Result<&[Quote], QuoteError> {
+        let free = self.free_days(from, to)?;
+        if unscheduled.len() > free.len() {
+            return Err(QuoteError::NotEnoughDays(
+                unscheduled.len(),
+                free.len(),
+            ));
+        }
+
+        let ordered = match policy {
+            SchedulePolicy::Sequential => unscheduled,
+            SchedulePolicy::RoundRobinByAuthor => {
+                round_robin_by_author(unscheduled)
+            }
+            SchedulePolicy::TagSpacing => {
+                self.spread_tags(unscheduled, &free, &from)
+            }
+        };
+
+        let start = self.quotes.len();
+        for (mut quote, date) in ordered.into_iter().zip(free) {
+            quote.date_added = Some(date);
+            self.quotes.push(quote);
+        }
+        Ok(&self.quotes[start..])
+    }
+
+    /// Orders quotes so that each free day goes to the remaining quote
+    /// whose tags are furthest from their nearest use.
+    fn spread_tags(
+        &self,
+        mut remaining: Vec<Quote>,
+        free: &[QuoteDate],
+        from: &QuoteDate,
+    ) -> Vec<Quote> {
+        // The days, counted from `from`, on which each tag is used.
+        let mut used: HashMap<String, Vec<i64>> = HashMap::new();
+        for quote in &self.quotes {
+            let Some(date) = quote.date_added else { continue };
+            let day = date.days_since(from);
+            for tag in &quote.tags {
+                used.entry(tag_key(tag)).or_default().push(day);
+            }
+        }
+
+        let mut ordered = Vec::with_capacity(remaining.len());
+        for date in free.iter().take(remaining.len()) {
+            let day = date.days_since(from);
+            let spacing = |quote: &Quote| {
+                quote
+                    .tags
+                    .iter()
+                    .filter_map(|tag| used.get(&tag_key(tag)))
+                    .flatten()
+                    .map(|&other| (day - other).abs())
+                    .min()
+                    .unwrap_or(i64::MAX)
+            };
+            // `max_by_key` keeps the last of equal keys, so search from
+            // the end to prefer the first listed quote on ties.
+            let best = (0..remaining.len())
+                .rev()
+                .max_by_key(|&index| spacing(&remaining[index]))
+                .unwrap_or_default();
+            let quote = remaining.remove(best);
+            for tag in &quote.tags {
+                used.entry(tag_key(tag)).or_default().push(day);
+            }
+            ordered.push(quote);
+        }
+        ordered
+    }
+}
+
+/// Returns the key under which a tag is compared, ignoring case and
+/// surrounding whitespace.
+fn tag_key(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Interleaves quotes by author, taking authors in the order they
+/// first appear.
+fn round_robin_by_author(quotes: Vec<Quote>) -> Vec<Quote> {
+    let mut authors: Vec<VecDeque<Quote>> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for quote in quotes {
+        let key = quote.author.trim().to_lowercase();
+        let position = *positions.entry(key).or_insert_with(|| {
+            authors.push(VecDeque::new());
+            authors.len() - 1
+        });
+        authors[position].push_back(quote);
+    }
+
+    let mut ordered = Vec::new();
+    while authors.iter().any(|quotes| !quotes.is_empty()) {
+        for quotes in &mut authors {
+            if let Some(quote) = quotes.pop_front() {
+                ordered.push(quote);
+            }
+        }
+    }
+    ordered
+}
+
+/// Parses quotes waiting to be scheduled.
+///
+/// Works like [`Quotes::from_str`], except that `date_added` may also
+/// be empty, and is dropped from every quote since
+/// [`Quotes::schedule`] will set it.
+///
+/// # Arguments
+///
+/// * `content` - The dataset.
+/// * `format` - The format the dataset is written in.
+///
+/// # Returns
+///
+/// Returns the quotes, or an error if the dataset cannot be parsed.
+pub fn unscheduled_from_str(
+    content: &str,
+    format: Format,
+) -> Result<Vec<Quote>, QuoteError> {
+    let records: Vec<serde_json::Value> = match format {
+        Format::Fortune => {
+            return Ok(quotes_from_fortune(content, None)?.quotes);
+        }
+        Format::Csv => {
+            let mut rdr = csv::Reader::from_reader(content.as_bytes());
+            let mut quotes = Vec::new();
+            for row in rdr.deserialize() {
+                let mut row: BTreeMap<String, String> = row?;
+                let _ = row.remove("date_added");
+                quotes.push(quote_from_csv_row(row)?);
+            }
+            return Ok(quotes);
+        }
+        Format::JsonLines => content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+        Format::Json | Format::Yaml | Format::Toml => {
+            let dataset: serde_json::Value = match format {
+                Format::Json => serde_json::from_str(content)?,
+                Format::Yaml => serde_yaml::from_str(content)?,
+                _ => toml::from_str(content)?,
+            };
+            if let Some(version) =
+                dataset.get("schema_version").and_then(|v| v.as_u64())
+            {
+                if version > u64::from(SCHEMA_VERSION) {
+                    return Err(QuoteError::UnsupportedSchemaVersion(
+                        u32::try_from(version).unwrap_or(u32::MAX),
+                    ));
+                }
+            }
+            match dataset.get("quotes").and_then(|v| v.as_array()) {
+                Some(records) => records.clone(),
+                None => {
+                    return Err(QuoteError::ParseError(
+                        "missing `quotes` list".into(),
+                    ))
+                }
+            }
+        }
+    };
+
+    records
+        .into_iter()
+        .map(|mut record| {
+            if let Some(fields) = record.as_object_mut() {
+                let _ = fields.remove("date_added");
+            }
+            check_id(serde_json::from_value(record)?)
+        })
+        .collect()
+}
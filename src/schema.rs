@@ -0,0 +1,514 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde_json::{json, Map, Value};
+
+use crate::dates::QuoteDate;
+use crate::quotes::{is_language_tag, is_slug, QuoteError};
+
+/// The address the schema is published at, used as its `$id`.
+pub const SCHEMA_ID: &str =
+    "https://wiserone.com/schemas/quotes.schema.json";
+
+/// The pattern of quote ids, which [`is_slug`] matches.
+const ID_PATTERN: &str = "^[A-Za-z0-9_-]+$";
+
+/// The pattern of language tags, which [`is_language_tag`] matches.
+const LANGUAGE_TAG_PATTERN: &str =
+    "^[A-Za-z0-9]{1,8}(-[A-Za-z0-9]{1,8})*$";
+
+/// A place where a dataset does not match the schema.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaError {
+    /// The JSON pointer to the offending value, such as
+    /// `/quotes/3/date_added`. The empty pointer is the whole dataset.
+    pub pointer: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer =
+            if self.pointer.is_empty() { "/" } else { &self.pointer };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Returns the JSON Schema (draft 2020-12) of a quote dataset.
+///
+/// The schema describes what [`Quotes`](crate::quotes::Quotes) and
+/// [`Quote`](crate::quotes::Quote) accept when read from JSON, YAML or
+/// TOML: optional fields may be `null`, and unknown quote fields are
+/// allowed since they are kept as extra fields.
+///
+/// The rules that [`Quotes::validate`](crate::quotes::Quotes::validate)
+/// applies to a single value, such as the form of an `id`, a
+/// translation language or a `weight`, are part of the schema too, so
+/// editors flag them as they are typed.
+pub fn quotes_schema() -> Value {
+    let text = |description: &str| json!({ "type": "string", "description": description });
+    let optional = |description: &str| json!({ "type": ["string", "null"], "description": description });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": SCHEMA_ID,
+        "title": "The Wiser One quotes",
+        "description": "A collection of quotes published one per day.",
+        "type": "object",
+        "properties": {
+            "$schema": text("The schema the dataset is written against."),
+            "schema_version": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "The schema version of the dataset; 1 when left out."
+            },
+            "quotes": {
+                "type": "array",
+                "description": "The quotes.",
+                "items": { "$ref": "#/$defs/quote" }
+            }
+        },
+        "required": ["quotes"],
+        "$defs": {
+            "quote": {
+                "type": "object",
+                "description": "A single quote.",
+                "properties": {
+                    "quote_text": text("The text of the quote."),
+                    "author": text("The author of the quote."),
+                    "date_added": {
+                        "type": ["string", "null"],
+                        "description": "The day the quote is published, as an ISO 8601 date or an RFC 3339 date and time. Left out while the quote waits for `schedule` to give it a day.",
+                        "anyOf": [
+                            { "format": "date" },
+                            { "format": "date-time" }
+                        ]
+                    },
+                    "image_url": text("The URL of the image associated with the quote."),
+                    "id": {
+                        "type": ["string", "null"],
+                        "description": "An optional identifier for the quote, made of ASCII letters, digits, `-` and `_`.",
+                        "pattern": ID_PATTERN
+                    },
+                    "tags": {
+                        "type": "array",
+                        "description": "Free-form tags used to group quotes by theme.",
+                        "items": { "type": "string" }
+                    },
+                    "source": {
+                        "type": ["object", "null"],
+                        "description": "Where the quote comes from.",
+                        "properties": {
+                            "kind": {
+                                "type": ["string", "null"],
+                                "enum": [
+                                    "book", "speech", "article",
+                                    "interview", "web", "other", null
+                                ],
+                                "description": "The kind of source."
+                            },
+                            "title": optional("The title of the book, speech or page."),
+                            "url": optional("A URL where the source can be found.")
+                        }
+                    },
+                    "language": optional("The language of the quote text, as a BCP 47 tag."),
+                    "image_alt": optional("Alternative text describing the image."),
+                    "notes": optional("Editorial notes that are not published."),
+                    "weight": {
+                        "type": ["number", "null"],
+                        "minimum": 0,
+                        "description": "How likely the quote is to be drawn by weighted selection, relative to the default of 1."
+                    },
+                    "translations": {
+                        "type": "object",
+                        "description": "Translations of the quote, keyed by BCP 47 language tag.",
+                        "propertyNames": { "pattern": LANGUAGE_TAG_PATTERN },
+                        "additionalProperties": { "$ref": "#/$defs/translation" }
+                    }
+                },
+                "required": ["quote_text", "author", "image_url"]
+            },
+            "translation": {
+                "type": "object",
+                "description": "A quote translated into another language.",
+                "properties": {
+                    "quote_text": text("The translated text of the quote."),
+                    "author": optional("The author's name, when it is written differently in the language."),
+                    "image_alt": optional("The translated description of the image.")
+                },
+                "required": ["quote_text"]
+            }
+        }
+    })
+}
+
+/// Checks a dataset against [`quotes_schema`].
+///
+/// # Arguments
+///
+/// * `dataset` - The dataset, parsed from JSON, YAML or TOML.
+///
+/// # Returns
+///
+/// Returns every place where the dataset does not match the schema, in
+/// document order; an empty list means the dataset is valid.
+pub fn check_dataset(dataset: &Value) -> Vec<SchemaError> {
+    let schema = schema();
+    let mut errors = Vec::new();
+    check(schema, schema, dataset, &Location::Root, &mut errors);
+    errors
+}
+
+/// Checks a dataset against the schema before it is decoded, so that
+/// mistakes are reported with their location.
+pub(crate) fn require_valid(dataset: &Value) -> Result<(), QuoteError> {
+    into_result(check_dataset(dataset))
+}
+
+/// Checks a single record of the `quotes` list, found at `index`, for
+/// readers that decode a dataset one quote at a time.
+pub(crate) fn require_valid_quote(
+    record: &Value,
+    index: usize,
+) -> Result<(), QuoteError> {
+    let schema = schema();
+    let mut errors = Vec::new();
+    let quotes = Location::Key(&Location::Root, "quotes");
+    let location = Location::Index(&quotes, index);
+    check(
+        schema,
+        &schema["$defs"]["quote"],
+        record,
+        &location,
+        &mut errors,
+    );
+    into_result(errors)
+}
+
+/// Checks a top-level field of a dataset other than `quotes`, for
+/// readers that decode a dataset one field at a time.
+pub(crate) fn require_valid_field(
+    name: &str,
+    value: &Value,
+) -> Result<(), QuoteError> {
+    let schema = schema();
+    let mut errors = Vec::new();
+    if let Some(property) = schema["properties"].get(name) {
+        let location = Location::Key(&Location::Root, name);
+        check(schema, property, value, &location, &mut errors);
+    }
+    into_result(errors)
+}
+
+/// Returns the schema, built on first use.
+fn schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(quotes_schema)
+}
+
+/// Turns the errors found by a check into a result.
+fn into_result(errors: Vec<SchemaError>) -> Result<(), QuoteError> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(QuoteError::SchemaViolation(errors))
+    }
+}
+
+/// The place in a dataset being checked.
+///
+/// Locations are only written out as JSON pointers when an error is
+/// reported, so checking a valid dataset does not build any strings.
+#[derive(Clone, Copy, Debug)]
+enum Location<'a> {
+    /// The whole dataset.
+    Root,
+    /// A property of an object.
+    Key(&'a Location<'a>, &'a str),
+    /// An item of an array.
+    Index(&'a Location<'a>, usize),
+}
+
+impl Location<'_> {
+    /// Returns the JSON pointer to the location.
+    fn pointer(&self) -> String {
+        match self {
+            Location::Root => String::new(),
+            Location::Key(parent, key) => {
+                format!("{}/{}", parent.pointer(), escape(key))
+            }
+            Location::Index(parent, index) => {
+                format!("{}/{}", parent.pointer(), index)
+            }
+        }
+    }
+
+    /// Returns the error `message` at the location.
+    fn error(&self, message: String) -> SchemaError {
+        SchemaError { pointer: self.pointer(), message }
+    }
+}
+
+/// Checks `value`, found at `location`, against `schema`.
+///
+/// Only the keywords used by [`quotes_schema`] are supported.
+fn check(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    location: &Location<'_>,
+    errors: &mut Vec<SchemaError>,
+) {
+    let Some(keywords) = schema.as_object() else {
+        return;
+    };
+
+    // A value of the wrong type would fail every other keyword too
+    if let Some(expected) = keywords.get("type") {
+        let types = match expected {
+            Value::Array(types) => types.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        let matches = types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| has_type(value, name));
+        if !matches {
+            let names: Vec<&str> =
+                types.iter().filter_map(Value::as_str).collect();
+            errors.push(location.error(format!(
+                "expected {}, found {}",
+                names.join(" or "),
+                type_name(value)
+            )));
+            return;
+        }
+    }
+
+    for (keyword, argument) in keywords {
+        match (keyword.as_str(), argument) {
+            ("$ref", Value::String(reference)) => {
+                if let Some(name) = reference.strip_prefix("#/$defs/") {
+                    check(
+                        root,
+                        &root["$defs"][name],
+                        value,
+                        location,
+                        errors,
+                    );
+                }
+            }
+            ("enum", Value::Array(allowed))
+                if !allowed.contains(value) =>
+            {
+                let names: Vec<String> = allowed
+                    .iter()
+                    .filter(|allowed| !allowed.is_null())
+                    .map(|allowed| format!("`{}`", display(allowed)))
+                    .collect();
+                errors.push(location.error(format!(
+                    "`{}` is not one of {}",
+                    display(value),
+                    names.join(", ")
+                )));
+            }
+            ("minimum", Value::Number(minimum)) => {
+                let minimum = minimum.as_f64().unwrap_or(f64::MIN);
+                if value.as_f64().is_some_and(|number| number < minimum)
+                {
+                    errors.push(location.error(format!(
+                        "must be at least {}",
+                        minimum
+                    )));
+                }
+            }
+            ("format", Value::String(format)) => {
+                if let Some(text) = value.as_str() {
+                    if !has_format(text, format) {
+                        errors.push(location.error(format!(
+                            "`{}` is not a valid {}",
+                            text, format
+                        )));
+                    }
+                }
+            }
+            ("pattern", Value::String(pattern)) => {
+                if let Some(text) = value.as_str() {
+                    if !matches_pattern(text, pattern) {
+                        errors.push(location.error(format!(
+                            "`{}` does not match `{}`",
+                            text, pattern
+                        )));
+                    }
+                }
+            }
+            ("anyOf", Value::Array(branches)) => {
+                let matches = branches.iter().any(|branch| {
+                    let mut branch_errors = Vec::new();
+                    check(
+                        root,
+                        branch,
+                        value,
+                        location,
+                        &mut branch_errors,
+                    );
+                    branch_errors.is_empty()
+                });
+                if !matches {
+                    errors.push(
+                        location.error(any_of_message(branches, value)),
+                    );
+                }
+            }
+            ("items", items) => {
+                for (index, item) in
+                    value.as_array().into_iter().flatten().enumerate()
+                {
+                    let location = Location::Index(location, index);
+                    check(root, items, item, &location, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        check_object(root, keywords, object, location, errors);
+    }
+}
+
+/// Returns the message for a value that matches none of the branches
+/// of an `anyOf`.
+fn any_of_message(branches: &[Value], value: &Value) -> String {
+    let formats: Vec<&str> = branches
+        .iter()
+        .filter_map(|branch| branch.get("format"))
+        .filter_map(Value::as_str)
+        .collect();
+    if formats.len() == branches.len() {
+        format!(
+            "`{}` is not a valid {}",
+            display(value),
+            formats.join(" or ")
+        )
+    } else {
+        "does not match any of the allowed forms".to_string()
+    }
+}
+
+/// Checks the properties of an object against `schema`.
+fn check_object(
+    root: &Value,
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    location: &Location<'_>,
+    errors: &mut Vec<SchemaError>,
+) {
+    for required in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(required) {
+            errors.push(location.error(format!(
+                "missing required property `{}`",
+                required
+            )));
+        }
+    }
+
+    let properties =
+        schema.get("properties").and_then(Value::as_object);
+    for (key, value) in object {
+        let location = Location::Key(location, key);
+        if let Some(names) = schema.get("propertyNames") {
+            let name = Value::String(key.clone());
+            check(root, names, &name, &location, errors);
+        }
+        match properties.and_then(|properties| properties.get(key)) {
+            Some(property) => {
+                check(root, property, value, &location, errors)
+            }
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(location.error(format!(
+                        "unexpected property `{}`",
+                        key
+                    )))
+                }
+                Some(additional @ Value::Object(_)) => {
+                    check(root, additional, value, &location, errors)
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Returns `true` if `value` is of the JSON Schema type `name`.
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+/// Returns the JSON Schema type name of `value`.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Returns `true` if `text` is written in the string format `format`.
+/// Unknown formats are accepted, as the specification requires.
+fn has_format(text: &str, format: &str) -> bool {
+    let has_time = text.contains(['T', 't']);
+    match format {
+        "date" => !has_time && QuoteDate::parse(text).is_ok(),
+        "date-time" => has_time && QuoteDate::parse(text).is_ok(),
+        _ => true,
+    }
+}
+
+/// Returns whether `text` matches `pattern`. Only the patterns used by
+/// [`quotes_schema`] are known; others match anything.
+fn matches_pattern(text: &str, pattern: &str) -> bool {
+    match pattern {
+        ID_PATTERN => is_slug(text),
+        LANGUAGE_TAG_PATTERN => is_language_tag(text),
+        _ => true,
+    }
+}
+
+/// Shows a value in a message, without quotes around strings.
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Escapes a property name for use in a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
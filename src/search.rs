@@ -0,0 +1,212 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::quotes::{Quote, Quotes};
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+
+/// BM25 document length normalisation.
+const B: f64 = 0.75;
+
+/// A quote matching a search, with its relevance score.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchHit<'a> {
+    /// The matching quote.
+    pub quote: &'a Quote,
+    /// How well the quote matches; higher is better.
+    pub score: f64,
+}
+
+/// One part of a search query.
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    /// A word that must match a whole token.
+    Word(String),
+    /// A word that must start a token, written `word*`.
+    Prefix(String),
+    /// Words that must follow each other, written `"two words"`.
+    Phrase(Vec<String>),
+}
+
+impl Term {
+    /// Returns how many times the term occurs in `tokens`.
+    fn count(&self, tokens: &[String]) -> usize {
+        match self {
+            Term::Word(word) => {
+                tokens.iter().filter(|token| *token == word).count()
+            }
+            Term::Prefix(prefix) => tokens
+                .iter()
+                .filter(|token| token.starts_with(prefix.as_str()))
+                .count(),
+            Term::Phrase(words) => tokens
+                .windows(words.len())
+                .filter(|window| window == words)
+                .count(),
+        }
+    }
+}
+
+impl Quotes {
+    /// Searches the text and author of every quote.
+    ///
+    /// Words in `query` are matched whole, `word*` matches any word
+    /// starting with `word` and `"several words"` matches the words in
+    /// that order. Case and diacritics are ignored, so `cafe` finds
+    /// `Café`. A quote matches if any part of the query does; results
+    /// are ranked with BM25, so rare words and repeated matches count
+    /// more, and ties keep the order of the collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search terms.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching quotes, best match first.
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        let terms = parse_query(query);
+        if terms.is_empty() || self.quotes.is_empty() {
+            return Vec::new();
+        }
+
+        let documents: Vec<Vec<String>> = self
+            .quotes
+            .iter()
+            .map(|quote| {
+                let mut tokens = tokenize(&quote.quote_text);
+                tokens.extend(tokenize(&quote.author));
+                tokens
+            })
+            .collect();
+        let count = documents.len() as f64;
+        let average_length = documents
+            .iter()
+            .map(|tokens| tokens.len() as f64)
+            .sum::<f64>()
+            / count;
+
+        let mut scores = vec![0.0; documents.len()];
+        for term in &terms {
+            let frequencies: Vec<usize> = documents
+                .iter()
+                .map(|tokens| term.count(tokens))
+                .collect();
+            let matching =
+                frequencies.iter().filter(|&&tf| tf > 0).count() as f64;
+            let idf = ((count - matching + 0.5) / (matching + 0.5)
+                + 1.0)
+                .ln();
+
+            for (index, &tf) in frequencies.iter().enumerate() {
+                if tf == 0 {
+                    continue;
+                }
+                let tf = tf as f64;
+                let length = documents[index].len() as f64;
+                let norm = if average_length > 0.0 {
+                    1.0 - B + B * length / average_length
+                } else {
+                    1.0
+                };
+                scores[index] +=
+                    idf * tf * (K1 + 1.0) / (tf + K1 * norm);
+            }
+        }
+
+        let mut hits: Vec<SearchHit<'_>> = self
+            .quotes
+            .iter()
+            .zip(scores)
+            .filter(|(_, score)| *score > 0.0)
+            .map(|(quote, score)| SearchHit { quote, score })
+            .collect();
+        // `sort_by` is stable, so ties keep the collection order.
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+}
+
+/// Splits a query into words, prefixes and quoted phrases.
+fn parse_query(query: &str) -> Vec<Term> {
+    let mut terms = Vec::new();
+    for (index, part) in query.split('"').enumerate() {
+        if index % 2 == 1 {
+            // Inside quotes.
+            let words = tokenize(part);
+            match words.len() {
+                0 => {}
+                1 => terms.extend(words.into_iter().map(Term::Word)),
+                _ => terms.push(Term::Phrase(words)),
+            }
+            continue;
+        }
+        for word in part.split_whitespace() {
+            let prefix = word.ends_with('*');
+            for token in tokenize(word) {
+                terms.push(if prefix {
+                    Term::Prefix(token)
+                } else {
+                    Term::Word(token)
+                });
+            }
+        }
+    }
+    terms
+}
+
+/// Splits text into folded words.
+fn tokenize(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Lowercases text and strips the diacritics of Latin letters.
+pub(crate) fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => {
+                folded.push('a')
+            }
+            'æ' => folded.push_str("ae"),
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => folded.push('c'),
+            'ď' | 'đ' | 'ð' => folded.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => {
+                folded.push('e')
+            }
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => folded.push('g'),
+            'ĥ' | 'ħ' => folded.push('h'),
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => {
+                folded.push('i')
+            }
+            'ĵ' => folded.push('j'),
+            'ķ' => folded.push('k'),
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => folded.push('l'),
+            'ñ' | 'ń' | 'ņ' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => {
+                folded.push('o')
+            }
+            'œ' => folded.push_str("oe"),
+            'ŕ' | 'ŗ' | 'ř' => folded.push('r'),
+            'ś' | 'ŝ' | 'ş' | 'š' => folded.push('s'),
+            'ß' => folded.push_str("ss"),
+            'ţ' | 'ť' | 'ŧ' => folded.push('t'),
+            'þ' => folded.push_str("th"),
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű'
+            | 'ų' => folded.push('u'),
+            'ŵ' => folded.push('w'),
+            'ý' | 'ÿ' | 'ŷ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            // Combining marks left by decomposed input.
+            '\u{300}'..='\u{36f}' => {}
+            c => folded.push(c),
+        }
+    }
+    folded
+}
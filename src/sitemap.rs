@@ -2,58 +2,118 @@
 // Copyright © 2024 The Wiser One. All rights reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use crate::archive::ARCHIVE_DIRECTORY;
+use crate::dates::QuoteDate;
+use crate::error::WiseroneError;
+use crate::quotes::is_language_tag;
 use dtt::datetime::DateTime;
-use std::error::Error;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Generates a sitemap.xml file for all HTML files in the docs folder.
+/// A page listed in the sitemap.
+#[derive(Debug)]
+struct Page {
+    /// The file name of the page, shared by its language versions.
+    file_name: String,
+    /// The language of the page.
+    hreflang: String,
+    /// The address of the page.
+    url: String,
+    /// Whether the page is at the top of the output directory rather
+    /// than in a language directory.
+    primary: bool,
+}
+
+/// Generates a sitemap.xml file for all HTML files in `output`.
+///
+/// Pages in language directories such as `<output>/fr`, and the
+/// archive pages under `<output>/archive`, are listed too. Versions of
+/// the same page in several languages get
+/// `<xhtml:link rel="alternate" hreflang>` entries pointing at each
+/// other, with the page at the top of `output` as the `x-default`.
+/// The archive pages, and pages without a `lang`, are in `locale`.
+///
+/// # Arguments
+///
+/// * `base_url` - The address pages are published under, ending in `/`.
+/// * `output` - The directory holding the pages, where the sitemap is
+///   written.
+/// * `locale` - The language of the site, such as `en-GB`.
+///
+/// # Errors
+///
+/// Returns `WiseroneError::Io` if the output directory cannot be listed
+/// or the sitemap cannot be written, and `WiseroneError::Sitemap` if a
+/// page has a file name that cannot appear in a URL.
 pub fn generate_sitemap_file(
     base_url: &str,
-) -> Result<(), Box<dyn Error>> {
-    let docs_path = Path::new("./docs");
-    let mut urls = Vec::new();
-
-    // Obtain the current date and time in ISO 8601 format using dtt
-    let dt = DateTime::new();
-    let iso = dt.format_rfc3339()?;
-    let year_str = dt.year();
-    let month_str = &iso[5..7];
-    let day_str = dt.day();
-    let hour_str = dt.hour().to_string();
-    let minute_str = dt.minute().to_string();
-    let second_str = dt.second().to_string();
-    let offset = dt.offset();
-
-    // Construct the ISO 8601 date and time string
-    let iso_8601 = format!(
-        "{}-{}-{}T{}:{}:{}{}",
-        year_str,
-        month_str,
-        day_str,
-        hour_str,
-        minute_str,
-        second_str,
-        offset
-    );
+    output: &Path,
+    locale: &str,
+) -> Result<(), WiseroneError> {
+    let mut pages = Vec::new();
 
-    // Current date and time in ISO 8601 format using dtt
-    let current_iso_date = iso_8601;
+    // Current date and time in RFC 3339 format
+    let current_iso_date = QuoteDate::from(DateTime::new()).to_string();
 
-    // Collect HTML filenames
-    if docs_path.exists() {
-        for entry in fs::read_dir(docs_path)? {
-            let path = entry?.path();
-            if path.is_file()
-                && path.extension().and_then(|s| s.to_str())
-                    == Some("html")
-            {
-                let file_name =
-                    path.file_name().unwrap().to_str().unwrap();
-                urls.push(format!("{}{}", base_url, file_name));
+    // Collect HTML filenames, then those of each language
+    if output.exists() {
+        for (file_name, path) in html_files(output)? {
+            let hreflang = page_language(&path)
+                .unwrap_or_else(|| locale.to_string());
+            let url = format!("{}{}", base_url, file_name);
+            pages.push(Page {
+                file_name,
+                hreflang,
+                url,
+                primary: true,
+            });
+        }
+        let list_error = |err| WiseroneError::io(output, err);
+        for entry in fs::read_dir(output).map_err(list_error)? {
+            let path = entry.map_err(list_error)?.path();
+            let Some(language) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| {
+                    *name != ARCHIVE_DIRECTORY && is_language_tag(name)
+                })
+            else {
+                continue;
+            };
+            if !path.is_dir() {
+                continue;
+            }
+            for (file_name, _) in html_files(&path)? {
+                let url =
+                    format!("{}{}/{}", base_url, language, file_name);
+                pages.push(Page {
+                    file_name,
+                    hreflang: language.to_string(),
+                    url,
+                    primary: false,
+                });
             }
         }
+        let archive = output.join(ARCHIVE_DIRECTORY);
+        if archive.is_dir() {
+            for file_name in nested_html_files(output, &archive)? {
+                pages.push(Page {
+                    url: format!("{}{}", base_url, file_name),
+                    file_name,
+                    hreflang: locale.to_string(),
+                    primary: true,
+                });
+            }
+        }
+    }
+    pages.sort_by(|a, b| a.url.cmp(&b.url));
+
+    // Group the language versions of each page
+    let mut versions: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+    for page in &pages {
+        versions.entry(page.file_name.as_str()).or_default().push(page);
     }
 
     // Start the XML string with namespaces
@@ -67,9 +127,28 @@ pub fn generate_sitemap_file(
     sitemap_xml += "xmlns:video=\"http://www.google.com/schemas/sitemap-video/1.1\">\n";
 
     // Add URLs to the sitemap with changefreq and dynamic lastmod
-    for url in urls {
-        sitemap_xml
-            .push_str(&format!("  <url>\n    <loc>{}</loc>\n", url));
+    for page in &pages {
+        sitemap_xml.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n",
+            page.url
+        ));
+        let alternates = &versions[page.file_name.as_str()];
+        if alternates.len() > 1 {
+            for alternate in alternates {
+                sitemap_xml.push_str(&alternate_link(
+                    &alternate.hreflang,
+                    &alternate.url,
+                ));
+            }
+            if let Some(primary) =
+                alternates.iter().find(|alternate| alternate.primary)
+            {
+                sitemap_xml.push_str(&alternate_link(
+                    "x-default",
+                    &primary.url,
+                ));
+            }
+        }
         sitemap_xml.push_str("    <changefreq>weekly</changefreq>\n");
         sitemap_xml.push_str(&format!(
             "    <lastmod>{}</lastmod>\n",
@@ -82,8 +161,87 @@ pub fn generate_sitemap_file(
     sitemap_xml.push_str("</urlset>");
 
     // Write the sitemap to a file
-    let mut file = fs::File::create("./docs/sitemap.xml")?;
-    file.write_all(sitemap_xml.as_bytes())?;
+    let sitemap_path = output.join("sitemap.xml");
+    let write_error = |err| WiseroneError::io(&sitemap_path, err);
+    let mut file =
+        fs::File::create(&sitemap_path).map_err(write_error)?;
+    file.write_all(sitemap_xml.as_bytes()).map_err(write_error)?;
 
     Ok(())
 }
+
+/// Returns an `<xhtml:link>` entry for a language version of a page.
+fn alternate_link(hreflang: &str, url: &str) -> String {
+    format!(
+        "    <xhtml:link rel=\"alternate\" hreflang=\"{}\" href=\"{}\"/>\n",
+        hreflang, url
+    )
+}
+
+/// Lists the HTML files directly inside `directory`, with their paths.
+fn html_files(
+    directory: &Path,
+) -> Result<Vec<(String, PathBuf)>, WiseroneError> {
+    let list_error = |err| WiseroneError::io(directory, err);
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory).map_err(list_error)? {
+        let path = entry.map_err(list_error)?.path();
+        if path.is_file()
+            && path.extension().and_then(|s| s.to_str()) == Some("html")
+        {
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| {
+                    WiseroneError::Sitemap(format!(
+                        "`{}` is not a valid UTF-8 file name",
+                        path.display()
+                    ))
+                })?
+                .to_string();
+            files.push((file_name, path));
+        }
+    }
+    Ok(files)
+}
+
+/// Lists the HTML files inside `directory` and its subdirectories, by
+/// their path from `output` with `/` between the parts.
+fn nested_html_files(
+    output: &Path,
+    directory: &Path,
+) -> Result<Vec<String>, WiseroneError> {
+    let list_error = |err| WiseroneError::io(directory, err);
+    let mut files = Vec::new();
+    for (_, path) in html_files(directory)? {
+        let relative = path.strip_prefix(output).unwrap_or(&path);
+        let parts: Option<Vec<&str>> = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect();
+        files.push(parts.map(|parts| parts.join("/")).ok_or_else(
+            || {
+                WiseroneError::Sitemap(format!(
+                    "`{}` is not a valid UTF-8 file name",
+                    path.display()
+                ))
+            },
+        )?);
+    }
+    for entry in fs::read_dir(directory).map_err(list_error)? {
+        let path = entry.map_err(list_error)?.path();
+        if path.is_dir() {
+            files.extend(nested_html_files(output, &path)?);
+        }
+    }
+    Ok(files)
+}
+
+/// Reads the language of a page from its `<html lang="...">` tag.
+fn page_language(path: &Path) -> Option<String> {
+    let html = fs::read_to_string(path).ok()?;
+    let start = html.find("<html lang=\"")? + "<html lang=\"".len();
+    let length = html[start..].find('"')?;
+    Some(html[start..start + length].to_string())
+        .filter(|language| is_language_tag(language))
+}
@@ -0,0 +1,470 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+
+use serde_json::Value;
+
+use crate::quotes::{
+    check_id, quote_from_csv_row, read_quotes_from_reader, Format,
+    Quote, QuoteError, Quotes, SCHEMA_VERSION,
+};
+use crate::schema::{
+    require_valid_field, require_valid_quote, SchemaError,
+};
+
+/// An iterator over the quotes of a dataset, read one record at a time.
+///
+/// CSV, JSON Lines and JSON datasets are decoded as they are read, so
+/// only one record is held in memory however large the dataset is.
+/// YAML, TOML and fortune datasets have no record-by-record reader and
+/// are loaded whole before their quotes are handed out.
+///
+/// Each item is a quote or the error that stopped the stream; no item
+/// follows an error. A JSON dataset is checked in the order it is
+/// written: when its `schema_version` follows the `quotes` list, the
+/// records come out before an unsupported version stops the stream.
+pub struct QuoteStream<'a> {
+    records: Box<dyn Iterator<Item = Result<Quote, QuoteError>> + 'a>,
+}
+
+impl fmt::Debug for QuoteStream<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuoteStream").finish_non_exhaustive()
+    }
+}
+
+impl Iterator for QuoteStream<'_> {
+    type Item = Result<Quote, QuoteError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next()
+    }
+}
+
+impl From<Quotes> for QuoteStream<'_> {
+    fn from(quotes: Quotes) -> Self {
+        QuoteStream {
+            records: Box::new(quotes.quotes.into_iter().map(Ok)),
+        }
+    }
+}
+
+/// Opens a quotes file for streaming.
+///
+/// The format is inferred from the file extension, as in
+/// [`read_quotes_from_file`](crate::quotes::read_quotes_from_file).
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the file containing quotes.
+///
+/// # Returns
+///
+/// Returns the stream, or an error if the file cannot be opened.
+pub fn stream_quotes_from_file(
+    file_path: &str,
+) -> Result<QuoteStream<'static>, QuoteError> {
+    let format = Format::from_path(file_path)?;
+    stream_quotes_from_reader(fs::File::open(file_path)?, format)
+}
+
+/// Streams quotes from any reader, such as standard input.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the dataset.
+/// * `format` - The format the dataset is written in.
+///
+/// # Returns
+///
+/// Returns the stream, or an error if a YAML, TOML or fortune dataset
+/// cannot be read or parsed.
+pub fn stream_quotes_from_reader<'a>(
+    reader: impl Read + 'a,
+    format: Format,
+) -> Result<QuoteStream<'a>, QuoteError> {
+    let records: Box<dyn Iterator<Item = _> + 'a> = match format {
+        Format::Csv => Box::new(csv_records(reader)),
+        Format::JsonLines => Box::new(json_line_records(reader)),
+        Format::Json => Box::new(JsonRecords::new(reader)),
+        Format::Yaml | Format::Toml | Format::Fortune => {
+            return Ok(read_quotes_from_reader(reader, format)?.into());
+        }
+    };
+    Ok(QuoteStream { records })
+}
+
+/// Decodes the rows of a CSV dataset as they are read.
+pub(crate) fn csv_records(
+    reader: impl Read,
+) -> impl Iterator<Item = Result<Quote, QuoteError>> {
+    csv::Reader::from_reader(reader)
+        .into_deserialize::<BTreeMap<String, String>>()
+        .map(|row| quote_from_csv_row(row?))
+}
+
+/// Decodes the lines of a JSON Lines dataset as they are read,
+/// skipping blank lines.
+pub(crate) fn json_line_records(
+    reader: impl Read,
+) -> impl Iterator<Item = Result<Quote, QuoteError>> {
+    BufReader::new(reader).lines().enumerate().filter_map(
+        |(number, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if line.trim().is_empty() {
+                return None;
+            }
+            Some(
+                serde_json::from_str(&line)
+                    .map_err(|err| {
+                        QuoteError::ParseError(format!(
+                            "invalid JSON Lines: line {}: {}",
+                            number + 1,
+                            err
+                        ))
+                    })
+                    .and_then(check_id),
+            )
+        },
+    )
+}
+
+/// Where [`JsonRecords`] is in the dataset.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum JsonState {
+    /// Before the opening brace of the dataset.
+    Start,
+    /// Before the name of a top-level field.
+    Field,
+    /// After the value of a top-level field.
+    AfterField,
+    /// Inside the `quotes` list, before a record.
+    Quote,
+    /// After the end of the dataset or an error.
+    Done,
+}
+
+/// Decodes the `quotes` list of a JSON dataset one record at a time.
+///
+/// The reader only looks at the structure of the dataset: each record,
+/// and each other top-level field, is collected as raw bytes and handed
+/// to `serde_json`, then checked against the schema like a dataset
+/// loaded whole. Fields are checked as they are reached, so a
+/// `schema_version` written after the `quotes` list is only rejected
+/// once its records have been handed out.
+#[derive(Debug)]
+struct JsonRecords<R> {
+    reader: BufReader<R>,
+    /// The number of bytes read so far, for error messages.
+    offset: usize,
+    state: JsonState,
+    /// The index of the next record in the `quotes` list.
+    index: usize,
+    /// Whether the dataset has a `quotes` list.
+    has_quotes: bool,
+}
+
+impl<R: Read> JsonRecords<R> {
+    fn new(reader: R) -> Self {
+        JsonRecords {
+            reader: BufReader::new(reader),
+            offset: 0,
+            state: JsonState::Start,
+            index: 0,
+            has_quotes: false,
+        }
+    }
+
+    /// Reads up to the next record, or to the end of the dataset.
+    fn advance(&mut self) -> Result<Option<Quote>, QuoteError> {
+        loop {
+            match self.state {
+                JsonState::Start => {
+                    self.expect(b'{', "`{`")?;
+                    self.state = if self.peek_token()? == Some(b'}') {
+                        JsonState::AfterField
+                    } else {
+                        JsonState::Field
+                    };
+                }
+                JsonState::Field => {
+                    self.skip_whitespace()?;
+                    let key = self.read_value()?;
+                    let key: String = serde_json::from_slice(&key)
+                        .map_err(|_| self.error("a field name"))?;
+                    self.expect(b':', "`:`")?;
+                    if key == "quotes" {
+                        self.expect(b'[', "the `quotes` list")?;
+                        self.has_quotes = true;
+                        self.state = if self.peek_token()? == Some(b']')
+                        {
+                            self.bump()?;
+                            JsonState::AfterField
+                        } else {
+                            JsonState::Quote
+                        };
+                    } else {
+                        self.skip_whitespace()?;
+                        let value: Value = serde_json::from_slice(
+                            &self.read_value()?,
+                        )?;
+                        check_field(&key, &value)?;
+                        self.state = JsonState::AfterField;
+                    }
+                }
+                JsonState::AfterField => match self.next_token()? {
+                    Some(b',') => self.state = JsonState::Field,
+                    Some(b'}') => return self.finish(),
+                    _ => return Err(self.error("`,` or `}`")),
+                },
+                JsonState::Quote => {
+                    self.skip_whitespace()?;
+                    let record: Value =
+                        serde_json::from_slice(&self.read_value()?)?;
+                    require_valid_quote(&record, self.index)?;
+                    let quote = serde_json::from_value(record)?;
+                    self.index += 1;
+                    match self.next_token()? {
+                        Some(b',') => {}
+                        Some(b']') => {
+                            self.state = JsonState::AfterField
+                        }
+                        _ => return Err(self.error("`,` or `]`")),
+                    }
+                    return Ok(Some(quote));
+                }
+                JsonState::Done => return Ok(None),
+            }
+        }
+    }
+
+    /// Checks what follows the dataset once its closing brace is read.
+    fn finish(&mut self) -> Result<Option<Quote>, QuoteError> {
+        if self.peek_token()?.is_some() {
+            return Err(self.error("the end of the dataset"));
+        }
+        self.state = JsonState::Done;
+        if !self.has_quotes {
+            return Err(QuoteError::SchemaViolation(vec![
+                SchemaError {
+                    pointer: String::new(),
+                    message: "missing required property `quotes`"
+                        .to_string(),
+                },
+            ]));
+        }
+        Ok(None)
+    }
+
+    /// Reads the raw bytes of the value starting at the current byte.
+    fn read_value(&mut self) -> Result<Vec<u8>, QuoteError> {
+        let mut value = Vec::new();
+        let mut scan = match self.peek()? {
+            Some(b'{' | b'[' | b'"') => Scan::Nested {
+                depth: 0,
+                in_string: false,
+                escaped: false,
+            },
+            Some(b'-' | b'0'..=b'9' | b't' | b'f' | b'n') => {
+                Scan::Scalar
+            }
+            _ => return Err(self.error("a value")),
+        };
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            if buffer.is_empty() {
+                if scan == Scan::Scalar {
+                    return Ok(value);
+                }
+                return Err(self.error("more input"));
+            }
+            let (length, complete) = scan.feed(buffer);
+            value.extend_from_slice(&buffer[..length]);
+            self.reader.consume(length);
+            self.offset += length;
+            if complete {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Consumes `expected`, after any whitespace.
+    fn expect(
+        &mut self,
+        expected: u8,
+        description: &str,
+    ) -> Result<(), QuoteError> {
+        if self.next_token()? == Some(expected) {
+            Ok(())
+        } else {
+            Err(self.error(description))
+        }
+    }
+
+    /// Consumes and returns the next byte after any whitespace.
+    fn next_token(&mut self) -> Result<Option<u8>, QuoteError> {
+        match self.peek_token()? {
+            Some(_) => self.bump().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the next byte after any whitespace, without consuming
+    /// it.
+    fn peek_token(&mut self) -> Result<Option<u8>, QuoteError> {
+        self.skip_whitespace()?;
+        self.peek()
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), QuoteError> {
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            let length = buffer
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+            let done = length < buffer.len() || buffer.is_empty();
+            self.reader.consume(length);
+            self.offset += length;
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, QuoteError> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    /// Consumes the next byte, failing at the end of the input.
+    fn bump(&mut self) -> Result<u8, QuoteError> {
+        let byte =
+            self.peek()?.ok_or_else(|| self.error("more input"))?;
+        self.reader.consume(1);
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    /// Returns the error for a dataset that does not continue with
+    /// `expected`.
+    fn error(&self, expected: &str) -> QuoteError {
+        QuoteError::ParseError(format!(
+            "invalid JSON: expected {} at byte {}",
+            expected, self.offset
+        ))
+    }
+}
+
+impl<R: Read> Iterator for JsonRecords<R> {
+    type Item = Result<Quote, QuoteError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Ok(Some(quote)) => Some(Ok(quote)),
+            Ok(None) => None,
+            Err(err) => {
+                self.state = JsonState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Finds where a raw JSON value ends, one buffer at a time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Scan {
+    /// An object, array or string, which ends with the bracket or
+    /// quote that closes it.
+    Nested {
+        /// The number of objects and arrays left open.
+        depth: usize,
+        /// Whether the scan is inside a string.
+        in_string: bool,
+        /// Whether the previous byte was a backslash in a string.
+        escaped: bool,
+    },
+    /// A number, `true`, `false` or `null`, which ends before the next
+    /// delimiter or whitespace.
+    Scalar,
+}
+
+impl Scan {
+    /// Scans `bytes` for the end of the value.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of bytes belonging to the value, and whether
+    /// the value ends within `bytes`.
+    fn feed(&mut self, bytes: &[u8]) -> (usize, bool) {
+        match self {
+            Scan::Scalar => match bytes.iter().position(|&byte| {
+                matches!(byte, b',' | b'}' | b']')
+                    || byte.is_ascii_whitespace()
+            }) {
+                Some(end) => (end, true),
+                None => (bytes.len(), false),
+            },
+            Scan::Nested { depth, in_string, escaped } => {
+                let mut index = 0;
+                while index < bytes.len() {
+                    if *in_string {
+                        if *escaped {
+                            *escaped = false;
+                            index += 1;
+                            continue;
+                        }
+                        // Skip to the next quote or backslash at once
+                        match bytes[index..].iter().position(|&byte| {
+                            matches!(byte, b'"' | b'\\')
+                        }) {
+                            Some(offset) => index += offset,
+                            None => return (bytes.len(), false),
+                        }
+                        if bytes[index] == b'\\' {
+                            *escaped = true;
+                        } else {
+                            *in_string = false;
+                        }
+                    } else {
+                        match bytes[index] {
+                            b'"' => *in_string = true,
+                            b'{' | b'[' => *depth += 1,
+                            b'}' | b']' => {
+                                *depth = depth.saturating_sub(1)
+                            }
+                            _ => {}
+                        }
+                    }
+                    index += 1;
+                    if *depth == 0 && !*in_string {
+                        return (index, true);
+                    }
+                }
+                (bytes.len(), false)
+            }
+        }
+    }
+}
+
+/// Checks a top-level field other than `quotes`, rejecting datasets
+/// written with a newer schema than this crate knows.
+fn check_field(name: &str, value: &Value) -> Result<(), QuoteError> {
+    if name == "schema_version" {
+        if let Some(version) = value.as_u64() {
+            if version > u64::from(SCHEMA_VERSION) {
+                return Err(QuoteError::UnsupportedSchemaVersion(
+                    u32::try_from(version).unwrap_or(u32::MAX),
+                ));
+            }
+        }
+    }
+    require_valid_field(name, value)
+}
@@ -0,0 +1,1387 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::dates::QuoteDate;
+use crate::error::WiseroneError;
+use crate::escape::{self, HtmlScanner};
+
+/// The extension of the templates loaded from a directory.
+const TEMPLATE_EXTENSION: &str = "html";
+
+/// How many includes and layouts may be nested before rendering gives
+/// up, so that a template including itself fails instead of
+/// overflowing the stack.
+const MAX_DEPTH: usize = 32;
+
+/// The filters a template may use.
+const FILTERS: [&str; 7] =
+    ["date", "default", "join", "length", "lower", "truncate", "upper"];
+
+/// A set of named templates that can include and extend each other.
+///
+/// Templates use a small Jinja-like syntax:
+///
+/// - `{{ quote.author }}` writes a value from the context, and
+///   `{{ quote.quote_text | truncate(80) }}` passes it through filters.
+/// - `{% if quote.tags %}…{% elif … %}…{% else %}…{% endif %}` and
+///   `{% for tag in quote.tags %}…{% else %}…{% endfor %}` choose and
+///   repeat parts of a page. Conditions may use `not`, `and`, `or`,
+///   `==` and `!=`. Inside a loop, `loop.index`, `loop.first` and
+///   `loop.last` describe the current item, and `{% for key, value in
+///   map %}` walks the entries of an object.
+/// - `{% include "partials/meta.html" %}` renders another template in
+///   place, with the same context.
+/// - `{% extends "base.html" %}` renders a parent layout instead, with
+///   each of its `{% block name %}…{% endblock %}` sections replaced by
+///   the child's block of the same name, if it has one.
+/// - `{# … #}` is a comment, and a `-` inside a delimiter (`{%-`,
+///   `-%}`, `{{-`, `-}}`) trims the whitespace on that side of the tag.
+///   A `{% … %}` tag or comment alone on its line is removed with the
+///   line, so it leaves no blank line behind.
+///
+/// Values are escaped for the place they are written: as HTML text or
+/// attribute values, as URLs in attributes such as `href` and `src`,
+/// as CSS in `style`, and as JSON or JavaScript strings in `<script>`
+/// (see [`escape`](crate::escape)). A value in a tag outside a quoted
+/// attribute, in an event handler or in a script comment is an error.
+/// Writing a value that is not in the context is also an error, unless
+/// it goes through the `default` filter; `if` treats it as false and
+/// `for` as empty.
+///
+/// The filters are:
+///
+/// - `date(format)` formats an RFC 3339 or ISO 8601 date with a `time`
+///   format description, such as `"[month repr:long] [day], [year]"`;
+/// - `truncate(length)` shortens text to `length` characters, ending
+///   with `…`;
+/// - `default(value)` replaces a missing, null or empty value;
+/// - `upper` and `lower` change the case of text, `length` counts the
+///   characters of text or the items of a list, and `join(separator)`
+///   joins the items of a list.
+#[derive(Clone, Debug, Default)]
+pub struct Templates {
+    templates: BTreeMap<String, Template>,
+}
+
+impl Templates {
+    /// Creates an empty set of templates.
+    pub fn new() -> Self {
+        Templates::default()
+    }
+
+    /// Loads every `.html` template in a directory and its
+    /// subdirectories, such as `_layouts`.
+    ///
+    /// Templates are named by their path from the directory, with `/`
+    /// separators: `quote.html`, `partials/meta.html`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the templates, `WiseroneError::Io` if a file cannot be
+    /// read, or `WiseroneError::Template` if a template is malformed.
+    pub fn load(
+        directory: impl AsRef<Path>,
+    ) -> Result<Self, WiseroneError> {
+        let mut templates = Templates::new();
+        templates.load_directory(directory.as_ref(), "")?;
+        Ok(templates)
+    }
+
+    /// Adds the templates in `directory`, naming them from `prefix`.
+    fn load_directory(
+        &mut self,
+        directory: &Path,
+        prefix: &str,
+    ) -> Result<(), WiseroneError> {
+        let list_error = |err| WiseroneError::io(directory, err);
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(directory).map_err(list_error)? {
+            paths.push(entry.map_err(list_error)?.path());
+        }
+        paths.sort();
+
+        for path in paths {
+            let Some(file_name) =
+                path.file_name().and_then(|name| name.to_str())
+            else {
+                continue;
+            };
+            let name = format!("{}{}", prefix, file_name);
+            if path.is_dir() {
+                self.load_directory(&path, &format!("{}/", name))?;
+            } else if path.extension().is_some_and(|extension| {
+                extension == TEMPLATE_EXTENSION
+            }) {
+                let source = fs::read_to_string(&path)
+                    .map_err(|err| WiseroneError::io(&path, err))?;
+                self.add(&name, &source)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a template, replacing any template of the same name.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())`, or `WiseroneError::Template` naming the line
+    /// of the first mistake in the template.
+    pub fn add(
+        &mut self,
+        name: &str,
+        source: &str,
+    ) -> Result<(), WiseroneError> {
+        let template = Template::parse(name, source)
+            .map_err(|err| WiseroneError::Template(err.to_string()))?;
+        let _ = self.templates.insert(name.to_string(), template);
+        Ok(())
+    }
+
+    /// Returns `true` if a template is called `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.templates.contains_key(name)
+    }
+
+    /// Renders the template called `name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the template.
+    /// * `context` - The values the template can use, usually an
+    ///   object.
+    ///
+    /// # Returns
+    ///
+    /// Returns the rendered text, or `WiseroneError::Template` if the
+    /// template is missing or uses a value it cannot write.
+    pub fn render(
+        &self,
+        name: &str,
+        context: &Value,
+    ) -> Result<String, WiseroneError> {
+        let mut output = Page::default();
+        let mut scope = Scope { context, locals: Vec::new() };
+        self.render_template(name, &mut scope, &mut output, 0)
+            .map_err(|err| WiseroneError::Template(err.to_string()))?;
+        Ok(output.text)
+    }
+
+    /// Renders a template, following its chain of layouts.
+    fn render_template(
+        &self,
+        name: &str,
+        scope: &mut Scope<'_>,
+        output: &mut Page,
+        depth: usize,
+    ) -> Result<(), RenderError> {
+        let mut template = self.get(name, depth)?;
+        let mut blocks = BTreeMap::new();
+        let mut depth = depth;
+        loop {
+            collect_blocks(&template.nodes, &mut blocks);
+            match &template.parent {
+                Some(parent) => {
+                    depth += 1;
+                    template = self.get(parent, depth)?;
+                }
+                None => break,
+            }
+        }
+        let renderer = Renderer { templates: self, blocks, depth };
+        renderer.render_nodes(&template.nodes, scope, output)
+    }
+
+    /// Returns the template called `name`.
+    fn get(
+        &self,
+        name: &str,
+        depth: usize,
+    ) -> Result<&Template, RenderError> {
+        if depth > MAX_DEPTH {
+            return Err(RenderError::new(
+                name,
+                0,
+                "includes or layouts are nested too deeply",
+            ));
+        }
+        self.templates.get(name).ok_or_else(|| {
+            RenderError::new(name, 0, "no template has this name")
+        })
+    }
+}
+
+/// A mistake found while parsing or rendering a template.
+#[derive(Debug)]
+struct RenderError {
+    template: String,
+    /// The one-based line of the mistake, or 0 for the whole template.
+    line: usize,
+    message: String,
+}
+
+impl RenderError {
+    fn new(
+        template: &str,
+        line: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        RenderError {
+            template: template.to_string(),
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "`{}`: {}", self.template, self.message)
+        } else {
+            write!(
+                f,
+                "`{}` line {}: {}",
+                self.template, self.line, self.message
+            )
+        }
+    }
+}
+
+/// A parsed template.
+#[derive(Clone, Debug)]
+struct Template {
+    /// The layout named by `{% extends %}`, if any.
+    parent: Option<String>,
+    nodes: Vec<Node>,
+}
+
+/// A part of a template.
+#[derive(Clone, Debug)]
+enum Node {
+    /// Text written as it is.
+    Text(String),
+    /// A `{{ … }}` tag.
+    Output(Expr, Position),
+    /// An `{% if %}` tag, with the body of each condition and the body
+    /// of the `{% else %}`.
+    If(Vec<(Condition, Vec<Node>)>, Vec<Node>, Position),
+    /// A `{% for %}` tag.
+    For(Loop),
+    /// An `{% include %}` tag.
+    Include(String, Position),
+    /// A `{% block %}` tag, with its default content.
+    Block(String, Vec<Node>),
+}
+
+/// Where a tag is, for error messages.
+#[derive(Clone, Debug)]
+struct Position {
+    template: String,
+    line: usize,
+}
+
+impl Position {
+    fn error(&self, message: impl Into<String>) -> RenderError {
+        RenderError::new(&self.template, self.line, message)
+    }
+}
+
+/// A `{% for %}` tag.
+#[derive(Clone, Debug)]
+struct Loop {
+    /// The name of the entry key, when walking an object by entry.
+    key: Option<String>,
+    /// The name of the item.
+    item: String,
+    source: Expr,
+    body: Vec<Node>,
+    /// The body of the `{% else %}`, rendered when there are no items.
+    otherwise: Vec<Node>,
+    position: Position,
+}
+
+/// A value and the filters it goes through.
+#[derive(Clone, Debug)]
+struct Expr {
+    operand: Operand,
+    filters: Vec<Filter>,
+}
+
+/// A value in an expression.
+#[derive(Clone, Debug)]
+enum Operand {
+    /// A path into the context, such as `quote.source.title`.
+    Path(Vec<String>),
+    /// A string, number, boolean or null written in the template.
+    Literal(Value),
+}
+
+/// A filter and its arguments.
+#[derive(Clone, Debug)]
+struct Filter {
+    name: String,
+    arguments: Vec<Operand>,
+}
+
+/// The condition of an `{% if %}` tag.
+#[derive(Clone, Debug)]
+enum Condition {
+    Or(Vec<Condition>),
+    And(Vec<Condition>),
+    Not(Box<Condition>),
+    /// A value that must be truthy.
+    Test(Expr),
+    /// Two values that must be equal, or different when `false`.
+    Compare(Expr, bool, Expr),
+}
+
+/// A piece of template source.
+#[derive(Debug)]
+enum Piece {
+    Text(String),
+    /// The inside of a `{{ … }}` tag, and its line.
+    Output(String, usize),
+    /// The inside of a `{% … %}` tag, and its line.
+    Tag(String, usize),
+}
+
+impl Template {
+    /// Parses the template called `name`.
+    fn parse(
+        name: &str,
+        source: &str,
+    ) -> Result<Template, RenderError> {
+        let pieces = split(name, source)?;
+        let mut parser =
+            Parser { name, pieces: pieces.into_iter(), parent: None };
+        let (nodes, end) = parser.parse_nodes(&[], true)?;
+        if let Some((keyword, line)) = end {
+            return Err(RenderError::new(
+                name,
+                line,
+                format!("unexpected `{{% {} %}}`", keyword),
+            ));
+        }
+        Ok(Template { parent: parser.parent, nodes })
+    }
+}
+
+/// Splits template source into text, output tags and statement tags,
+/// dropping comments and trimming whitespace where asked.
+fn split(name: &str, source: &str) -> Result<Vec<Piece>, RenderError> {
+    let mut pieces = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    let mut trim_next = false;
+
+    loop {
+        let start = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min();
+        let text = &rest[..start.unwrap_or(rest.len())];
+        let text = if trim_next { text.trim_start() } else { text };
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text.to_string()));
+        }
+        let Some(start) = start else {
+            break;
+        };
+        line += rest[..start].matches('\n').count();
+
+        let open = &rest[start..start + 2];
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let inner_start = start + 2;
+        let Some(length) = rest[inner_start..].find(close) else {
+            return Err(RenderError::new(
+                name,
+                line,
+                format!("`{}` is never closed", open),
+            ));
+        };
+        let mut inner = &rest[inner_start..inner_start + length];
+        let tag_start = source.len() - rest.len() + start;
+        rest = &rest[inner_start + length + 2..];
+
+        // A statement or comment alone on its line leaves no blank line
+        if open != "{{" {
+            let indent = source[..tag_start]
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default();
+            let line_end =
+                rest.strip_prefix("\r\n").or(rest.strip_prefix('\n'));
+            if indent.chars().all(|c| c == ' ' || c == '\t')
+                && (line_end.is_some() || rest.is_empty())
+            {
+                if let Some(Piece::Text(text)) = pieces.last_mut() {
+                    if text.ends_with(indent) {
+                        text.truncate(text.len() - indent.len());
+                    }
+                }
+                if let Some(line_end) = line_end {
+                    rest = line_end;
+                    line += 1;
+                }
+            }
+        }
+
+        if let Some(trimmed) = inner.strip_prefix('-') {
+            inner = trimmed;
+            if let Some(Piece::Text(text)) = pieces.last_mut() {
+                let kept = text.trim_end().len();
+                text.truncate(kept);
+            }
+        }
+        trim_next = false;
+        if let Some(trimmed) = inner.strip_suffix('-') {
+            inner = trimmed;
+            trim_next = true;
+        }
+
+        match open {
+            "{{" => pieces
+                .push(Piece::Output(inner.trim().to_string(), line)),
+            "{%" => {
+                pieces.push(Piece::Tag(inner.trim().to_string(), line))
+            }
+            _ => {}
+        }
+        line += inner.matches('\n').count();
+    }
+    pieces.retain(
+        |piece| !matches!(piece, Piece::Text(text) if text.is_empty()),
+    );
+    Ok(pieces)
+}
+
+/// The tag that ends a run of nodes, and its line.
+type EndTag = (String, usize);
+
+/// Builds nodes from the pieces of a template.
+struct Parser<'a> {
+    name: &'a str,
+    pieces: std::vec::IntoIter<Piece>,
+    parent: Option<String>,
+}
+
+impl Parser<'_> {
+    /// Parses nodes up to a tag whose keyword is in `until`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the nodes, and the keyword and line of the tag that
+    /// ended them, or `None` at the end of the template.
+    fn parse_nodes(
+        &mut self,
+        until: &[&str],
+        top_level: bool,
+    ) -> Result<(Vec<Node>, Option<EndTag>), RenderError> {
+        let mut nodes = Vec::new();
+        while let Some(piece) = self.pieces.next() {
+            let (tag, line) = match piece {
+                Piece::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Piece::Output(expression, line) => {
+                    let expr = Tokens::new(&expression)
+                        .and_then(|mut tokens| {
+                            tokens.parse_expr_to_end()
+                        })
+                        .map_err(|err| self.error(line, err))?;
+                    nodes.push(Node::Output(expr, self.position(line)));
+                    continue;
+                }
+                Piece::Tag(tag, line) => (tag, line),
+            };
+
+            let (keyword, rest) =
+                match tag.split_once(char::is_whitespace) {
+                    Some((keyword, rest)) => (keyword, rest.trim()),
+                    None => (tag.as_str(), ""),
+                };
+            if until.contains(&keyword) {
+                return Ok((nodes, Some((tag.clone(), line))));
+            }
+            match keyword {
+                "if" => nodes.push(self.parse_if(rest, line)?),
+                "for" => nodes.push(self.parse_for(rest, line)?),
+                "include" => {
+                    let name = string_argument(rest)
+                        .map_err(|err| self.error(line, err))?;
+                    nodes
+                        .push(Node::Include(name, self.position(line)));
+                }
+                "block" => {
+                    let name = rest.to_string();
+                    if name.is_empty() {
+                        return Err(
+                            self.error(line, "a block needs a name")
+                        );
+                    }
+                    let (body, _) =
+                        self.parse_until(&["endblock"], line, "block")?;
+                    nodes.push(Node::Block(name, body));
+                }
+                "extends" if top_level => {
+                    let parent = string_argument(rest)
+                        .map_err(|err| self.error(line, err))?;
+                    self.parent = Some(parent);
+                }
+                "extends" => {
+                    return Err(self.error(
+                        line,
+                        "`extends` must be at the top level",
+                    ))
+                }
+                "elif" | "else" | "endif" | "endfor" | "endblock" => {
+                    return Ok((nodes, Some((tag.clone(), line))));
+                }
+                _ => {
+                    return Err(self.error(
+                        line,
+                        format!("unknown tag `{}`", keyword),
+                    ))
+                }
+            }
+        }
+        Ok((nodes, None))
+    }
+
+    /// Parses nodes up to one of the tags in `until`, which must come.
+    fn parse_until(
+        &mut self,
+        until: &[&str],
+        line: usize,
+        opened: &str,
+    ) -> Result<(Vec<Node>, EndTag), RenderError> {
+        let (nodes, end) = self.parse_nodes(until, false)?;
+        match end {
+            Some((tag, end_line)) => {
+                let keyword =
+                    tag.split_whitespace().next().unwrap_or_default();
+                if until.contains(&keyword) {
+                    Ok((nodes, (tag, end_line)))
+                } else {
+                    Err(self.error(
+                        end_line,
+                        format!("unexpected `{{% {} %}}`", tag),
+                    ))
+                }
+            }
+            None => Err(self.error(
+                line,
+                format!("`{{% {} %}}` is never closed", opened),
+            )),
+        }
+    }
+
+    fn parse_if(
+        &mut self,
+        condition: &str,
+        line: usize,
+    ) -> Result<Node, RenderError> {
+        let mut branches = Vec::new();
+        let mut condition = self.condition(condition, line)?;
+        loop {
+            let (body, (tag, end_line)) = self.parse_until(
+                &["elif", "else", "endif"],
+                line,
+                "if",
+            )?;
+            branches.push((condition, body));
+            match tag.split_once(char::is_whitespace) {
+                Some(("elif", rest)) => {
+                    condition = self.condition(rest, end_line)?;
+                }
+                _ if tag == "else" => {
+                    let (otherwise, _) =
+                        self.parse_until(&["endif"], line, "if")?;
+                    return Ok(Node::If(
+                        branches,
+                        otherwise,
+                        self.position(line),
+                    ));
+                }
+                _ if tag == "endif" => {
+                    return Ok(Node::If(
+                        branches,
+                        Vec::new(),
+                        self.position(line),
+                    ));
+                }
+                _ => {
+                    return Err(self.error(
+                        end_line,
+                        format!("unexpected `{{% {} %}}`", tag),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn parse_for(
+        &mut self,
+        header: &str,
+        line: usize,
+    ) -> Result<Node, RenderError> {
+        let Some((names, source)) = header.split_once(" in ") else {
+            return Err(self.error(line, "expected `for item in list`"));
+        };
+        let names: Vec<&str> =
+            names.split(',').map(str::trim).collect();
+        let (key, item) = match names.as_slice() {
+            [item] => (None, item.to_string()),
+            [key, item] => (Some(key.to_string()), item.to_string()),
+            _ => {
+                return Err(
+                    self.error(line, "expected one or two names")
+                )
+            }
+        };
+        if !key.iter().chain([&item]).all(|name| is_identifier(name)) {
+            return Err(
+                self.error(line, "loop names must be identifiers")
+            );
+        }
+        let source = Tokens::new(source)
+            .and_then(|mut tokens| tokens.parse_expr_to_end())
+            .map_err(|err| self.error(line, err))?;
+
+        let (body, (tag, _)) =
+            self.parse_until(&["else", "endfor"], line, "for")?;
+        let otherwise = if tag == "else" {
+            self.parse_until(&["endfor"], line, "for")?.0
+        } else {
+            Vec::new()
+        };
+        Ok(Node::For(Loop {
+            key,
+            item,
+            source,
+            body,
+            otherwise,
+            position: self.position(line),
+        }))
+    }
+
+    fn condition(
+        &self,
+        condition: &str,
+        line: usize,
+    ) -> Result<Condition, RenderError> {
+        Tokens::new(condition)
+            .and_then(|mut tokens| {
+                let condition = tokens.parse_or()?;
+                tokens.expect_end()?;
+                Ok(condition)
+            })
+            .map_err(|err| self.error(line, err))
+    }
+
+    fn position(&self, line: usize) -> Position {
+        Position { template: self.name.to_string(), line }
+    }
+
+    fn error(
+        &self,
+        line: usize,
+        message: impl Into<String>,
+    ) -> RenderError {
+        RenderError::new(self.name, line, message)
+    }
+}
+
+/// Reads the quoted template name of an `include` or `extends` tag.
+fn string_argument(argument: &str) -> Result<String, String> {
+    let mut tokens = Tokens::new(argument)?;
+    match tokens.next() {
+        Some(Token::Str(name)) => {
+            tokens.expect_end()?;
+            Ok(name)
+        }
+        _ => Err("expected a quoted template name".to_string()),
+    }
+}
+
+/// Returns `true` if `name` can name a loop variable.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| {
+        first.is_ascii_alphabetic() || first == '_'
+    }) && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A token of an expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A name or a dotted path.
+    Name(String),
+    Str(String),
+    Number(serde_json::Number),
+    Pipe,
+    Open,
+    Close,
+    Comma,
+    Equal,
+    NotEqual,
+}
+
+/// The tokens of an expression, consumed by the parsing methods.
+struct Tokens {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Tokens {
+    /// Splits an expression into tokens.
+    fn new(source: &str) -> Result<Tokens, String> {
+        let mut tokens = Vec::new();
+        let mut chars = source.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                c if c.is_whitespace() => {
+                    let _ = chars.next();
+                }
+                '|' => {
+                    let _ = chars.next();
+                    tokens.push(Token::Pipe);
+                }
+                '(' => {
+                    let _ = chars.next();
+                    tokens.push(Token::Open);
+                }
+                ')' => {
+                    let _ = chars.next();
+                    tokens.push(Token::Close);
+                }
+                ',' => {
+                    let _ = chars.next();
+                    tokens.push(Token::Comma);
+                }
+                '=' | '!' => {
+                    let _ = chars.next();
+                    if chars.next() != Some('=') {
+                        return Err(format!("expected `{}=`", c));
+                    }
+                    tokens.push(if c == '=' {
+                        Token::Equal
+                    } else {
+                        Token::NotEqual
+                    });
+                }
+                '"' | '\'' => {
+                    let _ = chars.next();
+                    let mut text = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('\\') => match chars.next() {
+                                Some('n') => text.push('\n'),
+                                Some(escaped) => text.push(escaped),
+                                None => break,
+                            },
+                            Some(end) if end == c => break,
+                            Some(other) => text.push(other),
+                            None => {
+                                return Err("a string is never closed"
+                                    .to_string())
+                            }
+                        }
+                    }
+                    tokens.push(Token::Str(text));
+                }
+                c if c.is_ascii_digit() || c == '-' => {
+                    let mut number = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_ascii_digit() || c == '-' || c == '.' {
+                            number.push(c);
+                            let _ = chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    let value: serde_json::Number =
+                        number.parse().map_err(|_| {
+                            format!("`{}` is not a number", number)
+                        })?;
+                    tokens.push(Token::Number(value));
+                }
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_ascii_alphanumeric()
+                            || c == '_'
+                            || c == '.'
+                        {
+                            name.push(c);
+                            let _ = chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    tokens.push(Token::Name(name));
+                }
+                other => return Err(format!("unexpected `{}`", other)),
+            }
+        }
+        Ok(Tokens { tokens: tokens.into_iter().peekable() })
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        self.tokens.next()
+    }
+
+    fn expect_end(&mut self) -> Result<(), String> {
+        match self.tokens.next() {
+            None => Ok(()),
+            Some(token) => {
+                Err(format!("unexpected {}", describe(&token)))
+            }
+        }
+    }
+
+    /// Parses a whole `{{ … }}` tag or loop source.
+    fn parse_expr_to_end(&mut self) -> Result<Expr, String> {
+        let expr = self.parse_expr()?;
+        self.expect_end()?;
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.parse_and()?];
+        while self
+            .tokens
+            .next_if_eq(&Token::Name("or".into()))
+            .is_some()
+        {
+            conditions.push(self.parse_and()?);
+        }
+        Ok(if conditions.len() == 1 {
+            conditions.remove(0)
+        } else {
+            Condition::Or(conditions)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Condition, String> {
+        let mut conditions = vec![self.parse_not()?];
+        while self
+            .tokens
+            .next_if_eq(&Token::Name("and".into()))
+            .is_some()
+        {
+            conditions.push(self.parse_not()?);
+        }
+        Ok(if conditions.len() == 1 {
+            conditions.remove(0)
+        } else {
+            Condition::And(conditions)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Condition, String> {
+        if self.tokens.next_if_eq(&Token::Name("not".into())).is_some()
+        {
+            return Ok(Condition::Not(Box::new(self.parse_not()?)));
+        }
+        let left = self.parse_expr()?;
+        let equal = match self.tokens.peek() {
+            Some(Token::Equal) => true,
+            Some(Token::NotEqual) => false,
+            _ => return Ok(Condition::Test(left)),
+        };
+        let _ = self.tokens.next();
+        Ok(Condition::Compare(left, equal, self.parse_expr()?))
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, String> {
+        let operand = self.parse_operand()?;
+        let mut filters = Vec::new();
+        while self.tokens.next_if_eq(&Token::Pipe).is_some() {
+            let name = match self.tokens.next() {
+                Some(Token::Name(name))
+                    if FILTERS.contains(&name.as_str()) =>
+                {
+                    name
+                }
+                Some(Token::Name(name)) => {
+                    return Err(format!("unknown filter `{}`", name))
+                }
+                _ => return Err("expected a filter name".to_string()),
+            };
+            let mut arguments = Vec::new();
+            if self.tokens.next_if_eq(&Token::Open).is_some()
+                && self.tokens.next_if_eq(&Token::Close).is_none()
+            {
+                loop {
+                    arguments.push(self.parse_operand()?);
+                    match self.tokens.next() {
+                        Some(Token::Comma) => {}
+                        Some(Token::Close) => break,
+                        _ => {
+                            return Err(
+                                "expected `,` or `)`".to_string()
+                            )
+                        }
+                    }
+                }
+            }
+            filters.push(Filter { name, arguments });
+        }
+        Ok(Expr { operand, filters })
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.tokens.next() {
+            Some(Token::Str(text)) => {
+                Ok(Operand::Literal(Value::String(text)))
+            }
+            Some(Token::Number(number)) => {
+                Ok(Operand::Literal(Value::Number(number)))
+            }
+            Some(Token::Name(name)) => Ok(match name.as_str() {
+                "true" => Operand::Literal(Value::Bool(true)),
+                "false" => Operand::Literal(Value::Bool(false)),
+                "null" | "none" => Operand::Literal(Value::Null),
+                _ => {
+                    let path: Vec<String> =
+                        name.split('.').map(String::from).collect();
+                    if path.iter().any(String::is_empty) {
+                        return Err(format!(
+                            "`{}` is not a valid path",
+                            name
+                        ));
+                    }
+                    Operand::Path(path)
+                }
+            }),
+            Some(token) => {
+                Err(format!("unexpected {}", describe(&token)))
+            }
+            None => Err("expected a value".to_string()),
+        }
+    }
+}
+
+/// Names a token in an error message.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Name(name) => format!("`{}`", name),
+        Token::Str(text) => format!("\"{}\"", text),
+        Token::Number(number) => format!("`{}`", number),
+        Token::Pipe => "`|`".to_string(),
+        Token::Open => "`(`".to_string(),
+        Token::Close => "`)`".to_string(),
+        Token::Comma => "`,`".to_string(),
+        Token::Equal => "`==`".to_string(),
+        Token::NotEqual => "`!=`".to_string(),
+    }
+}
+
+/// Adds the blocks defined in `nodes` to `blocks`, unless a template
+/// further down the chain of layouts already defined them.
+fn collect_blocks<'a>(
+    nodes: &'a [Node],
+    blocks: &mut BTreeMap<&'a str, &'a [Node]>,
+) {
+    for node in nodes {
+        match node {
+            Node::Block(name, body) => {
+                let _ = blocks
+                    .entry(name.as_str())
+                    .or_insert(body.as_slice());
+                collect_blocks(body, blocks);
+            }
+            Node::If(branches, otherwise, _) => {
+                for (_, body) in branches {
+                    collect_blocks(body, blocks);
+                }
+                collect_blocks(otherwise, blocks);
+            }
+            Node::For(lp) => {
+                collect_blocks(&lp.body, blocks);
+                collect_blocks(&lp.otherwise, blocks);
+            }
+            Node::Text(_)
+            | Node::Output(_, _)
+            | Node::Include(_, _) => {}
+        }
+    }
+}
+
+/// The values a template can see: the context, and the variables of
+/// the loops being rendered.
+#[derive(Debug)]
+struct Scope<'a> {
+    context: &'a Value,
+    locals: Vec<(String, Value)>,
+}
+
+impl Scope<'_> {
+    /// Returns the value at `path`, or `None` if there is none.
+    fn lookup(&self, path: &[String]) -> Option<&Value> {
+        let (first, rest) = path.split_first()?;
+        let mut value = match self
+            .locals
+            .iter()
+            .rev()
+            .find(|(name, _)| name == first)
+        {
+            Some((_, value)) => value,
+            None => self.context.get(first)?,
+        };
+        for segment in rest {
+            value = match value {
+                Value::Array(items) => {
+                    items.get(segment.parse::<usize>().ok()?)?
+                }
+                other => other.get(segment)?,
+            };
+        }
+        Some(value)
+    }
+}
+
+/// A page being rendered, and the scanner that tells where its next
+/// value goes.
+#[derive(Debug, Default)]
+struct Page {
+    text: String,
+    scanner: HtmlScanner,
+}
+
+/// Renders the nodes of a template with the blocks of its layouts.
+struct Renderer<'a> {
+    templates: &'a Templates,
+    blocks: BTreeMap<&'a str, &'a [Node]>,
+    depth: usize,
+}
+
+impl Renderer<'_> {
+    fn render_nodes(
+        &self,
+        nodes: &[Node],
+        scope: &mut Scope<'_>,
+        output: &mut Page,
+    ) -> Result<(), RenderError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.text.push_str(text),
+                Node::Output(expr, position) => {
+                    match evaluate(expr, scope)
+                        .map_err(|err| position.error(err))?
+                    {
+                        Some(value) => {
+                            let context = output
+                                .scanner
+                                .context(&output.text)
+                                .map_err(|err| position.error(err))?;
+                            escape::write(
+                                context,
+                                &value,
+                                &mut output.text,
+                            );
+                        }
+                        None => {
+                            return Err(position.error(format!(
+                                "`{}` is not defined",
+                                expr.describe()
+                            )))
+                        }
+                    }
+                }
+                Node::If(branches, otherwise, position) => {
+                    let mut body = otherwise.as_slice();
+                    for (condition, branch) in branches {
+                        if holds(condition, scope)
+                            .map_err(|err| position.error(err))?
+                        {
+                            body = branch;
+                            break;
+                        }
+                    }
+                    self.render_nodes(body, scope, output)?;
+                }
+                Node::For(lp) => self.render_loop(lp, scope, output)?,
+                Node::Include(name, position) => self
+                    .templates
+                    .render_template(
+                        name,
+                        scope,
+                        output,
+                        self.depth + 1,
+                    )
+                    .map_err(|err| {
+                        position.error(format!(
+                            "in `{}`: {}",
+                            name, err.message
+                        ))
+                    })?,
+                Node::Block(name, default) => {
+                    let body = self
+                        .blocks
+                        .get(name.as_str())
+                        .copied()
+                        .unwrap_or(default);
+                    self.render_nodes(body, scope, output)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn render_loop(
+        &self,
+        lp: &Loop,
+        scope: &mut Scope<'_>,
+        output: &mut Page,
+    ) -> Result<(), RenderError> {
+        let source = evaluate(&lp.source, scope)
+            .map_err(|err| lp.position.error(err))?;
+        let entries: Vec<(Value, Value)> = match source {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| (Value::from(index), item))
+                .collect(),
+            Some(Value::Object(entries)) => entries
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), value))
+                .collect(),
+            Some(other) => {
+                return Err(lp
+                    .position
+                    .error(format!("cannot loop over `{}`", other)))
+            }
+        };
+        if entries.is_empty() {
+            return self.render_nodes(&lp.otherwise, scope, output);
+        }
+
+        let length = entries.len();
+        for (index, (key, value)) in entries.into_iter().enumerate() {
+            let mut state = Map::new();
+            let _ = state.insert("index".into(), (index + 1).into());
+            let _ = state.insert("index0".into(), index.into());
+            let _ = state.insert("first".into(), (index == 0).into());
+            let _ = state
+                .insert("last".into(), (index + 1 == length).into());
+            let _ = state.insert("length".into(), length.into());
+
+            let locals = scope.locals.len();
+            scope.locals.push(("loop".into(), Value::Object(state)));
+            match &lp.key {
+                Some(name) => {
+                    scope.locals.push((name.clone(), key));
+                    scope.locals.push((lp.item.clone(), value));
+                }
+                // A single name walks the keys of an object
+                None if key.is_string() => {
+                    scope.locals.push((lp.item.clone(), key));
+                }
+                None => scope.locals.push((lp.item.clone(), value)),
+            }
+            let result = self.render_nodes(&lp.body, scope, output);
+            scope.locals.truncate(locals);
+            result?;
+        }
+        Ok(())
+    }
+}
+
+impl Expr {
+    /// Names the value of the expression in an error message.
+    fn describe(&self) -> String {
+        match &self.operand {
+            Operand::Path(path) => path.join("."),
+            Operand::Literal(value) => value.to_string(),
+        }
+    }
+}
+
+/// Returns the value of an operand, or `None` if it is not defined.
+fn operand_value(
+    operand: &Operand,
+    scope: &Scope<'_>,
+) -> Option<Value> {
+    match operand {
+        Operand::Path(path) => scope.lookup(path).cloned(),
+        Operand::Literal(value) => Some(value.clone()),
+    }
+}
+
+/// Returns the value of an expression after its filters, or `None` if
+/// it is not defined.
+fn evaluate(
+    expr: &Expr,
+    scope: &Scope<'_>,
+) -> Result<Option<Value>, String> {
+    let mut value = operand_value(&expr.operand, scope);
+    for filter in &expr.filters {
+        let mut arguments = Vec::new();
+        for argument in &filter.arguments {
+            match operand_value(argument, scope) {
+                Some(argument) => arguments.push(argument),
+                None if filter.name == "default" => {
+                    arguments.push(Value::Null)
+                }
+                None => {
+                    return Err(format!(
+                        "an argument of `{}` is not defined",
+                        filter.name
+                    ))
+                }
+            }
+        }
+        value = apply(&filter.name, value, &arguments)?;
+    }
+    Ok(value)
+}
+
+/// Applies the filter called `name`.
+fn apply(
+    name: &str,
+    value: Option<Value>,
+    arguments: &[Value],
+) -> Result<Option<Value>, String> {
+    let argument = |index: usize| {
+        arguments.get(index).ok_or_else(|| {
+            format!("`{}` needs {} argument(s)", name, index + 1)
+        })
+    };
+    if name == "default" {
+        return Ok(Some(if value.as_ref().map_or(true, is_empty) {
+            argument(0)?.clone()
+        } else {
+            value.unwrap_or_default()
+        }));
+    }
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    let value = match name {
+        "date" => {
+            let format = argument(0)?
+                .as_str()
+                .ok_or("the format of `date` must be a string")?;
+            let text = value.as_str().ok_or_else(|| {
+                format!("`date` needs a date, found `{}`", value)
+            })?;
+            let date: QuoteDate = text.parse().map_err(|_| {
+                format!("`{}` is not a valid date", text)
+            })?;
+            let formatted =
+                date.datetime().format(format).map_err(|err| {
+                    format!("invalid date format: {}", err)
+                })?;
+            Value::String(formatted)
+        }
+        "truncate" => {
+            let length = argument(0)?.as_u64().ok_or(
+                "the length of `truncate` must be a whole number",
+            )? as usize;
+            let text = text_of(&value);
+            if text.chars().count() <= length {
+                Value::String(text)
+            } else {
+                let kept: String = text
+                    .chars()
+                    .take(length.saturating_sub(1))
+                    .collect();
+                Value::String(format!("{}…", kept.trim_end()))
+            }
+        }
+        "upper" => Value::String(text_of(&value).to_uppercase()),
+        "lower" => Value::String(text_of(&value).to_lowercase()),
+        "length" => Value::from(match &value {
+            Value::Array(items) => items.len(),
+            Value::Object(entries) => entries.len(),
+            other => text_of(other).chars().count(),
+        }),
+        "join" => {
+            let separator = argument(0)?
+                .as_str()
+                .ok_or("the separator of `join` must be a string")?;
+            let items = value.as_array().ok_or_else(|| {
+                format!("`join` needs a list, found `{}`", value)
+            })?;
+            Value::String(
+                items
+                    .iter()
+                    .map(text_of)
+                    .collect::<Vec<_>>()
+                    .join(separator),
+            )
+        }
+        _ => return Err(format!("unknown filter `{}`", name)),
+    };
+    Ok(Some(value))
+}
+
+/// Returns `true` if a value counts as false in a condition, or as
+/// missing for `default`.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(value) => !value,
+        Value::String(text) => text.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(entries) => entries.is_empty(),
+        Value::Number(number) => number.as_f64() == Some(0.0),
+    }
+}
+
+/// Returns the text a value is written as.
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns `true` if the condition of an `{% if %}` tag holds.
+fn holds(
+    condition: &Condition,
+    scope: &Scope<'_>,
+) -> Result<bool, String> {
+    Ok(match condition {
+        Condition::Or(conditions) => {
+            for condition in conditions {
+                if holds(condition, scope)? {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+        Condition::And(conditions) => {
+            for condition in conditions {
+                if !holds(condition, scope)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        Condition::Not(condition) => !holds(condition, scope)?,
+        Condition::Test(expr) => evaluate(expr, scope)?
+            .is_some_and(|value| !is_empty(&value)),
+        Condition::Compare(left, equal, right) => {
+            let left = evaluate(left, scope)?.unwrap_or_default();
+            let right = evaluate(right, scope)?.unwrap_or_default();
+            (left == right) == *equal
+        }
+    })
+}
@@ -0,0 +1,587 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::{fmt, fs};
+
+use crate::dates::QuoteDate;
+use crate::fortune::{fortunes, quotes_from_fortune};
+use crate::quotes::{
+    is_language_tag, is_slug, quote_from_csv_row, Format, Quote,
+    QuoteError, Quotes, SCHEMA_VERSION,
+};
+
+/// How serious a validation finding is.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// The record is usable but probably not what the editor intended.
+    Warning,
+    /// The record would produce a broken or overwritten page.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found in a quote dataset.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Finding {
+    /// The zero-based index of the record in the dataset.
+    pub index: usize,
+    /// The one-based line of the record in the source file, when known.
+    pub line: Option<usize>,
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The name of the offending field.
+    pub field: &'static str,
+    /// A human readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: record {}", self.severity, self.index + 1)?;
+        if let Some(line) = self.line {
+            write!(f, " (line {})", line)?;
+        }
+        write!(f, ": {}: {}", self.field, self.message)
+    }
+}
+
+/// The outcome of validating a quote dataset.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// Every finding, in record order.
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if nothing was found.
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns `true` if at least one finding is an error.
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Error)
+    }
+
+    /// Returns the findings of the given severity.
+    pub fn by_severity(
+        &self,
+        severity: Severity,
+    ) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity == severity)
+    }
+
+    /// Fills in the `line` of every finding from the positions of the
+    /// records in the source file.
+    fn attach_lines(&mut self, lines: &[usize]) {
+        for finding in &mut self.findings {
+            finding.line = lines.get(finding.index).copied();
+        }
+    }
+}
+
+impl Quotes {
+    /// Checks the dataset for problems that would break or silently
+    /// overwrite generated pages.
+    ///
+    /// The following are reported as errors: an empty `quote_text`, two
+    /// quotes published on the same day (they would write the same
+    /// `docs/YYYY_MM_DD.html`), an `image_url` that is not an http(s)
+    /// URL, a negative `weight`, an explicit `id` that is not a slug
+    /// (see [`is_slug`]) and two quotes sharing an explicit `id`. Empty
+    /// authors, repeated quotes and leading or trailing whitespace are
+    /// reported as warnings. Quotes without a `date_added` are waiting
+    /// for [`Quotes::schedule`] and are not reported.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ValidationReport` listing every finding in record
+    /// order. Line numbers are left empty; use [`validate_file`] to get
+    /// them.
+    pub fn validate(&self) -> ValidationReport {
+        ValidationReport {
+            findings: check(self.quotes.iter().enumerate(), false),
+        }
+    }
+}
+
+/// Runs the dataset checks over `(index, quote)` pairs.
+///
+/// Indices are passed in rather than derived so that records which
+/// failed to decode can be skipped without renumbering the others. When
+/// `imageless` is set, the source has no room for images, so an empty
+/// `image_url` is only a warning.
+fn check<'a>(
+    quotes: impl Iterator<Item = (usize, &'a Quote)>,
+    imageless: bool,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut days: HashMap<String, usize> = HashMap::new();
+    let mut ids: HashMap<String, usize> = HashMap::new();
+
+    for (index, quote) in quotes {
+        let mut report = |severity, field, message: String| {
+            findings.push(Finding {
+                index,
+                line: None,
+                severity,
+                field,
+                message,
+            })
+        };
+
+        for (field, value) in [
+            ("quote_text", &quote.quote_text),
+            ("author", &quote.author),
+            ("image_url", &quote.image_url),
+        ] {
+            if !value.is_empty() && value.trim() != value {
+                report(
+                    Severity::Warning,
+                    field,
+                    "has leading or trailing whitespace".into(),
+                );
+            }
+        }
+
+        if quote.quote_text.trim().is_empty() {
+            report(Severity::Error, "quote_text", "is empty".into());
+        }
+        if quote.author.trim().is_empty() {
+            report(Severity::Warning, "author", "is empty".into());
+        }
+
+        if let Some(day) = quote.day() {
+            if let Some(first) = days.get(&day) {
+                report(
+                    Severity::Error,
+                    "date_added",
+                    format!(
+                        "{} is already used by record {}",
+                        day,
+                        first + 1
+                    ),
+                );
+            } else {
+                let _ = days.insert(day, index);
+            }
+        }
+
+        if imageless && quote.image_url.trim().is_empty() {
+            report(
+                Severity::Warning,
+                "image_url",
+                "is empty, as the source holds no images".into(),
+            );
+        } else if !is_http_url(quote.image_url.trim()) {
+            report(
+                Severity::Error,
+                "image_url",
+                format!("`{}` is not an http(s) URL", quote.image_url),
+            );
+        }
+
+        if let Some(weight) = quote.weight {
+            if !weight.is_finite() || weight < 0.0 {
+                report(
+                    Severity::Error,
+                    "weight",
+                    format!("`{}` is not zero or more", weight),
+                );
+            }
+        }
+
+        for (language, translation) in &quote.translations {
+            if !is_language_tag(language) {
+                report(
+                    Severity::Error,
+                    "translations",
+                    format!("`{}` is not a language tag", language),
+                );
+            }
+            if translation.quote_text.trim().is_empty() {
+                report(
+                    Severity::Error,
+                    "translations",
+                    format!("the `{}` text is empty", language),
+                );
+            }
+        }
+
+        if let Some(id) = quote.id.as_deref().filter(|id| !is_slug(id))
+        {
+            report(
+                Severity::Error,
+                "id",
+                format!(
+                    "`{}` may only hold ASCII letters, digits, `-` and `_`",
+                    id
+                ),
+            );
+        }
+
+        let id = quote.stable_id();
+        if let Some(first) = ids.get(&id) {
+            if quote.id.is_some() {
+                report(
+                    Severity::Error,
+                    "id",
+                    format!(
+                        "`{}` is already used by record {}",
+                        id,
+                        first + 1
+                    ),
+                );
+            } else {
+                report(
+                    Severity::Warning,
+                    "quote_text",
+                    format!("repeats record {}", first + 1),
+                );
+            }
+        } else {
+            let _ = ids.insert(id, index);
+        }
+    }
+
+    findings
+}
+
+/// Reads a quote dataset and validates it.
+///
+/// Unlike [`read_quotes_from_file`](crate::quotes::read_quotes_from_file),
+/// a record that cannot be decoded (for example because its
+/// `date_added` is not a valid date) does not stop the whole file: it
+/// is reported as an error and the remaining records are still checked
+/// with [`Quotes::validate`]. Each finding carries the line of its
+/// record in the source file. Fortune files hold no images, so their
+/// empty `image_url`s are warnings rather than errors.
+///
+/// # Arguments
+///
+/// * `file_path` - Path to the file containing quotes.
+///
+/// # Returns
+///
+/// Returns the `ValidationReport`, or an error if the file cannot be
+/// read or is not a well-formed dataset at all.
+pub fn validate_file(
+    file_path: &str,
+) -> Result<ValidationReport, QuoteError> {
+    let format = Format::from_path(file_path)?;
+    validate_str(&fs::read_to_string(file_path)?, format)
+}
+
+/// Validates a dataset held in memory, such as one read from standard
+/// input.
+///
+/// Records are decoded and located as in [`validate_file`].
+///
+/// # Arguments
+///
+/// * `content` - The dataset.
+/// * `format` - The format the dataset is written in.
+///
+/// # Returns
+///
+/// Returns the `ValidationReport`, or an error if the content is not a
+/// well-formed dataset at all.
+pub fn validate_str(
+    content: &str,
+    format: Format,
+) -> Result<ValidationReport, QuoteError> {
+    let records = decode_records(content, format)?;
+
+    let mut findings = Vec::new();
+    let mut decoded = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        match record {
+            Ok(quote) => decoded.push((index, quote)),
+            Err((field, message)) => findings.push(Finding {
+                index,
+                line: None,
+                severity: Severity::Error,
+                field,
+                message: message.clone(),
+            }),
+        }
+    }
+    // Fortune files only hold the text and author of each quote
+    let imageless = format == Format::Fortune;
+    findings.extend(check(decoded.into_iter(), imageless));
+    findings.sort_by_key(|finding| finding.index);
+
+    let mut report = ValidationReport { findings };
+    // Only trust the positions if every record was located.
+    let lines = record_lines(content, format);
+    if lines.len() == records.len() {
+        report.attach_lines(&lines);
+    }
+    Ok(report)
+}
+
+/// A record that could not be decoded, as the offending field and a
+/// description of the problem.
+type RecordError = (&'static str, String);
+
+/// Decodes each record of a dataset on its own, so that one bad record
+/// does not hide problems in the others.
+fn decode_records(
+    content: &str,
+    format: Format,
+) -> Result<Vec<Result<Quote, RecordError>>, QuoteError> {
+    let dataset: serde_json::Value = match format {
+        Format::Json => serde_json::from_str(content)?,
+        Format::Yaml => serde_yaml::from_str(content)?,
+        Format::Toml => toml::from_str(content)?,
+        Format::Csv => {
+            let mut rdr = csv::Reader::from_reader(content.as_bytes());
+            return Ok(rdr
+                .deserialize()
+                .map(|row| match row {
+                    Ok(row) => {
+                        quote_from_csv_row(row).map_err(record_error)
+                    }
+                    Err(err) => Err(("record", err.to_string())),
+                })
+                .collect());
+        }
+        Format::Fortune => {
+            return Ok(quotes_from_fortune(content, None)?
+                .quotes
+                .into_iter()
+                .map(Ok)
+                .collect());
+        }
+        Format::JsonLines => {
+            return Ok(content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| match serde_json::from_str(line) {
+                    Ok(record) => decode_record(&record),
+                    Err(err) => Err(("record", err.to_string())),
+                })
+                .collect());
+        }
+    };
+
+    if let Some(version) =
+        dataset.get("schema_version").and_then(|v| v.as_u64())
+    {
+        if version > u64::from(SCHEMA_VERSION) {
+            return Err(QuoteError::UnsupportedSchemaVersion(
+                u32::try_from(version).unwrap_or(u32::MAX),
+            ));
+        }
+    }
+
+    let records =
+        dataset.get("quotes").and_then(|v| v.as_array()).ok_or_else(
+            || QuoteError::ParseError("missing `quotes` list".into()),
+        )?;
+
+    Ok(records.iter().map(decode_record).collect())
+}
+
+/// Decodes a single record.
+fn decode_record(
+    record: &serde_json::Value,
+) -> Result<Quote, RecordError> {
+    serde_json::from_value::<Quote>(record.clone()).map_err(|err| {
+        // Point at the date when that is what failed to decode.
+        match record.get("date_added").and_then(|v| v.as_str()) {
+            Some(date) if QuoteDate::parse(date).is_err() => {
+                record_error(QuoteError::InvalidDate(date.into()))
+            }
+            _ => ("record", err.to_string()),
+        }
+    })
+}
+
+/// Describes a decoding error of a single record.
+fn record_error(error: QuoteError) -> RecordError {
+    match error {
+        QuoteError::InvalidDate(value) => {
+            ("date_added", format!("`{}` is not a valid date", value))
+        }
+        QuoteError::InvalidId(id) => (
+            "id",
+            format!(
+                "`{}` may only hold ASCII letters, digits, `-` and `_`",
+                id
+            ),
+        ),
+        other => ("record", other.to_string()),
+    }
+}
+
+/// Returns the one-based line on which each record starts.
+///
+/// CSV positions come from the CSV reader itself. For the other
+/// formats, each record is located by the line holding its
+/// `quote_text` key (or its `[[quotes]]` header in TOML, or its whole
+/// line in JSON Lines, or its first line in a fortune file), which is
+/// where an editor needs to look. Only the keys of the records
+/// themselves count, not those of nested values such as
+/// `translations`.
+fn record_lines(content: &str, format: Format) -> Vec<usize> {
+    match format {
+        Format::Fortune => fortunes(content)
+            .into_iter()
+            .map(|fortune| fortune.line)
+            .collect(),
+        Format::Csv => {
+            let mut rdr = csv::Reader::from_reader(content.as_bytes());
+            rdr.records()
+                .filter_map(|record| match record {
+                    Ok(record) => {
+                        record.position().map(|pos| pos.line())
+                    }
+                    Err(err) => err.position().map(|pos| pos.line()),
+                })
+                .map(|line| line as usize)
+                .collect()
+        }
+        Format::Json => json_record_lines(content),
+        Format::Yaml => yaml_record_lines(content),
+        Format::Toml => lines_where(content, |line| {
+            line.trim_start().starts_with("[[quotes]]")
+        }),
+        Format::JsonLines => {
+            lines_where(content, |line| !line.trim().is_empty())
+        }
+    }
+}
+
+/// Returns the numbers of the lines for which `is_record` holds.
+fn lines_where(
+    content: &str,
+    is_record: impl Fn(&str) -> bool,
+) -> Vec<usize> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| is_record(line))
+        .map(|(number, _)| number + 1)
+        .collect()
+}
+
+/// Locates the records of a JSON dataset.
+///
+/// The records are the objects two levels inside the dataset, in its
+/// `quotes` list. Each is located by its `quote_text` key, or by its
+/// opening brace if it has none. Like the streaming reader, the scan
+/// tracks how deep it is in objects and arrays, and skips strings, so
+/// the keys of nested values are not mistaken for records.
+fn json_record_lines(content: &str) -> Vec<usize> {
+    /// The depth of the keys of a record: the dataset, the `quotes`
+    /// list and the record itself.
+    const RECORD_DEPTH: usize = 3;
+
+    let bytes = content.as_bytes();
+    let mut lines = Vec::new();
+    let mut line = 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_start = 0;
+    let mut located = false;
+    for (index, &byte) in bytes.iter().enumerate() {
+        if byte == b'\n' {
+            line += 1;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+                let is_key = bytes[index + 1..]
+                    .iter()
+                    .find(|byte| !byte.is_ascii_whitespace())
+                    == Some(&b':');
+                if depth == RECORD_DEPTH
+                    && !located
+                    && is_key
+                    && &bytes[string_start..index] == b"quote_text"
+                {
+                    if let Some(record) = lines.last_mut() {
+                        *record = line;
+                        located = true;
+                    }
+                }
+            }
+            continue;
+        }
+        match byte {
+            b'"' => {
+                in_string = true;
+                string_start = index + 1;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                if byte == b'{' && depth == RECORD_DEPTH {
+                    lines.push(line);
+                    located = false;
+                }
+            }
+            b'}' | b']' => depth = usize::saturating_sub(depth, 1),
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Locates the records of a YAML dataset by their `quote_text` keys.
+///
+/// The keys of a record are indented less than those of its nested
+/// values, so only the least indented `quote_text` keys are records.
+fn yaml_record_lines(content: &str) -> Vec<usize> {
+    let keys: Vec<(usize, usize)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| {
+            let trimmed = line.trim_start();
+            let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+            let key = item.trim_start();
+            let column = line.len() - key.len();
+            let key = key.trim_start_matches(['"', '\'']);
+            (key.starts_with("quote_text") && key.contains(':'))
+                .then_some((number + 1, column))
+        })
+        .collect();
+    let record_column = keys.iter().map(|&(_, column)| column).min();
+    keys.into_iter()
+        .filter(|&(_, column)| Some(column) == record_column)
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Returns `true` if `value` looks like an absolute http(s) URL.
+fn is_http_url(value: &str) -> bool {
+    let rest = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"));
+    match rest {
+        Some(rest) => {
+            let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+            !host.is_empty() && !value.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
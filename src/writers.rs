@@ -0,0 +1,311 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::fortune::{write_fortune, write_fortune_files};
+use crate::quotes::{Format, Quote, QuoteError, Quotes};
+
+impl Quotes {
+    /// Writes the quotes to `writer` in the given format.
+    ///
+    /// JSON is pretty-printed. Every field is kept: CSV flattens the
+    /// richer fields into the same columns that
+    /// [`read_quotes_from_file`](crate::quotes::read_quotes_from_file)
+    /// reads back, and only writes the optional columns that at least
+    /// one quote uses. CSV and JSON Lines have no room for the
+    /// `schema_version` marker, so reading them back yields the version
+    /// of the fields they use, as for any dataset without one. CSV
+    /// extra fields must be strings, and TOML ones cannot hold `null`.
+    /// Fortune files only keep the text and author of each quote, and
+    /// are written without their strfile(8) index.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write the dataset.
+    /// * `format` - The format to write.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if successful, or an error if writing fails or
+    /// the quotes cannot be represented in the format.
+    pub fn write_to(
+        &self,
+        writer: impl Write,
+        format: Format,
+    ) -> Result<(), QuoteError> {
+        self.write(writer, format, true)
+    }
+
+    /// Writes the quotes to `writer` like [`Quotes::write_to`], but with
+    /// JSON on a single line. Other formats are written as usual.
+    pub fn write_compact_to(
+        &self,
+        writer: impl Write,
+        format: Format,
+    ) -> Result<(), QuoteError> {
+        self.write(writer, format, false)
+    }
+
+    /// Writes the quotes, pretty-printing JSON if `pretty` is set.
+    fn write(
+        &self,
+        mut writer: impl Write,
+        format: Format,
+        pretty: bool,
+    ) -> Result<(), QuoteError> {
+        match format {
+            Format::Json => {
+                if pretty {
+                    serde_json::to_writer_pretty(&mut writer, self)
+                } else {
+                    serde_json::to_writer(&mut writer, self)
+                }
+                .map_err(write_error)?;
+                writeln!(writer)?;
+            }
+            Format::JsonLines => {
+                for quote in &self.quotes {
+                    serde_json::to_writer(&mut writer, quote)
+                        .map_err(write_error)?;
+                    writeln!(writer)?;
+                }
+            }
+            Format::Yaml => {
+                serde_yaml::to_writer(&mut writer, self)
+                    .map_err(write_error)?;
+            }
+            Format::Toml => {
+                check_extra(&self.quotes, "TOML", |value| {
+                    !has_null(value)
+                })?;
+                let toml =
+                    toml::to_string(self).map_err(write_error)?;
+                writer.write_all(toml.as_bytes())?;
+            }
+            Format::Csv => write_csv(&self.quotes, &mut writer)?,
+            Format::Fortune => {
+                write_fortune(&self.quotes, &mut writer)?
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes quotes to a file (JSON, CSV, YAML, TOML, JSON Lines or
+/// fortune).
+///
+/// The format is inferred from the file extension, as in
+/// [`read_quotes_from_file`](crate::quotes::read_quotes_from_file).
+/// Fortune files also get their strfile(8) index, as in
+/// [`write_fortune_files`].
+///
+/// # Arguments
+///
+/// * `quotes` - The quotes to write.
+/// * `file_path` - Path to the file to create or replace.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful, or an error if the file cannot be
+/// written.
+pub fn write_quotes_to_file(
+    quotes: &Quotes,
+    file_path: &str,
+) -> Result<(), QuoteError> {
+    let format = Format::from_path(file_path)?;
+    if format == Format::Fortune {
+        return write_fortune_files(quotes, file_path);
+    }
+    quotes.write_to(BufWriter::new(File::create(file_path)?), format)
+}
+
+/// Checks that every extra field of `quotes` can be written in
+/// `format`, according to `holds`.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if they all can, or a `QuoteError::WriteError`
+/// naming the first quote and field that cannot.
+fn check_extra(
+    quotes: &[Quote],
+    format: &str,
+    holds: impl Fn(&serde_json::Value) -> bool,
+) -> Result<(), QuoteError> {
+    for quote in quotes {
+        if let Some((key, value)) =
+            quote.extra.iter().find(|(_, value)| !holds(value))
+        {
+            return Err(QuoteError::WriteError(format!(
+                "`{}` has a `{}` field of `{}`, which {} cannot hold",
+                quote.stable_id(),
+                key,
+                value,
+                format
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if `value` is or holds a `null`, which TOML has no
+/// way to write.
+fn has_null(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::Array(values) => values.iter().any(has_null),
+        serde_json::Value::Object(fields) => {
+            fields.values().any(has_null)
+        }
+        _ => false,
+    }
+}
+
+/// Describes a serialization error as a `QuoteError`.
+fn write_error(error: impl std::fmt::Display) -> QuoteError {
+    QuoteError::WriteError(error.to_string())
+}
+
+/// The optional CSV columns, in the order they are written.
+const OPTIONAL_COLUMNS: [&str; 9] = [
+    "id",
+    "tags",
+    "source_kind",
+    "source_title",
+    "source_url",
+    "language",
+    "image_alt",
+    "notes",
+    "weight",
+];
+
+/// Returns the CSV cell of an optional column, empty when unset.
+fn optional_cell(quote: &Quote, column: &str) -> String {
+    let source = quote.source.as_ref();
+    match column {
+        "id" => quote.id.clone(),
+        "tags" => Some(quote.tags.join(";")),
+        "source_kind" => source
+            .and_then(|source| source.kind)
+            .and_then(|kind| match serde_json::to_value(kind) {
+                Ok(serde_json::Value::String(kind)) => Some(kind),
+                _ => None,
+            }),
+        "source_title" => {
+            source.and_then(|source| source.title.clone())
+        }
+        "source_url" => source.and_then(|source| source.url.clone()),
+        "language" => quote.language.clone(),
+        "image_alt" => quote.image_alt.clone(),
+        "notes" => quote.notes.clone(),
+        "weight" => quote.weight.map(|weight| weight.to_string()),
+        _ => None,
+    }
+    .unwrap_or_default()
+}
+
+/// The translated fields, written as `<field>.<language>` CSV columns.
+const TRANSLATED_FIELDS: [&str; 3] =
+    ["quote_text", "author", "image_alt"];
+
+/// Returns the CSV cell of a translated field, empty when unset.
+fn translated_cell(
+    quote: &Quote,
+    field: &str,
+    language: &str,
+) -> String {
+    let Some(translation) = quote.translations.get(language) else {
+        return String::new();
+    };
+    match field {
+        "quote_text" => Some(translation.quote_text.clone()),
+        "author" => translation.author.clone(),
+        _ => translation.image_alt.clone(),
+    }
+    .unwrap_or_default()
+}
+
+/// Writes quotes as CSV, one row per quote.
+///
+/// Translations get a `<field>.<language>` column for each translated
+/// field. Extra fields get a column each. They must be strings, as
+/// every cell is read back as one.
+fn write_csv(
+    quotes: &[Quote],
+    writer: impl Write,
+) -> Result<(), QuoteError> {
+    check_extra(quotes, "CSV", serde_json::Value::is_string)?;
+    let optional: Vec<&str> = OPTIONAL_COLUMNS
+        .into_iter()
+        .filter(|column| {
+            quotes
+                .iter()
+                .any(|quote| !optional_cell(quote, column).is_empty())
+        })
+        .collect();
+    let translated: Vec<(&str, &str)> = quotes
+        .iter()
+        .flat_map(|quote| quote.translations.keys().map(String::as_str))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .flat_map(|language| {
+            TRANSLATED_FIELDS
+                .into_iter()
+                .map(move |field| (field, language))
+        })
+        .filter(|(field, language)| {
+            quotes.iter().any(|quote| {
+                !translated_cell(quote, field, language).is_empty()
+            })
+        })
+        .collect();
+    let translated_columns: Vec<String> = translated
+        .iter()
+        .map(|(field, language)| format!("{}.{}", field, language))
+        .collect();
+    let extra: BTreeSet<&str> = quotes
+        .iter()
+        .flat_map(|quote| quote.extra.keys().map(String::as_str))
+        .collect();
+
+    let mut wtr = csv::Writer::from_writer(writer);
+    let mut header =
+        vec!["quote_text", "author", "date_added", "image_url"];
+    header.extend(&optional);
+    header.extend(translated_columns.iter().map(String::as_str));
+    header.extend(&extra);
+    wtr.write_record(&header).map_err(write_error)?;
+
+    for quote in quotes {
+        let mut row = vec![
+            quote.quote_text.clone(),
+            quote.author.clone(),
+            quote
+                .date_added
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            quote.image_url.clone(),
+        ];
+        row.extend(
+            optional.iter().map(|column| optional_cell(quote, column)),
+        );
+        row.extend(translated.iter().map(|(field, language)| {
+            translated_cell(quote, field, language)
+        }));
+        row.extend(extra.iter().map(|key| {
+            quote
+                .extra
+                .get(*key)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        }));
+        wtr.write_record(&row).map_err(write_error)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Fixtures shared by the integration tests.
+//!
+//! Each test crate uses only some of them, so unused ones are allowed.
+#![allow(dead_code)]
+
+use wiserone::quotes::{Quote, Quotes};
+
+/// Writes `contents` to a uniquely named file in the system temporary
+/// directory and returns its path.
+pub fn write_temp_file(
+    name: &str,
+    contents: &str,
+) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "wiserone-{}-{}",
+        std::process::id(),
+        name
+    ));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// Builds `n` quotes on consecutive days.
+pub fn numbered_quotes(n: usize) -> Quotes {
+    Quotes::new(
+        (0..n)
+            .map(|i| Quote {
+                quote_text: format!("Quote {}", i),
+                author: "Author".to_string(),
+                date_added: Some(
+                    format!("2024-01-{:02}", i + 1).parse().unwrap(),
+                ),
+                image_url: "https://example.com/image.jpg".to_string(),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
+/// Builds a quote by `author`, tagged with `tags`, on `date`.
+pub fn quote(
+    text: &str,
+    author: &str,
+    tags: &[&str],
+    date: &str,
+) -> Quote {
+    Quote {
+        quote_text: text.to_string(),
+        author: author.to_string(),
+        date_added: Some(date.parse().unwrap()),
+        image_url: "https://example.com/image.jpg".to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+/// Builds a quote by `author` on the given day of January 2024.
+pub fn quote_on_day(text: &str, author: &str, day: u32) -> Quote {
+    quote(text, author, &[], &format!("2024-01-{:02}", day))
+}
@@ -0,0 +1,173 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::path::Path;
+
+use wiserone::archive::{generate_archive, ArchiveEntry};
+use wiserone::config::SiteConfig;
+use wiserone::quotes::Quote;
+use wiserone::sitemap::generate_sitemap_file;
+
+/// Returns a quote added on `date`.
+fn quote(text: &str, date: &str) -> Quote {
+    serde_json::from_value(serde_json::json!({
+        "quote_text": text,
+        "author": "The Wiser One",
+        "date_added": date,
+        "image_url": "https://kura.pro/banner.webp"
+    }))
+    .unwrap()
+}
+
+/// Returns a site writing into `output` with the shipped layouts.
+fn site(output: &Path, page_size: usize) -> SiteConfig {
+    let mut site = SiteConfig {
+        base_url: "https://example.com".to_string(),
+        ..SiteConfig::default()
+    };
+    site.paths.layouts =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("_layouts");
+    site.paths.output = output.to_path_buf();
+    site.archive.page_size = page_size;
+    site
+}
+
+/// Test that the archive lists every quote by page, year and month.
+#[test]
+fn test_generate_archive() {
+    let output = std::env::temp_dir().join("wiserone-test-archive");
+    let _ = fs::remove_dir_all(&output);
+    let site = site(&output, 2);
+
+    let entries: Vec<ArchiveEntry> = [
+        ("Oldest & first.", "2023-12-31"),
+        ("New year.", "2024-01-01"),
+        ("Mid January.", "2024-01-15"),
+        ("Newest.", "2024-02-29"),
+    ]
+    .iter()
+    .map(|(text, date)| {
+        let quote = quote(text, date);
+        let filename =
+            format!("{}.html", quote.date_added.unwrap().slug());
+        ArchiveEntry::new(&quote, &filename, &site).unwrap()
+    })
+    .collect();
+
+    // A page left from a larger archive is removed
+    fs::create_dir_all(output.join("archive/page")).unwrap();
+    fs::write(output.join("archive/page/9.html"), "stale").unwrap();
+
+    let written = generate_archive(&entries, &site).unwrap();
+    let mut relative: Vec<String> = written
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&output)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    relative.sort();
+    assert_eq!(
+        relative,
+        [
+            "archive/2023/12/index.html",
+            "archive/2023/index.html",
+            "archive/2024/01/index.html",
+            "archive/2024/02/index.html",
+            "archive/2024/index.html",
+            "archive/index.html",
+            "archive/page/2.html",
+        ]
+    );
+    assert!(!output.join("archive/page/9.html").exists());
+
+    // The first page holds the newest quotes, and links to the next
+    let first =
+        fs::read_to_string(output.join("archive/index.html")).unwrap();
+    let newest = first.find("Newest.").unwrap();
+    let mid = first.find("Mid January.").unwrap();
+    assert!(newest < mid);
+    assert!(!first.contains("New year."));
+    assert!(first.contains(
+        "href=\"https://example.com/2024_02_29.html\">Newest.</a>"
+    ));
+    assert!(first.contains(
+        "The Wiser One,\n          <time datetime=\"2024-02-29\">29 February 2024</time>"
+    ));
+    assert!(first.contains(
+        "href=\"https://example.com/archive/page/2.html\" rel=\"next\""
+    ));
+    assert!(!first.contains("rel=\"prev\""));
+    assert!(first.contains(
+        "<link rel=\"canonical\" href=\"https://example.com/archive/index.html\" />"
+    ));
+
+    let second =
+        fs::read_to_string(output.join("archive/page/2.html")).unwrap();
+    assert!(second.contains("Oldest &amp; first."));
+    assert!(second.contains("rel=\"prev\""));
+
+    // Years list their months, and months their quotes
+    let year =
+        fs::read_to_string(output.join("archive/2024/index.html"))
+            .unwrap();
+    assert!(year.contains(
+        "<a href=\"https://example.com/archive/2024/02/index.html\">February</a> (1)"
+    ));
+    assert!(year.contains(
+        "<a href=\"https://example.com/archive/2024/01/index.html\">January</a> (2)"
+    ));
+    assert!(!year.contains("Oldest"));
+    let month =
+        fs::read_to_string(output.join("archive/2024/01/index.html"))
+            .unwrap();
+    assert!(month
+        .contains("<title>Quotes of January 2024 | wiserone</title>"));
+    assert!(
+        month.contains("New year.") && month.contains("Mid January.")
+    );
+    assert!(!month.contains("Newest."));
+
+    // The sitemap lists the archive pages
+    generate_sitemap_file(
+        "https://example.com/",
+        &output,
+        &site.locale,
+    )
+    .unwrap();
+    let sitemap =
+        fs::read_to_string(output.join("sitemap.xml")).unwrap();
+    for path in &relative {
+        assert!(
+            sitemap.contains(&format!(
+                "<loc>https://example.com/{}</loc>",
+                path
+            )),
+            "{}",
+            path
+        );
+    }
+    assert!(!sitemap.contains("hreflang=\"archive\""));
+
+    fs::remove_dir_all(&output).unwrap();
+}
+
+/// Test that an archive without quotes still has its first page.
+#[test]
+fn test_generate_empty_archive() {
+    let output =
+        std::env::temp_dir().join("wiserone-test-archive-empty");
+    let _ = fs::remove_dir_all(&output);
+
+    let written = generate_archive(&[], &site(&output, 30)).unwrap();
+    assert_eq!(written, [output.join("archive").join("index.html")]);
+    let page = fs::read_to_string(&written[0]).unwrap();
+    assert!(!page.contains("<li class=\"py-2"));
+    assert!(!page.contains("pagination"));
+
+    fs::remove_dir_all(&output).unwrap();
+}
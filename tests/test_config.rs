@@ -0,0 +1,136 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::path::Path;
+
+use wiserone::config::{SiteConfig, DEFAULT_CONFIG_FILE};
+use wiserone::error::WiseroneError;
+
+/// Returns the message of a configuration error.
+fn config_error(result: Result<SiteConfig, WiseroneError>) -> String {
+    match result {
+        Err(WiseroneError::Config(message)) => message,
+        other => panic!("expected a config error, got {:?}", other),
+    }
+}
+
+/// Test that the shipped configuration describes The Wiser One.
+#[test]
+fn test_shipped_config_matches_the_defaults() {
+    let shipped = SiteConfig::load(DEFAULT_CONFIG_FILE).unwrap();
+    assert_eq!(shipped, SiteConfig::default());
+    assert_eq!(shipped.paths.layouts, Path::new("_layouts"));
+    assert_eq!(shipped.paths.output, Path::new("docs"));
+    assert_eq!(shipped.home(), "https://wiserone.com");
+    assert_eq!(
+        shipped.analytics.measurement_id.as_deref(),
+        Some("G-4HKZ6N3QSC")
+    );
+}
+
+/// Test that a configuration only needs the keys that differ.
+#[test]
+fn test_config_keeps_defaults_for_missing_keys() {
+    let toml = "base_url = \"https://quotes.example.com/\"\n\
+                name = \"example\"\n\
+                locale = \"fr\"\n\
+                [analytics]\n\
+                [social]\n\
+                mastodon = \"@example@example.social\"\n";
+    let config = SiteConfig::from_toml(toml).unwrap();
+    assert_eq!(config.name, "example");
+    assert_eq!(config.locale, "fr");
+    assert_eq!(config.cdn, SiteConfig::default().cdn);
+    assert_eq!(config.analytics.measurement_id, None);
+    assert_eq!(config.social.twitter, None);
+    assert_eq!(
+        config.social.mastodon.as_deref(),
+        Some("@example@example.social")
+    );
+    assert_eq!(config.home(), "https://quotes.example.com");
+    assert_eq!(
+        config.page_url("fr/index.html"),
+        "https://quotes.example.com/fr/index.html"
+    );
+    assert_eq!(config.page_url(""), "https://quotes.example.com/");
+
+    let yaml = "base_url: https://quotes.example.com\n\
+                social:\n  github: example\n";
+    let config = SiteConfig::from_yaml(yaml).unwrap();
+    assert_eq!(config.social.github.as_deref(), Some("example"));
+    assert_eq!(config.name, "wiserone");
+    assert_eq!(
+        SiteConfig::from_yaml("").unwrap(),
+        SiteConfig::default()
+    );
+    assert_eq!(
+        SiteConfig::from_toml("").unwrap(),
+        SiteConfig::default()
+    );
+}
+
+/// Test that mistakes in a configuration are reported.
+#[test]
+fn test_config_errors() {
+    assert!(config_error(SiteConfig::from_toml("baseurl = \"x\""))
+        .contains("unknown field `baseurl`"));
+    assert!(config_error(SiteConfig::from_yaml(
+        "social: {facebook: x}"
+    ))
+    .contains("unknown field `facebook`"));
+    assert_eq!(
+        config_error(SiteConfig::from_toml("cdn = \"kura.pro\"")),
+        "`cdn` must be an http or https URL, found `kura.pro`"
+    );
+    assert_eq!(
+        config_error(SiteConfig::from_toml("name = \" \"")),
+        "`name` must not be empty"
+    );
+    assert_eq!(
+        config_error(SiteConfig::from_toml("[archive]\npage_size = 0")),
+        "`archive.page_size` must be at least 1"
+    );
+    assert_eq!(
+        config_error(SiteConfig::from_toml("locale = \"en_GB\"")),
+        "`locale` must be a language tag, found `en_GB`"
+    );
+}
+
+/// Test that files are read by their extension.
+#[test]
+fn test_load_config_files() {
+    let directory = std::env::temp_dir().join("wiserone-test-config");
+    fs::create_dir_all(&directory).unwrap();
+
+    let yaml = directory.join("site.yml");
+    fs::write(&yaml, "name: from-yaml\n").unwrap();
+    let config = SiteConfig::load(&yaml).unwrap();
+    assert_eq!(config.name, "from-yaml");
+    assert_eq!(config.paths.output, directory.join("docs"));
+
+    // Relative paths are relative to the configuration file
+    let toml = directory.join("paths.toml");
+    fs::write(
+        &toml,
+        "[paths]\nlayouts = \"theme\"\noutput = \"/srv/site\"\n",
+    )
+    .unwrap();
+    let config = SiteConfig::load(&toml).unwrap();
+    assert_eq!(config.paths.layouts, directory.join("theme"));
+    assert_eq!(config.paths.output, Path::new("/srv/site"));
+    assert_eq!(config.paths.log, directory.join("wiserone.log"));
+    assert!(!serde_json::to_string(&config).unwrap().contains("paths"));
+
+    let toml = directory.join("site.toml");
+    fs::write(&toml, "name = 3\n").unwrap();
+    let message = config_error(SiteConfig::load(&toml));
+    assert!(message.starts_with(&format!("`{}`: ", toml.display())));
+
+    assert!(matches!(
+        SiteConfig::load(directory.join("missing.toml")),
+        Err(WiseroneError::Io(_, _))
+    ));
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,94 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use common::quote_on_day;
+use wiserone::dedupe::{DuplicateKind, DEFAULT_THRESHOLD};
+use wiserone::quotes::{read_quotes_from_file, Quotes};
+
+/// Builds a collection with one exact and one near duplicate.
+fn sample_quotes() -> Quotes {
+    Quotes::new(vec![
+        quote_on_day(
+            "Simplicity is the ultimate sophistication.",
+            "Author",
+            1,
+        ),
+        quote_on_day(
+            "The best way out is always through.",
+            "Author",
+            2,
+        ),
+        quote_on_day(
+            "  “SIMPLICITY is the ultimate   sophistication!” ",
+            "Author",
+            3,
+        ),
+        quote_on_day(
+            "The best way out is allways through",
+            "Author",
+            4,
+        ),
+        quote_on_day("Well begun is half done.", "Author", 5),
+    ])
+}
+
+/// Test that exact and near duplicates are reported with both dates.
+#[test]
+fn test_find_duplicates() {
+    let duplicates = sample_quotes().find_duplicates(DEFAULT_THRESHOLD);
+    let pairs: Vec<_> = duplicates
+        .iter()
+        .map(|duplicate| {
+            (duplicate.first, duplicate.second, duplicate.kind)
+        })
+        .collect();
+    assert_eq!(
+        pairs,
+        [(0, 2, DuplicateKind::Exact), (1, 3, DuplicateKind::Near)]
+    );
+    assert_eq!(
+        duplicates[0].to_string(),
+        "record 3 (2024-01-03) repeats record 1 (2024-01-01)"
+    );
+    assert!(duplicates[1].similarity >= DEFAULT_THRESHOLD);
+    assert!(duplicates[1]
+        .to_string()
+        .contains("% similar to record 2"));
+}
+
+/// Test that a threshold of 1 only reports exact duplicates.
+#[test]
+fn test_find_exact_duplicates_only() {
+    let duplicates = sample_quotes().find_duplicates(1.0);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].kind, DuplicateKind::Exact);
+}
+
+/// Test that deduplication keeps the record listed first in each
+/// group, whatever its date.
+#[test]
+fn test_dedupe_keeps_first() {
+    let (cleaned, dropped) = sample_quotes().dedupe(DEFAULT_THRESHOLD);
+    assert_eq!(dropped.len(), 2);
+    let days: Vec<_> = cleaned
+        .quotes
+        .iter()
+        .map(|quote| quote.day().unwrap())
+        .collect();
+    assert_eq!(days, ["2024-01-01", "2024-01-02", "2024-01-05"]);
+
+    let (cleaned, _) = Quotes::new(vec![
+        quote_on_day("Well begun is half done.", "Author", 9),
+        quote_on_day("Well begun is half done.", "Author", 1),
+    ])
+    .dedupe(DEFAULT_THRESHOLD);
+    assert_eq!(cleaned.quotes.len(), 1);
+    assert_eq!(cleaned.quotes[0].day().unwrap(), "2024-01-09");
+
+    let bundled =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+    assert!(bundled.find_duplicates(DEFAULT_THRESHOLD).is_empty());
+}
@@ -0,0 +1,74 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::error::Error;
+use std::path::Path;
+use wiserone::ascii::ArtError;
+use wiserone::error::WiseroneError;
+use wiserone::quotes::{read_quotes_from_file, QuoteError, Quotes};
+
+/// Returns the quote error carried by `result`, if any.
+fn quote_error<T>(
+    result: Result<T, WiseroneError>,
+) -> Option<QuoteError> {
+    match result {
+        Err(WiseroneError::Quote(err)) => Some(err),
+        _ => None,
+    }
+}
+
+/// Test that quote errors convert into the crate error and can be
+/// matched on.
+#[test]
+fn test_quote_errors_convert() {
+    let empty = Quotes::default();
+    let result =
+        empty.select_random_quote().map_err(WiseroneError::from);
+    assert!(matches!(
+        quote_error(result),
+        Some(QuoteError::NoQuotesAvailable)
+    ));
+
+    let result = read_quotes_from_file("./missing.json")
+        .map_err(WiseroneError::from);
+    let err = result.unwrap_err();
+    assert!(matches!(
+        err,
+        WiseroneError::Quote(QuoteError::IOError(_))
+    ));
+    assert!(err.source().is_some());
+}
+
+/// Test that I/O errors name the offending path.
+#[test]
+fn test_io_error_names_path() {
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+    let err = WiseroneError::io("./docs/page.html", io);
+    match &err {
+        WiseroneError::Io(path, _) => {
+            assert_eq!(path, Path::new("./docs/page.html"))
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(err.to_string(), "I/O Error: `./docs/page.html`: gone");
+    assert!(err.source().is_some());
+}
+
+/// Test the messages of the other variants.
+#[test]
+fn test_error_messages() {
+    let art = WiseroneError::from(ArtError::FontLoadError);
+    assert_eq!(
+        art.to_string(),
+        "ASCII Art Error: Failed to load FIGlet"
+    );
+
+    let failed =
+        WiseroneError::ValidationFailed("quotes.json".into(), 2);
+    assert_eq!(
+        failed.to_string(),
+        "`quotes.json` failed validation with 2 error(s)"
+    );
+    assert!(failed.source().is_none());
+}
@@ -0,0 +1,275 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_json::{json, Value};
+use wiserone::config::SiteConfig;
+use wiserone::error::WiseroneError;
+use wiserone::escape::{css, html, script_string, url, INVALID_URL};
+use wiserone::template::Templates;
+
+/// Quotes written to break out of every place a layout uses them.
+const HOSTILE: [&str; 10] = [
+    "\"</script><script>alert(1)</script>",
+    "He said \"hello\" & she said 'bye'",
+    "<img src=x onerror=alert(1)>",
+    "Line\nbreak\u{2028}separator \\ backslash",
+    "`${alert(1)}` and $money",
+    "--><!-- a comment -->",
+    "{{ quote.author }} {% if %} not a tag",
+    "javascript:alert(1)",
+    "x) ; background: url(evil.png",
+    "</style><h1>Styled</h1>",
+];
+
+/// Renders a single template called `page.html`.
+fn render(
+    source: &str,
+    context: &Value,
+) -> Result<String, WiseroneError> {
+    let mut templates = Templates::new();
+    templates.add("page.html", source)?;
+    templates.render("page.html", context)
+}
+
+/// Builds the context of a quote page, as page generation does.
+fn page_context(text: &str, author: &str, image_url: &str) -> Value {
+    let site = SiteConfig::default();
+    json!({
+        "site": site,
+        "page": {
+            "alternates": [
+                {"hreflang": "en", "url": "https://wiserone.com/2024_01_01.html"}
+            ],
+            "author": author,
+            "home": "https://wiserone.com",
+            "language": "en",
+            "locale": "en",
+            "title": text,
+            "url": "https://wiserone.com/2024_01_01.html"
+        },
+        "quote": {
+            "id": "q-1",
+            "quote_text": text,
+            "author": author,
+            "date_added": "2024-01-01",
+            "image_url": image_url
+        }
+    })
+}
+
+/// Reverses the HTML escaping of text.
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns the text between `start` and the next `end` in `html`.
+fn between<'a>(html: &'a str, start: &str, end: &str) -> &'a str {
+    let from = html.find(start).unwrap() + start.len();
+    let to = from + html[from..].find(end).unwrap();
+    &html[from..to]
+}
+
+/// Counts the characters that can end an element, an attribute or a
+/// string in a script.
+fn delimiters(html: &str) -> [usize; 4] {
+    ['<', '>', '"', '\''].map(|c| html.matches(c).count())
+}
+
+/// Test the escaping of each context.
+#[test]
+fn test_escapers() {
+    let mut output = String::new();
+    html("<a href=\"x\">Tom & Jerry's</a>", &mut output);
+    assert_eq!(
+        output,
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+
+    let mut output = String::new();
+    script_string("\"</script>\\'`${x}`\n\u{2028}", true, &mut output);
+    assert_eq!(
+        output,
+        "\\u0022\\u003c/script\\u003e\\\\\\u0027\\u0060\\u0024{x}\\u0060\\n\\u2028"
+    );
+    let parsed: String =
+        serde_json::from_str(&format!("\"{}\"", output)).unwrap();
+    assert_eq!(parsed, "\"</script>\\'`${x}`\n\u{2028}");
+
+    let mut output = String::new();
+    css("a.jpg) ; color: red", &mut output);
+    assert_eq!(output, "a.jpg\\29 \\20 \\3b \\20 color:\\20 red");
+
+    assert_eq!(
+        url("https://e.com/a b\"c?q=1&r=<2>", true),
+        "https://e.com/a%20b%22c?q=1&r=%3C2%3E"
+    );
+    assert_eq!(url("/fr/été.html", true), "/fr/%C3%A9t%C3%A9.html");
+    for unsafe_url in [
+        "javascript:alert(1)",
+        "JavaScript:alert(1)",
+        " javascript:alert(1)",
+        "java\tscript:alert(1)",
+        "data:text/html,<script>",
+        "vbscript:msgbox",
+    ] {
+        assert_eq!(
+            url(unsafe_url, true),
+            INVALID_URL,
+            "{}",
+            unsafe_url
+        );
+    }
+    assert_eq!(
+        url("javascript:alert(1)", false),
+        "javascript:alert(1)"
+    );
+    assert_eq!(url("mailto:a@b.c", true), "mailto:a@b.c");
+    assert_eq!(url("notes:1/2", true), INVALID_URL);
+    assert_eq!(url("a/b:c", true), "a/b:c");
+}
+
+/// Test that values are escaped for the place they are written.
+#[test]
+fn test_render_escapes_by_context() {
+    let context = json!({
+        "text": "\"O'Neil\" <b> & co",
+        "link": "javascript:alert(1)",
+        "path": "a b/c",
+        "tags": ["<a>", "b"],
+        "number": 7
+    });
+    let cases = [
+        ("<p>{{ text }}</p>", "<p>&quot;O&#39;Neil&quot; &lt;b&gt; &amp; co</p>"),
+        (
+            "<p title='{{ text }}'>",
+            "<p title='&quot;O&#39;Neil&quot; &lt;b&gt; &amp; co'>",
+        ),
+        ("<a href=\"{{ link }}\">", "<a href=\"about:invalid\">"),
+        (
+            "<a href=\"https://e.com/{{ path }}?q={{ text }}\">",
+            "<a href=\"https://e.com/a%20b/c?q=%22O%27Neil%22%20%3Cb%3E%20&amp;%20co\">",
+        ),
+        (
+            "<div style=\"background: url({{ path }})\">",
+            "<div style=\"background: url(a\\20 b/c)\">",
+        ),
+        ("<style>p::after { content: '{{ path }}' }</style>",
+         "<style>p::after { content: 'a\\20 b/c' }</style>"),
+        (
+            "<script>let a = \"{{ text }}\", b = '{{ text }}';</script>",
+            "<script>let a = \"\\u0022O\\u0027Neil\\u0022 \\u003cb\\u003e \\u0026 co\", \
+             b = '\\u0022O\\u0027Neil\\u0022 \\u003cb\\u003e \\u0026 co';</script>",
+        ),
+        (
+            "<script>let tags = {{ tags }}, n = {{ number }};</script>",
+            "<script>let tags = [\"\\u003ca\\u003e\",\"b\"], n = 7;</script>",
+        ),
+        (
+            "<script>// it's a comment\nlet a = '{{ path }}';</script><p>{{ path }}</p>",
+            "<script>// it's a comment\nlet a = 'a b/c';</script><p>a b/c</p>",
+        ),
+        ("<!-- {{ text }} -->", "<!-- &quot;O&#39;Neil&quot; &lt;b&gt; &amp; co -->"),
+        ("<!DOCTYPE html><p>{{ number }}</p>", "<!DOCTYPE html><p>7</p>"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(
+            render(source, &context).unwrap(),
+            expected,
+            "{}",
+            source
+        );
+    }
+
+    for (source, message) in [
+        ("<div {{ text }}>", "quoted attribute"),
+        ("<div class={{ text }}>", "quoted attribute"),
+        ("<a onclick=\"go('{{ text }}')\">", "event handlers"),
+        ("<script>/* {{ text }} */</script>", "script comments"),
+    ] {
+        match render(source, &context) {
+            Err(WiseroneError::Template(error)) => {
+                assert!(
+                    error.contains(message),
+                    "{}: {}",
+                    source,
+                    error
+                )
+            }
+            other => {
+                panic!("{}: expected an error, got {:?}", source, other)
+            }
+        }
+    }
+}
+
+/// Test that hostile quotes leave every part of the quote page intact.
+#[test]
+fn test_hostile_quotes_keep_the_layout_well_formed() {
+    let templates = Templates::load("_layouts").unwrap();
+    let plain = templates
+        .render(
+            "quote.html",
+            &page_context(
+                "Plain",
+                "Author",
+                "https://example.com/a.jpg",
+            ),
+        )
+        .unwrap();
+    let expected = delimiters(&plain);
+
+    for hostile in HOSTILE {
+        let page = templates
+            .render(
+                "quote.html",
+                &page_context(hostile, hostile, hostile),
+            )
+            .unwrap();
+
+        // No value adds a character that could close what holds it
+        assert_eq!(delimiters(&page), expected, "{}", hostile);
+        if hostile.starts_with("javascript:") {
+            assert!(page.contains(
+                "<link rel=\"preload\" as=\"image\" href=\"about:invalid\">"
+            ));
+        }
+        for attribute in ["href=\"", "src=\""] {
+            for value in page.split(attribute).skip(1) {
+                assert!(
+                    !value.starts_with("javascript:"),
+                    "{}",
+                    hostile
+                );
+            }
+        }
+
+        // The structured data parses back to the quote
+        let json_ld = between(
+            &page,
+            "type=\"application/ld+json\">",
+            "</script>",
+        );
+        let data: Value = serde_json::from_str(json_ld).unwrap();
+        assert_eq!(data["headline"], hostile);
+        assert_eq!(data["author"], hostile);
+        assert_eq!(data["copyrightHolder"]["@id"], hostile);
+
+        // The text and attributes read back as the quote
+        let heading = between(
+            &page,
+            "<h1 class=\"position-relative fs-2 text-theme\">",
+            "</h1>",
+        );
+        assert_eq!(unescape_html(heading), hostile);
+        let title =
+            between(&page, "<meta content=\"", "\" name=\"author\">");
+        assert_eq!(unescape_html(title), hostile);
+        let alt = between(&page, "<img alt=\"", "\" class=");
+        assert_eq!(unescape_html(alt), hostile);
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use wiserone::fortune::{quotes_from_fortune, strfile_index};
+use wiserone::quotes::{read_quotes_from_file, Format, Quote, Quotes};
+use wiserone::schedule::SchedulePolicy;
+use wiserone::writers::write_quotes_to_file;
+
+const COOKIES: &str = "\
+%% A comment
+Know thyself.
+\t\t-- Socrates
+%
+The best way out
+is always through.
+   — Robert Frost
+
+%
+
+%
+An unattributed saying.
+%
+";
+
+/// Test that fortunes are split into quotes on consecutive days.
+#[test]
+fn test_quotes_from_fortune() {
+    let quotes = quotes_from_fortune(
+        COOKIES,
+        Some("2024-01-01".parse().unwrap()),
+    )
+    .unwrap();
+    let read: Vec<(&str, &str, String)> = quotes
+        .quotes
+        .iter()
+        .map(|quote| {
+            (
+                quote.quote_text.as_str(),
+                quote.author.as_str(),
+                quote.day().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        read,
+        vec![
+            ("Know thyself.", "Socrates", "2024-01-01".to_string()),
+            (
+                "The best way out\nis always through.",
+                "Robert Frost",
+                "2024-01-02".to_string()
+            ),
+            ("An unattributed saying.", "", "2024-01-03".to_string()),
+        ]
+    );
+}
+
+/// Test that writing and reading a fortune file keeps texts and
+/// authors.
+#[test]
+fn test_fortune_round_trip() {
+    let quotes = Quotes::new(vec![
+        Quote {
+            quote_text: "Carpe diem.".to_string(),
+            author: "Horace".to_string(),
+            ..Default::default()
+        },
+        Quote {
+            quote_text: "Two\nlines.".to_string(),
+            ..Default::default()
+        },
+    ]);
+    let mut buffer = Vec::new();
+    quotes.write_to(&mut buffer, Format::Fortune).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert_eq!(text, "Carpe diem.\n\t\t-- Horace\n%\nTwo\nlines.\n%\n");
+
+    let read = Quotes::from_str(&text, Format::Fortune).unwrap();
+    assert_eq!(read.quotes.len(), 2);
+    assert_eq!(read.quotes[0].author, "Horace");
+    assert_eq!(read.quotes[1].quote_text, "Two\nlines.");
+
+    let delimiter = Quotes::new(vec![Quote {
+        quote_text: "Half\n%\nand half".to_string(),
+        ..Default::default()
+    }]);
+    assert!(delimiter.write_to(Vec::new(), Format::Fortune).is_err());
+}
+
+/// Test that fortune quotes are read without dates, and that
+/// scheduling gives them real days.
+#[test]
+fn test_fortune_quotes_are_unscheduled() {
+    let mut quotes =
+        Quotes::from_str(COOKIES, Format::Fortune).unwrap();
+    assert!(quotes
+        .quotes
+        .iter()
+        .all(|quote| quote.date_added.is_none()));
+
+    let mut json = Vec::new();
+    Quotes::from_str(
+        "Know thyself.\n\t\t-- Socrates\n%\n",
+        Format::Fortune,
+    )
+    .unwrap()
+    .write_compact_to(&mut json, Format::Json)
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        "{\"schema_version\":2,\"quotes\":[{\"quote_text\":\"Know thyself.\",\
+         \"author\":\"Socrates\",\"image_url\":\"\"}]}\n"
+    );
+
+    let unscheduled = quotes.take_unscheduled();
+    let _ = quotes
+        .schedule(
+            unscheduled,
+            "2025-01-01".parse().unwrap(),
+            "2025-01-10".parse().unwrap(),
+            SchedulePolicy::Sequential,
+        )
+        .unwrap();
+    let days: Vec<String> =
+        quotes.quotes.iter().filter_map(Quote::day).collect();
+    assert_eq!(days, ["2025-01-01", "2025-01-02", "2025-01-03"]);
+}
+
+/// Test that the index matches the layout written by strfile.
+#[test]
+fn test_strfile_index() {
+    let index = strfile_index(b"a\n%\nbb\n%\n");
+    let words: Vec<u32> = index[..20]
+        .chunks(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+        .collect();
+    // Version, fortunes, longest and shortest length, flags.
+    assert_eq!(words, vec![2, 2, 3, 2, 0]);
+    assert_eq!(&index[20..24], b"%\0\0\0");
+    let offsets: Vec<u32> = index[24..]
+        .chunks(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()))
+        .collect();
+    assert_eq!(offsets, vec![0, 4, 9]);
+
+    // A last fortune without a trailing delimiter still counts.
+    assert_eq!(strfile_index(b"a\n%\nbb\n")[4..8], 2_u32.to_be_bytes());
+}
+
+/// Test that writing a `.fortune` file also writes its index.
+#[test]
+fn test_write_fortune_file() {
+    let path = std::env::temp_dir()
+        .join(format!("wiserone-{}.fortune", std::process::id()));
+    let path = path.to_str().unwrap();
+    let quotes =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+    write_quotes_to_file(&quotes, path).unwrap();
+
+    let fortune = std::fs::read(path).unwrap();
+    let index = std::fs::read(format!("{}.dat", path)).unwrap();
+    assert_eq!(index, strfile_index(&fortune));
+    assert_eq!(
+        u32::from_be_bytes(index[4..8].try_into().unwrap()) as usize,
+        quotes.quotes.len()
+    );
+    let read = read_quotes_from_file(path).unwrap();
+    assert_eq!(read.quotes.len(), quotes.quotes.len());
+    assert_eq!(read.quotes[0].quote_text, quotes.quotes[0].quote_text);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(format!("{}.dat", path)).unwrap();
+}
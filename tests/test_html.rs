@@ -0,0 +1,152 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::path::Path;
+
+use wiserone::config::SiteConfig;
+use wiserone::error::WiseroneError;
+use wiserone::html::{generate_html_file, generate_html_files};
+use wiserone::quotes::Quote;
+
+/// Test that pages are built from and into the configured paths.
+#[test]
+fn test_generate_html_file_uses_the_configured_paths() {
+    let root = std::env::temp_dir().join("wiserone-test-html");
+    let _ = fs::remove_dir_all(&root);
+
+    let mut site = SiteConfig::default();
+    site.paths.layouts =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("_layouts");
+    site.paths.output = root.join("public");
+    site.paths.log = root.join("build.log");
+
+    let quote: Quote = serde_json::from_str(
+        r#"{
+            "quote_text": "Know thyself.",
+            "author": "The Wiser One",
+            "date_added": "2024-01-01",
+            "image_url": "https://kura.pro/banner.webp"
+        }"#,
+    )
+    .unwrap();
+    generate_html_file("2024_01_01.html", &quote, &site).unwrap();
+
+    let page =
+        fs::read_to_string(root.join("public").join("2024_01_01.html"))
+            .unwrap();
+    assert!(page.contains("Know thyself."));
+    let log = fs::read_to_string(root.join("build.log")).unwrap();
+    assert!(log.contains("2024_01_01.html"));
+
+    // A layouts directory without a quote layout is reported
+    site.paths.layouts = root.join("missing");
+    match generate_html_file("2024_01_01.html", &quote, &site) {
+        Err(WiseroneError::Io(path, _)) => {
+            assert_eq!(path, root.join("missing"))
+        }
+        other => panic!("expected an I/O error, got {:?}", other),
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+/// Test that a run over only some quotes writes their pages but keeps
+/// the archive of every quote.
+#[test]
+fn test_generate_html_files_keeps_the_archive_when_filtered() {
+    let root = std::env::temp_dir().join("wiserone-test-filtered");
+    let _ = fs::remove_dir_all(&root);
+
+    let mut site = SiteConfig::default();
+    site.paths.layouts =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("_layouts");
+    site.paths.output = root.join("public");
+    site.paths.log = root.join("build.log");
+
+    let quotes: Vec<Quote> =
+        [("Socrates", "2024-01-01"), ("Horace", "2024-01-02")]
+            .iter()
+            .map(|(author, date)| {
+                serde_json::from_value(serde_json::json!({
+                    "quote_text": format!("Said by {}.", author),
+                    "author": author,
+                    "date_added": date,
+                    "image_url": "https://kura.pro/banner.webp"
+                }))
+                .unwrap()
+            })
+            .collect();
+    let filename = |quote: &Quote| {
+        format!("{}.html", quote.date_added.unwrap().slug())
+    };
+    let index = root.join("public/archive/index.html");
+
+    let written = generate_html_files(
+        quotes.iter().cloned().map(Ok),
+        filename,
+        true,
+        &site,
+    )
+    .unwrap();
+    assert_eq!(written, 2);
+    let archive = fs::read_to_string(&index).unwrap();
+    assert!(
+        archive.contains("2024_01_01.html")
+            && archive.contains("2024_01_02.html")
+    );
+
+    // Only Horace's page is written again, and the archive is kept
+    fs::remove_file(root.join("public").join("2024_01_02.html"))
+        .unwrap();
+    let written = generate_html_files(
+        quotes[1..].iter().cloned().map(Ok),
+        filename,
+        false,
+        &site,
+    )
+    .unwrap();
+    assert_eq!(written, 1);
+    assert!(root.join("public").join("2024_01_02.html").exists());
+    assert_eq!(fs::read_to_string(&index).unwrap(), archive);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+/// Test that a page is never written outside the output directory,
+/// whatever the id of its quote.
+#[test]
+fn test_unsafe_page_names_are_refused() {
+    let root = std::env::temp_dir().join("wiserone-test-traversal");
+    let _ = fs::remove_dir_all(&root);
+
+    let mut site = SiteConfig::default();
+    site.paths.layouts =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("_layouts");
+    site.paths.output = root.join("out");
+    site.paths.log = root.join("build.log");
+
+    let quote = Quote {
+        quote_text: "Know thyself.".to_string(),
+        author: "The Wiser One".to_string(),
+        date_added: Some("2024-01-01".parse().unwrap()),
+        image_url: "https://kura.pro/banner.webp".to_string(),
+        id: Some("../escaped".to_string()),
+        ..Default::default()
+    };
+    let by_id = |quote: &Quote| format!("{}.html", quote.stable_id());
+    match generate_html_files([Ok(quote.clone())], by_id, true, &site) {
+        Err(WiseroneError::Template(message)) => assert_eq!(
+            message,
+            "`../escaped.html` is not a safe page name"
+        ),
+        other => panic!("expected a template error, got {:?}", other),
+    }
+    assert!(
+        generate_html_file("../escaped.html", &quote, &site).is_err()
+    );
+    assert!(!root.join("escaped.html").exists());
+
+    fs::remove_dir_all(&root).unwrap();
+}
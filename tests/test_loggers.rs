@@ -37,4 +37,17 @@ mod tests {
         assert_eq!(log_entry.description, expected_description);
         assert_eq!(log_entry.format, expected_format);
     }
+
+    #[test]
+    fn test_create_log_file_creates_its_directory() {
+        let root = std::env::temp_dir().join("wiserone-test-log");
+        let _ = std::fs::remove_dir_all(&root);
+        let path = root.join("logs").join("wiserone.log");
+
+        let file = wiserone::loggers::create_log_file(&path).unwrap();
+        drop(file);
+        assert!(path.is_file());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,131 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use common::quote;
+use vrd::Random;
+use wiserone::query::{quote_weight, QuoteFilter, TagWeight};
+use wiserone::quotes::{QuoteError, Quotes};
+
+/// Builds a small collection covering several authors and tags.
+fn sample_quotes() -> Quotes {
+    let mut french =
+        quote("Le doute est un hommage.", "Camus", &[], "2024-03-01");
+    french.language = Some("fr-FR".to_string());
+    Quotes::new(vec![
+        quote("Know thyself.", "Socrates", &["wisdom"], "2024-01-01"),
+        quote("Carpe diem.", "Horace", &["time", "life"], "2024-02-01"),
+        quote(
+            "The unexamined life is not worth living.",
+            "Socrates",
+            &["Life"],
+            "2024-02-15T23:00:00+01:00",
+        ),
+        french,
+    ])
+}
+
+/// Returns the texts of the quotes kept by `filter`.
+fn texts(filter: &QuoteFilter) -> Vec<String> {
+    sample_quotes()
+        .filter(filter)
+        .quotes
+        .into_iter()
+        .map(|quote| quote.quote_text)
+        .collect()
+}
+
+/// Test filtering by author, tag, language and text length.
+#[test]
+fn test_filter_by_fields() {
+    let by_author = QuoteFilter {
+        author: Some(" socrates".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        texts(&by_author),
+        ["Know thyself.", "The unexamined life is not worth living."]
+    );
+
+    let by_tag = QuoteFilter {
+        tags: vec!["life".to_string(), "wisdom".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(texts(&by_tag).len(), 3);
+
+    let by_language = QuoteFilter {
+        language: Some("fr".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(texts(&by_language), ["Le doute est un hommage."]);
+
+    let by_length = QuoteFilter {
+        min_length: Some(12),
+        max_length: Some(30),
+        ..Default::default()
+    };
+    assert_eq!(
+        texts(&by_length),
+        ["Know thyself.", "Le doute est un hommage."]
+    );
+
+    assert_eq!(texts(&QuoteFilter::default()).len(), 4);
+    assert!(QuoteFilter::default().is_empty());
+    assert!(!by_author.is_empty() && !by_length.is_empty());
+}
+
+/// Test that date ranges are inclusive and use the calendar day written
+/// in the dataset.
+#[test]
+fn test_filter_by_date_range() {
+    let filter = QuoteFilter {
+        since: Some("2024-02-01".parse().unwrap()),
+        until: Some("2024-02-15".parse().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(
+        texts(&filter),
+        ["Carpe diem.", "The unexamined life is not worth living."]
+    );
+}
+
+/// Test that weighted selection follows quote and tag weights.
+#[test]
+fn test_weighted_selection() {
+    let mut quotes = sample_quotes();
+    for quote in &mut quotes.quotes {
+        quote.weight = Some(0.0);
+    }
+    quotes.quotes[1].weight = Some(2.0);
+
+    let mut rng = Random::from_u64_seed(11);
+    for _ in 0..20 {
+        let selected =
+            quotes.select_weighted_with(&[], &mut rng).unwrap();
+        assert_eq!(selected.quote_text, "Carpe diem.");
+    }
+
+    let boost: TagWeight = "wisdom=3".parse().unwrap();
+    assert_eq!(quote_weight(&sample_quotes().quotes[0], &[boost]), 3.0);
+
+    let silence: TagWeight = "time=0".parse().unwrap();
+    assert!(matches!(
+        quotes.select_weighted_with(&[silence], &mut rng),
+        Err(QuoteError::NoQuotesAvailable)
+    ));
+}
+
+/// Test parsing tag weights.
+#[test]
+fn test_tag_weight_from_str() {
+    let weight: TagWeight = " life = 2.5".parse().unwrap();
+    assert_eq!(
+        weight,
+        TagWeight { tag: "life".to_string(), weight: 2.5 }
+    );
+    assert!("life".parse::<TagWeight>().is_err());
+    assert!("life=often".parse::<TagWeight>().is_err());
+    assert!("life=-1".parse::<TagWeight>().is_err());
+}
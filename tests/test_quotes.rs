@@ -2,8 +2,19 @@
 // Copyright © 2024 The Wiser One. All rights reserved.
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+mod common;
+
+use common::{numbered_quotes, write_temp_file};
 use std::collections::HashSet;
-use wiserone::quotes::{Quote, Quotes};
+use vrd::Random;
+use wiserone::dates::QuoteDate;
+use wiserone::quotes::{
+    is_language_tag, is_slug, read_quotes_from_dir,
+    read_quotes_from_file, read_quotes_from_glob,
+    read_quotes_from_reader, ConflictPolicy, Format, Quote, QuoteError,
+    Quotes, Source, SourceKind, Translation, DEFAULT_LANGUAGE,
+    SCHEMA_VERSION,
+};
 
 /// Test the creation and field access of the Quote struct.
 #[test]
@@ -11,13 +22,14 @@ fn test_quote_struct() {
     let quote = Quote {
         quote_text: "Test quote".to_string(),
         author: "Test author".to_string(),
-        date_added: "2024-01-21".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
         image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(quote.quote_text, "Test quote");
     assert_eq!(quote.author, "Test author");
-    assert_eq!(quote.date_added, "2024-01-21");
+    assert_eq!(quote.date_added.unwrap().to_string(), "2024-01-21");
     assert_eq!(quote.image_url, "http://example.com/image.jpg");
 }
 
@@ -28,14 +40,16 @@ fn test_quotes_struct_initialization() {
         Quote {
             quote_text: "Quote 1".to_string(),
             author: "Author 1".to_string(),
-            date_added: "2024-01-21".to_string(),
+            date_added: Some("2024-01-21".parse().unwrap()),
             image_url: "http://example.com/image1.jpg".to_string(),
+            ..Default::default()
         },
         Quote {
             quote_text: "Quote 2".to_string(),
             author: "Author 2".to_string(),
-            date_added: "2024-01-22".to_string(),
+            date_added: Some("2024-01-22".parse().unwrap()),
             image_url: "http://example.com/image2.jpg".to_string(),
+            ..Default::default()
         },
     ];
 
@@ -50,13 +64,14 @@ fn test_random_quote_selection() {
         Quote {
             quote_text: "Test quote 1".to_string(),
             author: "Author 1".to_string(),
-            date_added: "2024-01-01".to_string(),
+            date_added: Some("2024-01-01".parse().unwrap()),
             image_url: "http://example.com/image1.jpg".to_string(),
+            ..Default::default()
         },
         // ... more quotes ...
     ];
 
-    let mut quotes = Quotes::new(quotes_vec);
+    let quotes = Quotes::new(quotes_vec);
 
     let selected_quote_text = match quotes.select_random_quote() {
         Ok(quote) => quote.quote_text.clone(),
@@ -76,15 +91,17 @@ fn test_all_quotes_selection() {
         Quote {
             quote_text: "Test quote 1".to_string(),
             author: "Author 1".to_string(),
-            date_added: "2024-01-01".to_string(),
+            date_added: Some("2024-01-01".parse().unwrap()),
             image_url: "http://example.com/image1.jpg".to_string(),
+            ..Default::default()
         },
         //... more quotes...
         Quote {
             quote_text: "Test quote 2".to_string(),
             author: "Author 2".to_string(),
-            date_added: "2024-01-02".to_string(),
+            date_added: Some("2024-01-02".parse().unwrap()),
             image_url: "http://example.com/image2.jpg".to_string(),
+            ..Default::default()
         },
     ];
     let quotes = Quotes::new(quotes_vec.clone());
@@ -109,8 +126,9 @@ fn test_quote_equality() {
     let quote1 = Quote {
         quote_text: "Same quote".to_string(),
         author: "Same author".to_string(),
-        date_added: "2024-01-21".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
         image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
     };
 
     let quote2 = quote1.clone();
@@ -121,8 +139,9 @@ fn test_quote_equality() {
         quote_text: "Different quote".to_string(),
         // other fields same as quote1
         author: "Same author".to_string(),
-        date_added: "2024-01-21".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
         image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
     };
 
     assert_ne!(quote1, quote3);
@@ -131,8 +150,11 @@ fn test_quote_equality() {
 /// Test selecting a random quote when the vector is empty.
 #[test]
 fn test_select_random_quote_empty_vector() {
-    let mut quotes = Quotes::new(Vec::new());
-    assert!(quotes.select_random_quote().is_err());
+    let quotes = Quotes::new(Vec::new());
+    assert!(matches!(
+        quotes.select_random_quote(),
+        Err(QuoteError::NoQuotesAvailable)
+    ));
 }
 
 /// Test selecting all quotes when the vector is empty.
@@ -144,7 +166,7 @@ fn test_select_all_quotes_empty_vector() {
         Ok(_) => panic!(
             "Expected an error for empty quotes vector, but got Ok"
         ),
-        Err(e) => assert_eq!(e.to_string(), "No available quotes"),
+        Err(e) => assert!(matches!(e, QuoteError::NoQuotesAvailable)),
     }
 }
 
@@ -154,8 +176,9 @@ fn test_quote_serialization_deserialization() {
     let quote = Quote {
         quote_text: "Test quote".to_string(),
         author: "Test author".to_string(),
-        date_added: "2024-01-21".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
         image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
     };
 
     let serialized = serde_json::to_string(&quote).unwrap();
@@ -171,10 +194,648 @@ fn test_invalid_quote_creation() {
     let quote = Quote {
         quote_text: "".to_string(),
         author: "".to_string(),
-        date_added: "2024-01-21".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
         image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
     };
 
     assert_eq!(quote.quote_text, "");
     assert_eq!(quote.author, "");
 }
+
+/// Test reading quotes from YAML files with both extensions.
+#[test]
+fn test_read_quotes_from_yaml_file() {
+    let yaml = "quotes:\n  - quote_text: \"Test quote\"\n    author: \"Test author\"\n    date_added: \"2024-01-21T06:06:06Z\"\n    image_url: \"http://example.com/image.jpg\"\n";
+
+    for name in ["quotes.yaml", "quotes.yml"] {
+        let path = write_temp_file(name, yaml);
+        let quotes = read_quotes_from_file(path.to_str().unwrap())
+            .expect("YAML dataset should load");
+        assert_eq!(quotes.quotes.len(), 1);
+        assert_eq!(quotes.quotes[0].author, "Test author");
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+/// Test reading quotes from a TOML file.
+#[test]
+fn test_read_quotes_from_toml_file() {
+    let toml = "[[quotes]]\nquote_text = \"Test quote\"\nauthor = \"Test author\"\ndate_added = \"2024-01-21T06:06:06Z\"\nimage_url = \"http://example.com/image.jpg\"\n\n[[quotes]]\nquote_text = \"Another quote\"\nauthor = \"Another author\"\ndate_added = \"2024-01-22T06:06:06Z\"\nimage_url = \"http://example.com/image.jpg\"\n";
+
+    let path = write_temp_file("quotes.toml", toml);
+    let quotes = read_quotes_from_file(path.to_str().unwrap())
+        .expect("TOML dataset should load");
+    assert_eq!(quotes.quotes.len(), 2);
+    assert_eq!(quotes.quotes[1].quote_text, "Another quote");
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Test that parse errors name the format that failed.
+#[test]
+fn test_parse_errors_name_the_format() {
+    let cases = [
+        ("broken.json", "{\"quotes\": [", "JSON"),
+        ("broken.yaml", "quotes: [\"unterminated", "YAML"),
+        ("broken.toml", "[[quotes]\nquote_text = ", "TOML"),
+    ];
+
+    for (name, contents, format) in cases {
+        let path = write_temp_file(name, contents);
+        let err = read_quotes_from_file(path.to_str().unwrap())
+            .expect_err("malformed dataset should fail");
+        assert!(
+            matches!(err, QuoteError::ParseError(ref msg) if msg.contains(format)),
+            "unexpected error for {}: {}",
+            name,
+            err
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+/// Test that the existing v1 datasets still load unchanged.
+#[test]
+fn test_v1_datasets_still_load() {
+    for file in ["./quotes/01-quotes.json", "./quotes/01-quotes.csv"] {
+        let quotes = read_quotes_from_file(file).unwrap();
+        assert!(!quotes.quotes.is_empty());
+
+        let first = &quotes.quotes[0];
+        assert_eq!(first.author, "The Wiser One");
+        assert!(first.id.is_none());
+        assert!(first.tags.is_empty());
+        assert!(first.source.is_none());
+        assert!(first.extra.is_empty());
+    }
+
+    // Every format reports version 1 for a dataset without a marker
+    for file in ["./quotes/01-quotes.json", "./quotes/01-quotes.csv"] {
+        let quotes = read_quotes_from_file(file).unwrap();
+        assert_eq!(quotes.schema_version, 1, "{}", file);
+    }
+    for (content, format) in [
+        ("quotes:\n  - quote_text: A\n    author: B\n    date_added: 2024-01-01\n    image_url: http://x\n", Format::Yaml),
+        ("{\"quote_text\":\"A\",\"author\":\"B\",\"date_added\":\"2024-01-01\",\"image_url\":\"http://x\"}\n", Format::JsonLines),
+    ] {
+        let quotes = Quotes::from_str(content, format).unwrap();
+        assert_eq!(quotes.schema_version, 1, "{}", format);
+    }
+    let merged =
+        read_quotes_from_dir("./quotes", ConflictPolicy::Error)
+            .unwrap();
+    assert_eq!(merged.quotes.schema_version, 1);
+}
+
+/// Test that a dataset without a marker gets the version of the fields
+/// it uses, and that merging keeps the highest version.
+#[test]
+fn test_unmarked_datasets_use_the_version_of_their_fields() {
+    let csv = "quote_text,author,date_added,image_url,tags\n\
+               A,B,2024-01-01,http://x,wisdom\n";
+    let jsonl = "{\"quote_text\":\"A\",\"author\":\"B\",\
+                 \"date_added\":\"2024-01-01\",\"image_url\":\"http://x\",\
+                 \"translations\":{\"fr\":{\"quote_text\":\"Un\"}}}\n";
+    let json = "{\"quotes\":[{\"quote_text\":\"A\",\"author\":\"B\",\
+                \"date_added\":\"2024-01-01\",\"image_url\":\"http://x\",\
+                \"id\":\"a\"}]}";
+    for (content, format) in [
+        (csv, Format::Csv),
+        (jsonl, Format::JsonLines),
+        (json, Format::Json),
+    ] {
+        let quotes = Quotes::from_str(content, format).unwrap();
+        assert_eq!(quotes.schema_version, SCHEMA_VERSION, "{}", format);
+
+        // Converting it keeps the version of its fields
+        let mut buffer = Vec::new();
+        quotes.write_to(&mut buffer, Format::Json).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(written["schema_version"], SCHEMA_VERSION);
+    }
+
+    // An explicit marker is kept
+    let marked = json.replacen('{', "{\"schema_version\":2,", 1);
+    let quotes = Quotes::from_str(&marked, Format::Json).unwrap();
+    assert_eq!(quotes.schema_version, 2);
+
+    // Merging a v1 file with a v2 file gives a v2 collection
+    let dir = std::env::temp_dir()
+        .join(format!("wiserone-{}-versions", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("1.jsonl"),
+        "{\"quote_text\":\"Z\",\"author\":\"B\",\
+         \"date_added\":\"2024-02-01\",\"image_url\":\"http://x\"}\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("2.csv"), csv).unwrap();
+    let merged = read_quotes_from_dir(
+        dir.to_str().unwrap(),
+        ConflictPolicy::Error,
+    )
+    .unwrap()
+    .quotes;
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(merged.quotes.len(), 2);
+    assert_eq!(merged.schema_version, SCHEMA_VERSION);
+}
+
+/// Test reading a v2 JSON dataset with every optional field.
+#[test]
+fn test_read_v2_json_dataset() {
+    let json = r#"{
+        "schema_version": 2,
+        "quotes": [{
+            "quote_text": "Test quote",
+            "author": "Test author",
+            "date_added": "2024-01-21T06:06:06Z",
+            "image_url": "http://example.com/image.jpg",
+            "id": "test-quote",
+            "tags": ["simplicity", "focus"],
+            "source": {"kind": "book", "title": "A Book"},
+            "language": "en",
+            "image_alt": "A calm lake",
+            "notes": "Needs a better image",
+            "mood": "calm"
+        }]
+    }"#;
+    let path = write_temp_file("v2.json", json);
+    let quotes = read_quotes_from_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(quotes.schema_version, SCHEMA_VERSION);
+    let quote = &quotes.quotes[0];
+    assert_eq!(quote.id.as_deref(), Some("test-quote"));
+    assert_eq!(quote.tags, vec!["simplicity", "focus"]);
+    assert_eq!(
+        quote.source,
+        Some(Source {
+            kind: Some(SourceKind::Book),
+            title: Some("A Book".to_string()),
+            url: None,
+        })
+    );
+    assert_eq!(quote.language.as_deref(), Some("en"));
+    assert_eq!(quote.image_alt.as_deref(), Some("A calm lake"));
+    assert_eq!(quote.notes.as_deref(), Some("Needs a better image"));
+    assert_eq!(quote.extra["mood"], "calm");
+}
+
+/// Test reading the flattened v2 columns from a CSV dataset.
+#[test]
+fn test_read_v2_csv_dataset() {
+    let csv = "quote_text,author,date_added,image_url,tags,source_kind,source_url,mood\n\
+               Test quote,Test author,2024-01-21T06:06:06Z,http://example.com/image.jpg,simplicity; focus,web,https://example.com,calm\n\
+               Other quote,Other author,2024-01-22T06:06:06Z,http://example.com/image.jpg,,,,\n";
+    let path = write_temp_file("v2.csv", csv);
+    let quotes = read_quotes_from_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let first = &quotes.quotes[0];
+    assert_eq!(first.tags, vec!["simplicity", "focus"]);
+    let source = first.source.as_ref().unwrap();
+    assert_eq!(source.kind, Some(SourceKind::Web));
+    assert_eq!(source.url.as_deref(), Some("https://example.com"));
+    assert_eq!(first.extra["mood"], "calm");
+
+    let second = &quotes.quotes[1];
+    assert!(second.tags.is_empty());
+    assert!(second.source.is_none());
+    assert!(second.extra.is_empty());
+}
+
+/// Test that datasets from a newer schema are rejected.
+#[test]
+fn test_newer_schema_version_is_rejected() {
+    let json = r#"{"schema_version": 99, "quotes": []}"#;
+    let path = write_temp_file("v99.json", json);
+    let err =
+        read_quotes_from_file(path.to_str().unwrap()).unwrap_err();
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(err, QuoteError::UnsupportedSchemaVersion(99)));
+}
+
+/// Test that derived ids are stable and explicit ids take precedence.
+#[test]
+fn test_quote_stable_id() {
+    let quote = Quote {
+        quote_text: "Test quote".to_string(),
+        author: "Test author".to_string(),
+        date_added: Some("2024-01-21".parse().unwrap()),
+        image_url: "http://example.com/image.jpg".to_string(),
+        ..Default::default()
+    };
+
+    // Derived ids only depend on the text and the author.
+    let moved = Quote {
+        date_added: Some("2024-02-01".parse().unwrap()),
+        image_url: "http://example.com/other.jpg".to_string(),
+        ..quote.clone()
+    };
+    assert!(quote.stable_id().starts_with("q-"));
+    assert_eq!(quote.stable_id(), moved.stable_id());
+    assert_eq!(quote.stable_id(), "q-b08ce87d84cd9f51");
+
+    let reworded = Quote {
+        quote_text: "Another quote".to_string(),
+        ..quote.clone()
+    };
+    assert_ne!(quote.stable_id(), reworded.stable_id());
+
+    let explicit =
+        Quote { id: Some("my-quote".to_string()), ..quote.clone() };
+    assert_eq!(explicit.stable_id(), "my-quote");
+}
+
+/// Test looking up quotes by explicit and derived ids.
+#[test]
+fn test_quotes_get_by_id() {
+    let derived = Quote {
+        quote_text: "Derived".to_string(),
+        author: "Author".to_string(),
+        ..Default::default()
+    };
+    let explicit = Quote {
+        quote_text: "Explicit".to_string(),
+        author: "Author".to_string(),
+        id: Some("explicit".to_string()),
+        ..Default::default()
+    };
+    let quotes = Quotes::new(vec![derived.clone(), explicit.clone()]);
+
+    assert_eq!(quotes.get(&derived.stable_id()), Some(&derived));
+    assert_eq!(quotes.get("explicit"), Some(&explicit));
+    assert_eq!(quotes.get("missing"), None);
+}
+
+/// Test that quotes are sorted chronologically across time zones.
+#[test]
+fn test_all_quotes_sorted_by_instant() {
+    let quote = |text: &str, date: &str| Quote {
+        quote_text: text.to_string(),
+        date_added: Some(date.parse().unwrap()),
+        ..Default::default()
+    };
+    // Lexically "2024-01-01T23:00:00-05:00" sorts first, but it is
+    // 04:00 UTC on 2 January.
+    let quotes = Quotes::new(vec![
+        quote("later", "2024-01-01T23:00:00-05:00"),
+        quote("earlier", "2024-01-02T01:00:00Z"),
+    ]);
+    let sorted = quotes.select_all_quotes().unwrap();
+    assert_eq!(sorted[0].quote_text, "earlier");
+    assert_eq!(sorted[1].quote_text, "later");
+
+    // The page slug keeps the calendar day written in the dataset.
+    assert_eq!(sorted[1].date_added.unwrap().slug(), "2024_01_01");
+}
+
+/// Test that invalid dates are rejected with a dedicated error.
+#[test]
+fn test_invalid_date_is_rejected() {
+    assert!(matches!(
+        "2024-02-30".parse::<QuoteDate>(),
+        Err(QuoteError::InvalidDate(ref value)) if value == "2024-02-30"
+    ));
+
+    let csv = "quote_text,author,date_added,image_url\n\
+               Test quote,Test author,not-a-date,http://example.com/image.jpg\n";
+    let path = write_temp_file("bad-date.csv", csv);
+    let err =
+        read_quotes_from_file(path.to_str().unwrap()).unwrap_err();
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(err, QuoteError::InvalidDate(_)));
+}
+
+/// Test that dates are written back in the form they were read.
+#[test]
+fn test_quote_date_round_trip() {
+    for value in [
+        "2024-01-21",
+        "2024-01-21T06:06:06Z",
+        "2024-01-21T06:06:06+05:30",
+    ] {
+        let date: QuoteDate = value.parse().unwrap();
+        assert_eq!(date.to_string(), value);
+        assert_eq!(date.slug(), "2024_01_21");
+    }
+}
+
+/// Test merging the bundled directory, where the CSV files mirror the
+/// JSON files record for record.
+#[test]
+fn test_read_quotes_from_dir_merges_mirrors() {
+    let merged =
+        read_quotes_from_dir("./quotes", ConflictPolicy::Error)
+            .unwrap();
+    assert!(merged.collisions.is_empty());
+    assert_eq!(merged.quotes.quotes.len(), 60);
+
+    let json_only = read_quotes_from_glob(
+        "./quotes/0?-quotes.json",
+        ConflictPolicy::Error,
+    )
+    .unwrap();
+    assert_eq!(json_only.quotes, merged.quotes);
+}
+
+/// Test each conflict policy on two files that disagree about a day.
+#[test]
+fn test_merge_conflict_policies() {
+    let dir = std::env::temp_dir()
+        .join(format!("wiserone-{}-merge", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("a.json"),
+        r#"{"quotes": [
+            {"quote_text": "First", "author": "A", "date_added": "2024-01-01", "image_url": "https://example.com/a.jpg"},
+            {"quote_text": "Only in a", "author": "A", "date_added": "2024-01-02", "image_url": "https://example.com/a.jpg"}
+        ]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("b.yaml"),
+        "quotes:\n  - quote_text: Second\n    author: B\n    date_added: \"2024-01-01T12:00:00Z\"\n    image_url: https://example.com/b.jpg\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("README.md"), "Not a dataset").unwrap();
+    let dir_path = dir.to_str().unwrap();
+
+    let err = read_quotes_from_dir(dir_path, ConflictPolicy::Error)
+        .unwrap_err();
+    match err {
+        QuoteError::Conflict(collision) => {
+            assert_eq!(collision.key, "date 2024-01-01");
+            assert!(collision.first.ends_with("a.json"));
+            assert!(collision.second.ends_with("b.yaml"));
+        }
+        other => panic!("expected a conflict, got {}", other),
+    }
+
+    let first =
+        read_quotes_from_dir(dir_path, ConflictPolicy::FirstWins)
+            .unwrap();
+    assert_eq!(first.collisions.len(), 1);
+    let texts: Vec<_> = first
+        .quotes
+        .quotes
+        .iter()
+        .map(|q| q.quote_text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["First", "Only in a"]);
+
+    let last = read_quotes_from_dir(dir_path, ConflictPolicy::LastWins)
+        .unwrap();
+    assert_eq!(last.collisions.len(), 1);
+    let texts: Vec<_> = last
+        .quotes
+        .quotes
+        .iter()
+        .map(|q| q.quote_text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["Only in a", "Second"]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Test that the quote of the day is the scheduled one when there is
+/// one, and a stable rotation otherwise.
+#[test]
+fn test_quote_for_date() {
+    let quotes =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+
+    let scheduled: QuoteDate = "2024-01-05".parse().unwrap();
+    let quote = quotes.quote_for_date(&scheduled).unwrap();
+    assert_eq!(quote.day().unwrap(), "2024-01-05");
+
+    // Days without a scheduled quote rotate deterministically, whatever
+    // the order of the records.
+    let unscheduled: QuoteDate = "2025-06-15".parse().unwrap();
+    let first = quotes.quote_for_date(&unscheduled).unwrap().clone();
+    let mut reversed = quotes.clone();
+    reversed.quotes.reverse();
+    assert_eq!(reversed.quote_for_date(&unscheduled).unwrap(), &first);
+    assert_eq!(quotes.quote_for_date(&unscheduled).unwrap(), &first);
+
+    // Over a month the rotation reaches more than one quote.
+    let picks: HashSet<_> = (1..=28)
+        .map(|day| {
+            let date: QuoteDate =
+                format!("2025-06-{:02}", day).parse().unwrap();
+            quotes.quote_for_date(&date).unwrap().stable_id()
+        })
+        .collect();
+    assert!(picks.len() > 1);
+
+    let empty = Quotes::new(Vec::new());
+    assert!(matches!(
+        empty.quote_for_date(&unscheduled),
+        Err(QuoteError::NoQuotesAvailable)
+    ));
+}
+
+/// Test that seeded selection, sampling and shuffling are reproducible.
+#[test]
+fn test_seeded_selection_is_reproducible() {
+    let quotes = numbered_quotes(10);
+
+    let first = quotes.select_random_quote_seeded(42).unwrap();
+    for _ in 0..5 {
+        assert_eq!(
+            quotes.select_random_quote_seeded(42).unwrap(),
+            first
+        );
+    }
+
+    let sample = |seed| {
+        quotes
+            .sample(4, &mut Random::from_u64_seed(seed))
+            .into_iter()
+            .map(|quote| quote.quote_text.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(sample(7), sample(7));
+
+    let shuffled = |seed| {
+        quotes
+            .shuffled(&mut Random::from_u64_seed(seed))
+            .map(|quote| quote.quote_text.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(shuffled(7), shuffled(7));
+}
+
+/// Test that sampling draws distinct quotes and caps at the collection.
+#[test]
+fn test_sample_without_replacement() {
+    let quotes = numbered_quotes(5);
+    let mut rng = Random::from_u64_seed(1);
+
+    let sample = quotes.sample(3, &mut rng);
+    assert_eq!(sample.len(), 3);
+    let distinct: HashSet<_> =
+        sample.iter().map(|quote| &quote.quote_text).collect();
+    assert_eq!(distinct.len(), 3);
+
+    assert_eq!(quotes.sample(50, &mut rng).len(), 5);
+    assert_eq!(quotes.shuffled(&mut rng).count(), 5);
+    assert!(Quotes::default().sample(3, &mut rng).is_empty());
+    assert!(matches!(
+        Quotes::default().select_random_quote_seeded(1),
+        Err(QuoteError::NoQuotesAvailable)
+    ));
+}
+
+/// Test parsing quotes held in memory and read from any reader.
+#[test]
+fn test_read_quotes_from_reader_and_str() {
+    let json = r#"{"quotes": [{"quote_text": "Know thyself.",
+        "author": "Socrates", "date_added": "2024-01-01",
+        "image_url": "https://example.com/image.jpg"}]}"#;
+    let from_str = Quotes::from_str(json, Format::Json).unwrap();
+    assert_eq!(from_str.quotes[0].author, "Socrates");
+
+    let from_reader = read_quotes_from_reader(
+        std::io::Cursor::new(json),
+        Format::Json,
+    )
+    .unwrap();
+    assert_eq!(from_reader, from_str);
+
+    let csv = std::fs::read("./quotes/01-quotes.csv").unwrap();
+    let from_csv =
+        read_quotes_from_reader(csv.as_slice(), Format::Csv).unwrap();
+    assert_eq!(
+        from_csv.quotes,
+        read_quotes_from_file("./quotes/01-quotes.csv").unwrap().quotes
+    );
+
+    assert!(matches!(
+        Quotes::from_str(json, Format::Toml),
+        Err(QuoteError::ParseError(_))
+    ));
+    assert!(matches!(
+        Quotes::from_str(
+            r#"{"schema_version": 99, "quotes": []}"#,
+            Format::Json
+        ),
+        Err(QuoteError::UnsupportedSchemaVersion(99))
+    ));
+}
+
+/// Test naming and inferring dataset formats.
+#[test]
+fn test_format_from_str_and_path() {
+    assert_eq!("yml".parse(), Ok(Format::Yaml));
+    assert_eq!("JSON".parse(), Ok(Format::Json));
+    assert!("xml".parse::<Format>().is_err());
+    assert_eq!(
+        Format::from_path("quotes/a.toml").unwrap(),
+        Format::Toml
+    );
+    assert!(Format::from_path("quotes/a").is_err());
+    for format in
+        [Format::Json, Format::Csv, Format::Yaml, Format::Toml]
+    {
+        assert_eq!(format.to_string().parse(), Ok(format));
+    }
+}
+
+/// Test reading a quote in its primary language and in a translation.
+#[test]
+fn test_localized_quote() {
+    let quote = Quote {
+        quote_text: "Know thyself.".to_string(),
+        author: "Socrates".to_string(),
+        image_alt: Some("A bust".to_string()),
+        translations: [(
+            "fr".to_string(),
+            Translation {
+                quote_text: "Connais-toi toi-même.".to_string(),
+                author: Some("Socrate".to_string()),
+                image_alt: None,
+            },
+        )]
+        .into(),
+        ..Default::default()
+    };
+    assert_eq!(quote.primary_language(), DEFAULT_LANGUAGE);
+    assert_eq!(quote.localized(DEFAULT_LANGUAGE), Some(quote.clone()));
+    assert_eq!(quote.localized("de"), None);
+
+    let french = quote.localized("fr").unwrap();
+    assert_eq!(french.quote_text, "Connais-toi toi-même.");
+    assert_eq!(french.author, "Socrate");
+    assert_eq!(french.image_alt.as_deref(), Some("A bust"));
+    assert_eq!(french.primary_language(), "fr");
+    assert_eq!(french.date_added, quote.date_added);
+}
+
+/// Test reading translations from `<field>.<language>` CSV columns.
+#[test]
+fn test_translations_from_csv() {
+    let csv = "quote_text,author,date_added,image_url,quote_text.fr,author.fr\n\
+               Carpe diem.,Horace,2024-01-01,https://example.com/a.jpg,Cueille le jour.,\n";
+    let quotes = Quotes::from_str(csv, Format::Csv).unwrap();
+    let translation = &quotes.quotes[0].translations["fr"];
+    assert_eq!(translation.quote_text, "Cueille le jour.");
+    assert_eq!(translation.author, None);
+
+    let missing_text = "quote_text,author,date_added,image_url,author.fr\n\
+                        Carpe diem.,Horace,2024-01-01,https://example.com/a.jpg,Horace\n";
+    assert!(matches!(
+        Quotes::from_str(missing_text, Format::Csv),
+        Err(QuoteError::ParseError(_))
+    ));
+}
+
+/// Test recognising language tags.
+#[test]
+fn test_is_language_tag() {
+    for tag in ["fr", "en-GB", "zh-Hant-TW", "es-419"] {
+        assert!(is_language_tag(tag), "{}", tag);
+    }
+    for tag in ["", "..", "fr/", "en_GB", "en-", "toolongtag"] {
+        assert!(!is_language_tag(tag), "{}", tag);
+    }
+}
+
+/// Test that ids which could escape the output directory are refused
+/// by every reader.
+#[test]
+fn test_ids_must_be_slugs() {
+    for id in ["socrates-1", "Q_2", "2024"] {
+        assert!(is_slug(id), "{}", id);
+    }
+    for id in ["", "..", "../escaped", "a/b", "a\\b", "a b", "café"] {
+        assert!(!is_slug(id), "{}", id);
+    }
+
+    let record = r#"{"quote_text":"A","author":"B","date_added":"2024-01-01","image_url":"http://x","id":"../escaped"}"#;
+    match Quotes::from_str(
+        &format!("{{\"quotes\":[{}]}}", record),
+        Format::Json,
+    ) {
+        Err(QuoteError::SchemaViolation(errors)) => {
+            assert_eq!(errors[0].pointer, "/quotes/0/id")
+        }
+        other => panic!("expected a schema error, got {:?}", other),
+    }
+    let csv = "quote_text,author,date_added,image_url,id\n\
+               A,B,2024-01-01,http://x,../escaped\n";
+    for (content, format) in
+        [(record, Format::JsonLines), (csv, Format::Csv)]
+    {
+        match Quotes::from_str(content, format) {
+            Err(QuoteError::InvalidId(id)) => {
+                assert_eq!(id, "../escaped")
+            }
+            other => panic!("expected an invalid id, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use common::numbered_quotes;
+use std::collections::HashSet;
+use vrd::Random;
+use wiserone::dates::QuoteDate;
+use wiserone::quotes::{QuoteError, Quotes};
+use wiserone::rotation::{History, RotationPolicy};
+
+/// Returns the day `offset` days after 2024-03-01.
+fn day(offset: usize) -> QuoteDate {
+    let datetime =
+        "2024-03-01".parse::<QuoteDate>().unwrap().datetime();
+    QuoteDate::from(datetime.add_days(offset as i64).unwrap())
+}
+
+/// Test that the shuffle bag shows every quote once before repeating,
+/// even when the history is saved and reloaded between runs.
+#[test]
+fn test_shuffle_bag_walks_whole_collection() {
+    let quotes = numbered_quotes(6);
+    let path = std::env::temp_dir().join("wiserone_shuffle_bag.json");
+    let _ = std::fs::remove_file(&path);
+    let mut rng = Random::from_u64_seed(3);
+
+    let mut shown = Vec::new();
+    for offset in 0..12 {
+        let mut history = History::load(&path).unwrap();
+        let quote = quotes
+            .next_in_rotation(
+                &mut history,
+                RotationPolicy::ShuffleBag,
+                day(offset),
+                &mut rng,
+            )
+            .unwrap();
+        shown.push(quote.stable_id());
+        history.save(&path).unwrap();
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    for round in shown.chunks(6) {
+        assert_eq!(round.iter().collect::<HashSet<_>>().len(), 6);
+    }
+    assert_eq!(History::load(&path).unwrap(), History::default());
+}
+
+/// Test that least-recently-shown never repeats until every quote has
+/// been shown, then repeats the oldest one.
+#[test]
+fn test_least_recently_shown() {
+    let quotes = numbered_quotes(4);
+    let mut history = History::default();
+    let mut rng = Random::from_u64_seed(5);
+
+    let mut next = |offset| {
+        quotes
+            .next_in_rotation(
+                &mut history,
+                RotationPolicy::LeastRecentlyShown,
+                day(offset),
+                &mut rng,
+            )
+            .unwrap()
+            .stable_id()
+    };
+    let first: Vec<String> = (0..4).map(&mut next).collect();
+    assert_eq!(first.iter().collect::<HashSet<_>>().len(), 4);
+    assert_eq!(next(4), first[0]);
+    assert_eq!(next(5), first[1]);
+}
+
+/// Test that no quote repeats within the window, and that the policy
+/// still returns a quote once the window covers the whole collection.
+#[test]
+fn test_no_repeat_within_window() {
+    let quotes = numbered_quotes(5);
+    let mut history = History::default();
+    let mut rng = Random::from_u64_seed(9);
+
+    let shown: Vec<String> = (0..10)
+        .map(|offset| {
+            quotes
+                .next_in_rotation(
+                    &mut history,
+                    RotationPolicy::NoRepeatWithin(3),
+                    day(offset),
+                    &mut rng,
+                )
+                .unwrap()
+                .stable_id()
+        })
+        .collect();
+    for window in shown.windows(3) {
+        assert_eq!(window.iter().collect::<HashSet<_>>().len(), 3);
+    }
+
+    let mut history = History::default();
+    for offset in 0..3 {
+        assert!(quotes
+            .next_in_rotation(
+                &mut history,
+                RotationPolicy::NoRepeatWithin(30),
+                day(offset),
+                &mut rng,
+            )
+            .is_ok());
+    }
+    assert_eq!(history.published.len(), 3);
+}
+
+/// Test that rotating an empty collection fails.
+#[test]
+fn test_rotation_empty_collection() {
+    let quotes = Quotes::default();
+    let result = quotes.next_in_rotation(
+        &mut History::default(),
+        RotationPolicy::ShuffleBag,
+        day(0),
+        &mut Random::from_u64_seed(1),
+    );
+    assert!(matches!(result, Err(QuoteError::NoQuotesAvailable)));
+}
+
+/// Test parsing and printing rotation policies.
+#[test]
+fn test_rotation_policy_from_str() {
+    for policy in [
+        RotationPolicy::ShuffleBag,
+        RotationPolicy::LeastRecentlyShown,
+        RotationPolicy::NoRepeatWithin(30),
+    ] {
+        assert_eq!(policy.to_string().parse(), Ok(policy));
+    }
+    assert!("no-repeat:soon".parse::<RotationPolicy>().is_err());
+    assert!("sequential".parse::<RotationPolicy>().is_err());
+}
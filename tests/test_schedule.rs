@@ -0,0 +1,294 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use common::quote;
+use wiserone::dates::QuoteDate;
+use wiserone::quotes::{
+    read_quotes_from_file, Format, Quote, QuoteError, Quotes,
+};
+use wiserone::schedule::{unscheduled_from_str, SchedulePolicy};
+
+/// Returns the day and text of each quote.
+fn days(quotes: &[Quote]) -> Vec<(String, &str)> {
+    quotes
+        .iter()
+        .map(|quote| (quote.day().unwrap(), quote.quote_text.as_str()))
+        .collect()
+}
+
+/// Parses a date.
+fn date(value: &str) -> QuoteDate {
+    value.parse().unwrap()
+}
+
+/// Test that quotes fill the free days in order, around the days
+/// already taken.
+#[test]
+fn test_schedule_sequential() {
+    let mut quotes = Quotes::new(vec![quote(
+        "Taken",
+        "A",
+        &[],
+        "2024-03-02T06:06:06Z",
+    )]);
+    let unscheduled = vec![
+        quote("First", "A", &[], "2024-01-01"),
+        quote("Second", "A", &[], "2024-01-01"),
+    ];
+    let scheduled = quotes
+        .schedule(
+            unscheduled,
+            date("2024-03-01"),
+            date("2024-03-05"),
+            SchedulePolicy::Sequential,
+        )
+        .unwrap();
+    assert_eq!(
+        days(scheduled),
+        vec![
+            ("2024-03-01".to_string(), "First"),
+            ("2024-03-03".to_string(), "Second"),
+        ]
+    );
+    assert_eq!(
+        quotes.quotes[0].date_added.unwrap().to_string(),
+        "2024-03-02T06:06:06Z"
+    );
+}
+
+/// Test that authors take turns.
+#[test]
+fn test_schedule_round_robin_by_author() {
+    let mut quotes = Quotes::default();
+    let unscheduled = vec![
+        quote("A1", "Ann", &[], "2024-01-01"),
+        quote("A2", "Ann", &[], "2024-01-01"),
+        quote("A3", "Ann", &[], "2024-01-01"),
+        quote("B1", "Bob", &[], "2024-01-01"),
+        quote("C1", "Cid", &[], "2024-01-01"),
+    ];
+    let scheduled = quotes
+        .schedule(
+            unscheduled,
+            date("2024-03-01"),
+            date("2024-03-31"),
+            SchedulePolicy::RoundRobinByAuthor,
+        )
+        .unwrap();
+    let texts: Vec<&str> = scheduled
+        .iter()
+        .map(|quote| quote.quote_text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["A1", "B1", "C1", "A2", "A3"]);
+}
+
+/// Test that quotes sharing a tag are kept apart, including from the
+/// quotes already scheduled.
+#[test]
+fn test_schedule_tag_spacing() {
+    let mut quotes = Quotes::new(vec![quote(
+        "Old calm",
+        "A",
+        &["calm"],
+        "2024-02-29",
+    )]);
+    let unscheduled = vec![
+        quote("Calm 1", "A", &["Calm"], "2024-01-01"),
+        quote("Calm 2", "A", &["calm"], "2024-01-01"),
+        quote("Grit 1", "A", &["grit"], "2024-01-01"),
+        quote("Grit 2", "A", &["grit"], "2024-01-01"),
+    ];
+    let scheduled = quotes
+        .schedule(
+            unscheduled,
+            date("2024-03-01"),
+            date("2024-03-04"),
+            SchedulePolicy::TagSpacing,
+        )
+        .unwrap();
+    let texts: Vec<&str> = scheduled
+        .iter()
+        .map(|quote| quote.quote_text.as_str())
+        .collect();
+    assert_eq!(texts, vec!["Grit 1", "Calm 1", "Grit 2", "Calm 2"]);
+}
+
+/// Test that a range too short for the quotes changes nothing.
+#[test]
+fn test_schedule_not_enough_days() {
+    let mut quotes =
+        Quotes::new(vec![quote("Taken", "A", &[], "2024-03-01")]);
+    let unscheduled = vec![
+        quote("First", "A", &[], "2024-01-01"),
+        quote("Second", "A", &[], "2024-01-01"),
+    ];
+    let result = quotes.schedule(
+        unscheduled,
+        date("2024-03-01"),
+        date("2024-03-02"),
+        SchedulePolicy::Sequential,
+    );
+    assert!(matches!(result, Err(QuoteError::NotEnoughDays(2, 1))));
+    assert_eq!(quotes.quotes.len(), 1);
+}
+
+/// Test finding free days and taking out colliding quotes.
+#[test]
+fn test_free_days_and_collisions() {
+    let mut quotes = Quotes::new(vec![
+        quote("One", "A", &[], "2024-03-01T06:06:06Z"),
+        quote("Two", "A", &[], "2024-03-03"),
+        quote("Again", "A", &[], "2024-03-01T20:00:00Z"),
+    ]);
+    let free = quotes
+        .free_days(date("2024-03-01"), date("2024-03-04"))
+        .unwrap();
+    let free: Vec<String> =
+        free.iter().map(QuoteDate::iso_date).collect();
+    assert_eq!(free, vec!["2024-03-02", "2024-03-04"]);
+
+    let collisions = quotes.take_collisions();
+    assert_eq!(
+        days(&collisions),
+        vec![("2024-03-01".to_string(), "Again")]
+    );
+    assert_eq!(quotes.quotes.len(), 2);
+}
+
+/// Test reading quotes that have no date yet.
+#[test]
+fn test_unscheduled_from_str() {
+    let json = r#"{"quotes": [
+        {"quote_text": "No date", "author": "A", "image_url": ""},
+        {"quote_text": "Empty", "author": "A", "date_added": "", "image_url": ""}
+    ]}"#;
+    let quotes = unscheduled_from_str(json, Format::Json).unwrap();
+    assert_eq!(quotes.len(), 2);
+    assert!(quotes.iter().all(|quote| quote.date_added.is_none()));
+
+    let csv =
+        "quote_text,author,image_url\nNo date,A,https://e.com/a.jpg\n";
+    let quotes = unscheduled_from_str(csv, Format::Csv).unwrap();
+    assert_eq!(quotes[0].quote_text, "No date");
+
+    let yaml = "quotes:\n  - quote_text: Dated\n    author: A\n    date_added: 2024-01-01\n    image_url: ''\n";
+    let quotes = unscheduled_from_str(yaml, Format::Yaml).unwrap();
+    assert_eq!(quotes.len(), 1);
+    assert_eq!(quotes[0].date_added, None);
+    assert!(unscheduled_from_str("{}", Format::Json).is_err());
+}
+
+/// Test that quotes without a date in the dataset itself are taken
+/// out and given free days.
+#[test]
+fn test_schedule_unscheduled_quotes() {
+    let json = r#"{"quotes": [
+        {"quote_text": "Waiting", "author": "A", "image_url": ""},
+        {"quote_text": "Taken", "author": "A", "date_added": "2024-03-01", "image_url": ""}
+    ]}"#;
+    let mut quotes = Quotes::from_str(json, Format::Json).unwrap();
+    let unscheduled = quotes.take_unscheduled();
+    assert_eq!(unscheduled[0].quote_text, "Waiting");
+    assert_eq!(
+        days(&quotes.quotes),
+        vec![("2024-03-01".to_string(), "Taken")]
+    );
+    assert!(quotes.take_collisions().is_empty());
+
+    let scheduled = quotes
+        .schedule(
+            unscheduled,
+            date("2024-03-01"),
+            date("2024-03-02"),
+            SchedulePolicy::Sequential,
+        )
+        .unwrap();
+    assert_eq!(
+        days(scheduled),
+        vec![("2024-03-02".to_string(), "Waiting")]
+    );
+}
+
+/// Test that the `schedule` command empties its queue, so running it
+/// again does not add the same quotes twice.
+#[test]
+fn test_schedule_command_twice() {
+    let directory =
+        std::env::temp_dir().join("wiserone-test-schedule-command");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(
+        directory.join("quotes.json"),
+        r#"{"quotes": [
+            {"quote_text": "Taken", "author": "A", "date_added": "2025-01-01", "image_url": ""},
+            {"quote_text": "Waiting", "author": "B", "image_url": ""}
+        ]}"#,
+    )
+    .unwrap();
+    fs::write(
+        directory.join("queue.csv"),
+        "quote_text,author,image_url\nQueued,C,\n",
+    )
+    .unwrap();
+
+    let run = || {
+        let status = Command::new(env!("CARGO_BIN_EXE_wiserone"))
+            .args([
+                "schedule",
+                "quotes.json",
+                "--queue",
+                "queue.csv",
+                "--from",
+                "2025-01-01",
+                "--to",
+                "2025-01-10",
+            ])
+            .current_dir(&directory)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+        read_quotes_from_file(
+            directory.join("quotes.json").to_str().unwrap(),
+        )
+        .unwrap()
+    };
+
+    let first = run();
+    assert_eq!(
+        days(&first.quotes),
+        vec![
+            ("2025-01-01".to_string(), "Taken"),
+            ("2025-01-02".to_string(), "Waiting"),
+            ("2025-01-03".to_string(), "Queued"),
+        ]
+    );
+    let queue = read_quotes_from_file(
+        directory.join("queue.csv").to_str().unwrap(),
+    )
+    .unwrap();
+    assert!(queue.quotes.is_empty());
+
+    assert_eq!(run(), first);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+/// Test naming schedule policies.
+#[test]
+fn test_schedule_policy_from_str() {
+    for policy in [
+        SchedulePolicy::Sequential,
+        SchedulePolicy::RoundRobinByAuthor,
+        SchedulePolicy::TagSpacing,
+    ] {
+        assert_eq!(policy.to_string().parse(), Ok(policy));
+    }
+    assert!("random".parse::<SchedulePolicy>().is_err());
+}
@@ -0,0 +1,298 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+use wiserone::quotes::{
+    read_quotes_from_file, Format, Quote, QuoteError, Quotes, Source,
+    SourceKind, Translation,
+};
+use wiserone::schema::{check_dataset, quotes_schema};
+use wiserone::validate::validate_str;
+
+/// Returns the pointers of the places where `dataset` does not match
+/// the schema.
+fn pointers(dataset: &Value) -> Vec<String> {
+    check_dataset(dataset)
+        .into_iter()
+        .map(|error| error.pointer)
+        .collect()
+}
+
+/// Test that the shipped schema is the one the crate generates.
+#[test]
+fn test_shipped_schema_is_current() {
+    let shipped =
+        std::fs::read_to_string("./schemas/quotes.schema.json")
+            .unwrap();
+    let shipped: Value = serde_json::from_str(&shipped).unwrap();
+    assert_eq!(shipped, quotes_schema());
+}
+
+/// Builds a quote that sets every field.
+fn full_quote() -> Quote {
+    Quote {
+        quote_text: "Carpe diem.".to_string(),
+        author: "Horace".to_string(),
+        date_added: Some("2024-01-01".parse().unwrap()),
+        image_url: "https://example.com/a.jpg".to_string(),
+        id: Some("carpe".to_string()),
+        tags: vec!["time".to_string()],
+        source: Some(Source {
+            kind: Some(SourceKind::Book),
+            title: Some("Odes".to_string()),
+            url: Some("https://example.com".to_string()),
+        }),
+        language: Some("la".to_string()),
+        image_alt: Some("A sundial".to_string()),
+        notes: Some("Book 1".to_string()),
+        weight: Some(2.0),
+        translations: BTreeMap::from([(
+            "en".to_string(),
+            Translation {
+                quote_text: "Seize the day.".to_string(),
+                author: Some("Horace".to_string()),
+                image_alt: Some("A sundial".to_string()),
+            },
+        )]),
+        extra: BTreeMap::new(),
+    }
+}
+
+/// Returns the keys of an object.
+fn keys(value: &Value) -> BTreeSet<String> {
+    value.as_object().unwrap().keys().cloned().collect()
+}
+
+/// Asserts that each field of `record` is required by `schema` exactly
+/// when a `T` cannot be read without it.
+fn assert_required<T: serde::de::DeserializeOwned>(
+    schema: &Value,
+    record: &Value,
+) {
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    for field in keys(record) {
+        let mut without = record.clone();
+        let _ = without.as_object_mut().unwrap().remove(&field);
+        assert_eq!(
+            serde_json::from_value::<T>(without).is_ok(),
+            !required.contains(&field.as_str()),
+            "{}",
+            field
+        );
+    }
+}
+
+/// Test that the schema covers every field of a quote.
+#[test]
+fn test_schema_covers_every_field() {
+    let quote = full_quote();
+    let schema = quotes_schema();
+    let written = serde_json::to_value(&quote).unwrap();
+    for field in written.as_object().unwrap().keys() {
+        assert!(
+            schema["$defs"]["quote"]["properties"].get(field).is_some(),
+            "`{}` is missing from the schema",
+            field
+        );
+    }
+    let translation = &written["translations"]["en"];
+    for field in translation.as_object().unwrap().keys() {
+        assert!(schema["$defs"]["translation"]["properties"]
+            .get(field)
+            .is_some());
+    }
+
+    let kinds = &schema["$defs"]["quote"]["properties"]["source"]
+        ["properties"]["kind"]["enum"];
+    for kind in [
+        SourceKind::Book,
+        SourceKind::Speech,
+        SourceKind::Article,
+        SourceKind::Interview,
+        SourceKind::Web,
+        SourceKind::Other,
+    ] {
+        let kind = serde_json::to_value(kind).unwrap();
+        assert!(kinds.as_array().unwrap().contains(&kind));
+    }
+
+    let dataset =
+        serde_json::to_value(Quotes::new(vec![quote])).unwrap();
+    assert!(check_dataset(&dataset).is_empty());
+}
+
+/// Test that the schema and the quote types describe the same fields,
+/// and require the same ones, so neither drifts from the other.
+#[test]
+fn test_schema_matches_the_types() {
+    let schema = quotes_schema();
+    let quote = &schema["$defs"]["quote"];
+    let translation = &schema["$defs"]["translation"];
+    let written = serde_json::to_value(full_quote()).unwrap();
+
+    assert_eq!(keys(&written), keys(&quote["properties"]));
+    assert_eq!(
+        keys(&written["translations"]["en"]),
+        keys(&translation["properties"])
+    );
+    assert_eq!(
+        keys(&written["source"]),
+        keys(&quote["properties"]["source"]["properties"])
+    );
+    let mut dataset =
+        keys(&serde_json::to_value(Quotes::default()).unwrap());
+    let _ = dataset.insert("$schema".to_string());
+    assert_eq!(dataset, keys(&schema["properties"]));
+
+    assert_required::<Quote>(quote, &written);
+    assert_required::<Translation>(
+        translation,
+        &written["translations"]["en"],
+    );
+}
+
+/// Test that the rules `validate` applies to single values are in the
+/// schema too, at the same field.
+#[test]
+fn test_schema_agrees_with_validate() {
+    for (field, value, pointer) in [
+        ("id", json!("../escaped"), "/quotes/0/id"),
+        ("weight", json!(-1), "/quotes/0/weight"),
+        ("date_added", json!("not-a-date"), "/quotes/0/date_added"),
+        (
+            "translations",
+            json!({ "../fr": { "quote_text": "Un" } }),
+            "/quotes/0/translations/..~1fr",
+        ),
+    ] {
+        let mut record = json!({
+            "quote_text": "A",
+            "author": "B",
+            "date_added": "2024-01-01",
+            "image_url": "https://example.com/a.jpg"
+        });
+        record[field] = value;
+        let dataset = json!({ "quotes": [record] });
+        assert_eq!(pointers(&dataset), [pointer], "{}", field);
+
+        let report =
+            validate_str(&dataset.to_string(), Format::Json).unwrap();
+        let fields: Vec<&str> = report
+            .findings
+            .iter()
+            .map(|finding| finding.field)
+            .collect();
+        assert_eq!(fields, [field]);
+    }
+}
+
+/// Test that mistakes are reported at their JSON pointer.
+#[test]
+fn test_check_dataset_pointers() {
+    let dataset = json!({
+        "schema_version": 2,
+        "quotes": [
+            {
+                "quote_text": "Fine.",
+                "author": "A",
+                "date_added": "2024-01-01",
+                "image_url": ""
+            },
+            {
+                "quote_text": "Broken.",
+                "author": 7,
+                "date_added": "yesterday",
+                "image_url": "",
+                "source": {"kind": "podcast"},
+                "tags": ["ok", false],
+                "translations": {"fr": {"author": "B"}},
+                "mood": "kept as an extra field"
+            },
+            {"quote_text": "No image.", "author": "A"}
+        ]
+    });
+    assert_eq!(
+        pointers(&dataset),
+        vec![
+            "/quotes/1/author",
+            "/quotes/1/date_added",
+            "/quotes/1/source/kind",
+            "/quotes/1/tags/1",
+            "/quotes/1/translations/fr",
+            "/quotes/2",
+        ]
+    );
+    let errors = check_dataset(&dataset);
+    assert_eq!(
+        errors[0].to_string(),
+        "/quotes/1/author: expected string, found integer"
+    );
+    assert_eq!(
+        errors[1].message,
+        "`yesterday` is not a valid date or date-time"
+    );
+    assert_eq!(
+        errors[5].message,
+        "missing required property `image_url`"
+    );
+
+    assert_eq!(pointers(&json!({"quotes": {}})), vec!["/quotes"]);
+    assert_eq!(
+        check_dataset(&json!([]))[0].to_string(),
+        "/: expected object, found array"
+    );
+}
+
+/// Test that loading reports schema errors with their location.
+#[test]
+fn test_from_str_checks_the_schema() {
+    let json = r#"{"quotes": [
+        {"quote_text": "A", "author": "A", "date_added": "2024-01-01", "image_url": ""},
+        {"quote_text": "B", "author": "B", "date_added": "2024-13-01", "image_url": ""}
+    ]}"#;
+    let error = Quotes::from_str(json, Format::Json).unwrap_err();
+    assert!(matches!(error, QuoteError::SchemaViolation(ref errors)
+        if errors[0].pointer == "/quotes/1/date_added"));
+    assert_eq!(
+        error.to_string(),
+        "Schema Error: /quotes/1/date_added: `2024-13-01` is not a valid \
+         date or date-time"
+    );
+
+    let yaml =
+        "quotes:\n  - quote_text: A\n    date_added: 2024-01-01\n";
+    let error = Quotes::from_str(yaml, Format::Yaml).unwrap_err();
+    assert!(error.to_string().ends_with("(and 1 more)"));
+
+    let toml = "[[quotes]]\nquote_text = \"A\"\nauthor = \"A\"\n\
+                date_added = \"2024-01-01\"\nimage_url = \"\"\nweight = \"heavy\"\n";
+    assert!(matches!(
+        Quotes::from_str(toml, Format::Toml),
+        Err(QuoteError::SchemaViolation(_))
+    ));
+
+    let newer = r#"{"schema_version": 99, "quotes": [{"text": "A"}]}"#;
+    assert!(matches!(
+        Quotes::from_str(newer, Format::Json),
+        Err(QuoteError::UnsupportedSchemaVersion(99))
+    ));
+}
+
+/// Test that the bundled datasets match the schema.
+#[test]
+fn test_bundled_datasets_match_the_schema() {
+    for path in ["./quotes/01-quotes.json", "./quotes/02-quotes.json"] {
+        let content = std::fs::read_to_string(path).unwrap();
+        let dataset: Value = serde_json::from_str(&content).unwrap();
+        assert!(check_dataset(&dataset).is_empty(), "{}", path);
+        assert!(read_quotes_from_file(path).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use common::quote_on_day;
+use wiserone::quotes::{read_quotes_from_file, Quotes};
+
+/// Builds a small collection to search.
+fn sample_quotes() -> Quotes {
+    Quotes::new(vec![
+        quote_on_day("Simplicity is the ultimate sophistication.", "Leonardo", 1),
+        quote_on_day("Life is really simple, but we insist on making it complicated.", "Confucius", 2),
+        quote_on_day("Un café sans rêve est un café perdu.", "Émile", 3),
+        quote_on_day("The simple things are also the most extraordinary things.", "Paulo Coelho", 4),
+        quote_on_day("Simple simple simple.", "Anonymous", 5),
+    ])
+}
+
+/// Returns the authors of the quotes matching `query`, best first.
+fn authors(quotes: &Quotes, query: &str) -> Vec<String> {
+    quotes
+        .search(query)
+        .into_iter()
+        .map(|hit| hit.quote.author.clone())
+        .collect()
+}
+
+/// Test that matching words are ranked by relevance.
+#[test]
+fn test_search_ranks_matches() {
+    let quotes = sample_quotes();
+    let hits = quotes.search("simple");
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0].quote.author, "Anonymous");
+    assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    assert!(quotes.search("nothing here").is_empty());
+    assert!(quotes.search("  ").is_empty());
+}
+
+/// Test prefix and phrase matching.
+#[test]
+fn test_search_prefix_and_phrase() {
+    let quotes = sample_quotes();
+    assert_eq!(authors(&quotes, "simpl*").len(), 4);
+    assert_eq!(
+        authors(&quotes, "\"the simple things\""),
+        ["Paulo Coelho"]
+    );
+    assert!(authors(&quotes, "\"simple the things\"").is_empty());
+}
+
+/// Test that case and diacritics are ignored in text and authors.
+#[test]
+fn test_search_folds_case_and_diacritics() {
+    let quotes = sample_quotes();
+    assert_eq!(authors(&quotes, "CAFE reve"), ["Émile"]);
+    assert_eq!(authors(&quotes, "emile"), ["Émile"]);
+    assert_eq!(authors(&quotes, "cafe\u{301}"), ["Émile"]);
+}
+
+/// Test searching the bundled collection.
+#[test]
+fn test_search_bundled_quotes() {
+    let quotes =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+    let hits = quotes.search("innovation");
+    assert!(!hits.is_empty());
+    assert!(hits[0]
+        .quote
+        .quote_text
+        .to_lowercase()
+        .contains("innovation"));
+}
@@ -11,17 +11,69 @@ mod tests {
     #[test]
     fn test_generate_sitemap_file_no_html_files(
     ) -> Result<(), Box<dyn Error>> {
-        // Ensure the docs directory is empty
-        fs::remove_dir_all("./docs")?;
-        fs::create_dir("./docs")?;
+        // Start from an empty output directory
+        let output = std::env::temp_dir().join("wiserone-test-sitemap");
+        let _ = fs::remove_dir_all(&output);
+        fs::create_dir_all(&output)?;
 
         // Expect an empty sitemap to be generated
-        generate_sitemap_file("https://example.com/docs/")?;
+        generate_sitemap_file(
+            "https://example.com/docs/",
+            &output,
+            "en",
+        )?;
 
-        let sitemap_content = fs::read_to_string("./docs/sitemap.xml")?;
+        let sitemap_content =
+            fs::read_to_string(output.join("sitemap.xml"))?;
         assert!(sitemap_content.contains("<urlset xmlns="));
         assert!(!sitemap_content.contains("<loc>"));
 
+        fs::remove_dir_all(&output)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_sitemap_file_lists_the_output_directory(
+    ) -> Result<(), Box<dyn Error>> {
+        let output =
+            std::env::temp_dir().join("wiserone-test-sitemap-pages");
+        let _ = fs::remove_dir_all(&output);
+        fs::create_dir_all(output.join("fr"))?;
+        fs::write(output.join("a.html"), "<html lang=\"en\">")?;
+        fs::write(
+            output.join("fr").join("a.html"),
+            "<html lang=\"fr\">",
+        )?;
+        // A page without a language is in the locale of the site
+        fs::write(output.join("b.html"), "<html>")?;
+        fs::write(
+            output.join("fr").join("b.html"),
+            "<html lang=\"fr\">",
+        )?;
+
+        generate_sitemap_file(
+            "https://example.com/",
+            &output,
+            "en-GB",
+        )?;
+
+        let sitemap_content =
+            fs::read_to_string(output.join("sitemap.xml"))?;
+        assert!(sitemap_content
+            .contains("<loc>https://example.com/a.html</loc>"));
+        assert!(sitemap_content
+            .contains("<loc>https://example.com/fr/a.html</loc>"));
+        assert!(sitemap_content.contains(
+            "hreflang=\"x-default\" href=\"https://example.com/a.html\""
+        ));
+        assert!(sitemap_content.contains(
+            "hreflang=\"en\" href=\"https://example.com/a.html\""
+        ));
+        assert!(sitemap_content.contains(
+            "hreflang=\"en-GB\" href=\"https://example.com/b.html\""
+        ));
+
+        fs::remove_dir_all(&output)?;
         Ok(())
     }
 }
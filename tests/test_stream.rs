@@ -0,0 +1,224 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io::{self, Read};
+
+use wiserone::quotes::{read_quotes_from_file, Format, QuoteError};
+use wiserone::stream::{
+    stream_quotes_from_file, stream_quotes_from_reader, QuoteStream,
+};
+
+/// A reader that fails once its content has been read, to show that a
+/// stream hands out quotes before reaching the end of its input.
+struct Truncated<'a>(&'a [u8]);
+
+impl Read for Truncated<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::other("connection lost"));
+        }
+        self.0.read(buffer)
+    }
+}
+
+/// A reader that hands out one byte at a time, so that every value
+/// spans several reads.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let length = buffer.len().min(1);
+        self.0.read(&mut buffer[..length])
+    }
+}
+
+/// Test that streaming a file yields the quotes it would load.
+#[test]
+fn test_stream_matches_loading() {
+    for path in [
+        "./quotes/01-quotes.json",
+        "./quotes/01-quotes.csv",
+        "./quotes/02-quotes.json",
+    ] {
+        let loaded = read_quotes_from_file(path).unwrap();
+        let streamed = stream_quotes_from_file(path)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(streamed, loaded.quotes, "{}", path);
+    }
+
+    let quotes =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+    for format in [Format::JsonLines, Format::Yaml, Format::Toml] {
+        let mut buffer = Vec::new();
+        quotes.write_to(&mut buffer, format).unwrap();
+        let streamed =
+            stream_quotes_from_reader(buffer.as_slice(), format)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+        assert_eq!(streamed, quotes.quotes, "{}", format);
+    }
+}
+
+/// Test that the JSON reader copes with the layout of a dataset.
+#[test]
+fn test_stream_json_layout() {
+    let json = r#"
+        {
+          "title": {"nested": ["]", "}"]},
+          "quotes" : [
+            {"quote_text": "Brackets ] } and \"quotes\" [ {", "author": "A",
+             "date_added": "2024-01-01", "image_url": "", "weight": 1.5,
+             "tags": []},
+            {"quote_text": "Two", "author": "B", "date_added": "2024-01-02",
+             "image_url": "", "source": null}
+          ],
+          "schema_version": 2
+        }
+    "#;
+    let quotes =
+        stream_quotes_from_reader(json.as_bytes(), Format::Json)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+    assert_eq!(quotes.len(), 2);
+    assert_eq!(quotes[0].quote_text, "Brackets ] } and \"quotes\" [ {");
+    assert_eq!(quotes[0].weight, Some(1.5));
+    assert_eq!(quotes[1].author, "B");
+
+    let trickled = stream_quotes_from_reader(
+        Trickle(json.as_bytes()),
+        Format::Json,
+    )
+    .unwrap()
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+    assert_eq!(trickled, quotes);
+
+    let empty = r#"{"schema_version": 2, "quotes": []}"#;
+    assert_eq!(
+        stream_quotes_from_reader(empty.as_bytes(), Format::Json)
+            .unwrap()
+            .count(),
+        0
+    );
+}
+
+/// Test that errors stop the stream where they are found.
+#[test]
+fn test_stream_json_errors() {
+    let first_error = |json: &str| {
+        stream_quotes_from_reader(json.as_bytes(), Format::Json)
+            .unwrap()
+            .find_map(Result::err)
+            .unwrap()
+    };
+
+    let invalid = r#"{"quotes": [
+        {"quote_text": "A", "author": "A", "date_added": "2024-01-01", "image_url": ""},
+        {"quote_text": "B", "author": "B", "date_added": "soon", "image_url": ""}
+    ]}"#;
+    let mut stream =
+        stream_quotes_from_reader(invalid.as_bytes(), Format::Json)
+            .unwrap();
+    assert!(stream.next().unwrap().is_ok());
+    assert!(matches!(
+        stream.next(),
+        Some(Err(QuoteError::SchemaViolation(errors)))
+            if errors[0].pointer == "/quotes/1/date_added"
+    ));
+    assert!(stream.next().is_none());
+
+    assert!(matches!(
+        first_error(
+            r#"{"schema_version": 99, "quotes": [{"text": 1}]}"#
+        ),
+        QuoteError::UnsupportedSchemaVersion(99)
+    ));
+
+    let late_version = r#"{"quotes": [
+        {"quote_text": "A", "author": "A", "date_added": "2024-01-01", "image_url": ""}
+    ], "schema_version": 99}"#;
+    let mut stream = stream_quotes_from_reader(
+        late_version.as_bytes(),
+        Format::Json,
+    )
+    .unwrap();
+    assert!(stream.next().unwrap().is_ok());
+    assert!(matches!(
+        stream.next(),
+        Some(Err(QuoteError::UnsupportedSchemaVersion(99)))
+    ));
+    assert!(stream.next().is_none());
+    assert!(matches!(
+        first_error(r#"{"schema_version": "two", "quotes": []}"#),
+        QuoteError::SchemaViolation(errors)
+            if errors[0].pointer == "/schema_version"
+    ));
+    assert!(matches!(
+        first_error(r#"{"schema_version": 2}"#),
+        QuoteError::SchemaViolation(_)
+    ));
+    assert!(matches!(
+        first_error(r#"{"quotes": [] } trailing"#),
+        QuoteError::ParseError(_)
+    ));
+    assert!(matches!(
+        first_error(r#"{"quotes": [{"quote_text": "A"},]}"#),
+        QuoteError::SchemaViolation(_)
+    ));
+    assert!(matches!(
+        first_error(r#"[{"quote_text": "A"}]"#),
+        QuoteError::ParseError(_)
+    ));
+    assert!(matches!(
+        first_error(r#"{"quotes": [{"quote_text": "A""#),
+        QuoteError::ParseError(_)
+    ));
+}
+
+/// Test that quotes are handed out before the whole input is read.
+#[test]
+fn test_stream_is_incremental() {
+    let first = r#"{"quote_text": "A", "author": "A", "date_added": "2024-01-01", "image_url": ""}"#;
+
+    let json = format!("{{\"quotes\": [{}, ", first);
+    let mut stream = stream_quotes_from_reader(
+        Truncated(json.as_bytes()),
+        Format::Json,
+    )
+    .unwrap();
+    assert_eq!(stream.next().unwrap().unwrap().quote_text, "A");
+    assert!(matches!(stream.next(), Some(Err(QuoteError::IOError(_)))));
+
+    let lines = format!("{}\n\n", first);
+    let mut stream = stream_quotes_from_reader(
+        Truncated(lines.as_bytes()),
+        Format::JsonLines,
+    )
+    .unwrap();
+    assert_eq!(stream.next().unwrap().unwrap().author, "A");
+    assert!(stream.next().unwrap().is_err());
+
+    let csv =
+        "quote_text,author,date_added,image_url\nA,A,2024-01-01,\n";
+    let mut stream = stream_quotes_from_reader(
+        Truncated(csv.as_bytes()),
+        Format::Csv,
+    )
+    .unwrap();
+    assert_eq!(stream.next().unwrap().unwrap().quote_text, "A");
+    assert!(stream.next().unwrap().is_err());
+}
+
+/// Test that a loaded collection can be handed out as a stream.
+#[test]
+fn test_stream_from_quotes() {
+    let quotes =
+        read_quotes_from_file("./quotes/02-quotes.csv").unwrap();
+    let count = quotes.quotes.len();
+    assert_eq!(QuoteStream::from(quotes).count(), count);
+}
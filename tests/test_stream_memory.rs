@@ -0,0 +1,168 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Checks that streaming keeps a flat memory ceiling. The allocator
+//! below counts the bytes of the whole test binary, so this file holds
+//! a single test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wiserone::quotes::Format;
+use wiserone::stream::stream_quotes_from_reader;
+
+/// The number of quotes in the generated datasets.
+const COLLECTION_SIZE: usize = 100_000;
+
+/// The most memory streaming may use, far below the size of the
+/// datasets.
+const CEILING: usize = 1 << 20;
+
+/// The bytes currently allocated.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+/// The most bytes allocated at once since the last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, keeping count of the bytes in use.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            let current = CURRENT
+                .fetch_add(layout.size(), Ordering::SeqCst)
+                + layout.size();
+            let _ = PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        let _ = CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// A reader that writes a dataset of `COLLECTION_SIZE` quotes as it is
+/// read, so that the input is never held in memory.
+struct Generated {
+    format: Format,
+    /// The index of the next quote to write.
+    index: usize,
+    /// What is left to hand out of the current chunk.
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Generated {
+    fn new(format: Format) -> Self {
+        let header = match format {
+            Format::Json => "{\"schema_version\": 2, \"quotes\": [\n",
+            Format::Csv => "quote_text,author,date_added,image_url\n",
+            _ => "",
+        };
+        Generated {
+            format,
+            index: 0,
+            chunk: header.as_bytes().to_vec(),
+            position: 0,
+        }
+    }
+
+    /// Replaces the chunk with the next quote, or the end of the
+    /// dataset.
+    fn refill(&mut self) {
+        self.chunk.clear();
+        self.position = 0;
+        if self.index > COLLECTION_SIZE {
+            return;
+        }
+        let index = self.index;
+        self.index += 1;
+        let separator = if index == 0 { "" } else { ",\n" };
+        let record = |chunk: &mut Vec<u8>| {
+            chunk.extend_from_slice(
+                format!(
+                    "{{\"quote_text\": \"Quote number {}.\", \
+                     \"author\": \"Author {}\", \
+                     \"date_added\": \"2024-01-01\", \
+                     \"image_url\": \"https://example.com/{}.jpg\"}}",
+                    index,
+                    index % 100,
+                    index
+                )
+                .as_bytes(),
+            )
+        };
+        match (self.format, index == COLLECTION_SIZE) {
+            (Format::Json, true) => self.chunk.extend(b"\n]}\n"),
+            (_, true) => {}
+            (Format::Json, false) => {
+                self.chunk.extend(separator.as_bytes());
+                record(&mut self.chunk);
+            }
+            (Format::JsonLines, false) => {
+                record(&mut self.chunk);
+                self.chunk.push(b'\n');
+            }
+            (_, false) => self.chunk.extend(
+                format!(
+                    "Quote number {}.,Author {},2024-01-01,\
+                     https://example.com/{}.jpg\n",
+                    index,
+                    index % 100,
+                    index
+                )
+                .as_bytes(),
+            ),
+        }
+    }
+}
+
+impl Read for Generated {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunk.len() {
+            self.refill();
+        }
+        let length = (&self.chunk[self.position..]).read(buffer)?;
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Test that streaming a dataset many times larger than the ceiling
+/// stays under it.
+#[test]
+fn test_stream_memory_ceiling() {
+    for format in [Format::Json, Format::JsonLines, Format::Csv] {
+        let (mut count, mut bytes) = (0, 0);
+        let baseline = CURRENT.load(Ordering::SeqCst);
+        PEAK.store(baseline, Ordering::SeqCst);
+        for quote in
+            stream_quotes_from_reader(Generated::new(format), format)
+                .unwrap()
+        {
+            let quote = quote.unwrap();
+            count += 1;
+            bytes += quote.quote_text.len() + quote.image_url.len();
+        }
+        let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+        assert_eq!(count, COLLECTION_SIZE, "{}", format);
+        assert!(bytes > 4 * CEILING, "{}", format);
+        assert!(
+            peak < CEILING,
+            "{} used {} bytes to stream {} bytes of quotes",
+            format,
+            peak,
+            bytes
+        );
+    }
+}
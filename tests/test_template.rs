@@ -0,0 +1,364 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_json::{json, Value};
+use wiserone::config::SiteConfig;
+use wiserone::error::WiseroneError;
+use wiserone::template::Templates;
+
+/// Renders a single template called `page.html`.
+fn render(
+    source: &str,
+    context: &Value,
+) -> Result<String, WiseroneError> {
+    let mut templates = Templates::new();
+    templates.add("page.html", source)?;
+    templates.render("page.html", context)
+}
+
+/// Returns the message of a template error.
+fn error_message(result: Result<String, WiseroneError>) -> String {
+    match result {
+        Err(WiseroneError::Template(message)) => message,
+        other => panic!("expected a template error, got {:?}", other),
+    }
+}
+
+/// Test that values and filters are written.
+#[test]
+fn test_render_values_and_filters() {
+    let context = json!({
+        "quote": {
+            "quote_text": "Simplicity is the ultimate sophistication.",
+            "author": "Leonardo",
+            "date_added": "2024-03-05T10:00:00Z",
+            "tags": ["art", "design"],
+            "weight": 1.5,
+            "notes": null
+        }
+    });
+    let cases = [
+        ("{{ quote.author }}", "Leonardo"),
+        ("{{quote.weight}}", "1.5"),
+        ("{{ quote.tags.1 }}", "design"),
+        ("{{ quote.author | upper }}", "LEONARDO"),
+        ("{{ quote.author | lower | length }}", "8"),
+        ("{{ quote.tags | join(\", \") }}", "art, design"),
+        ("{{ quote.tags | length }}", "2"),
+        ("{{ quote.quote_text | truncate(20) }}", "Simplicity is the u…"),
+        ("{{ quote.author | truncate(20) }}", "Leonardo"),
+        (
+            "{{ quote.date_added | date(\"[month repr:long] [day], [year]\") }}",
+            "March 05, 2024",
+        ),
+        ("{{ quote.notes | default(\"none\") }}", "none"),
+        ("{{ quote.missing | default(quote.author) }}", "Leonardo"),
+        ("{{ \"literal\" }} {{ 42 }}", "literal 42"),
+        ("{# a comment #}kept", "kept"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(
+            render(source, &context).unwrap(),
+            expected,
+            "{}",
+            source
+        );
+    }
+}
+
+/// Test that `if` chooses between branches.
+#[test]
+fn test_render_conditions() {
+    let source = "{% if quote.source and not quote.hidden %}source\
+                  {% elif quote.language == \"fr\" or quote.tags %}other\
+                  {% else %}none{% endif %}";
+    let cases = [
+        (json!({"quote": {"source": {"title": "Odes"}}}), "source"),
+        (json!({"quote": {"source": {}, "language": "fr"}}), "other"),
+        (
+            json!({"quote": {"source": {"title": "Odes"}, "hidden": true}}),
+            "none",
+        ),
+        (json!({"quote": {"tags": ["time"]}}), "other"),
+        (json!({"quote": {"tags": [], "language": "en"}}), "none"),
+        (json!({}), "none"),
+    ];
+    for (context, expected) in cases {
+        assert_eq!(
+            render(source, &context).unwrap(),
+            expected,
+            "{}",
+            context
+        );
+    }
+    assert_eq!(
+        render(
+            "{% if count != 0 %}some{% endif %}",
+            &json!({"count": 3})
+        )
+        .unwrap(),
+        "some"
+    );
+}
+
+/// Test that `for` repeats its body for every item.
+#[test]
+fn test_render_loops() {
+    let context = json!({
+        "tags": ["a", "b", "c"],
+        "links": {"en": "/", "fr": "/fr/"},
+        "empty": []
+    });
+    assert_eq!(
+        render(
+            "{% for tag in tags %}{{ loop.index }}:{{ tag }}\
+             {% if not loop.last %},{% endif %}{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        "1:a,2:b,3:c"
+    );
+    assert_eq!(
+        render(
+            "{% for language, url in links %}{{ language }}={{ url }};{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        "en=/;fr=/fr/;"
+    );
+    assert_eq!(
+        render(
+            "{% for language in links %}{{ language }}{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        "enfr"
+    );
+    assert_eq!(
+        render(
+            "{% for tag in empty %}{{ tag }}{% else %}no tags{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        "no tags"
+    );
+    assert_eq!(
+        render(
+            "{% for tag in missing %}{{ tag }}{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        ""
+    );
+    assert_eq!(
+        render(
+            "{% for tag in tags %}{% for other in tags %}\
+             {% if tag == other %}{{ loop.index0 }}{% endif %}\
+             {% endfor %}{% endfor %}",
+            &context
+        )
+        .unwrap(),
+        "012"
+    );
+}
+
+/// Test that includes and layouts are combined.
+#[test]
+fn test_render_includes_and_layouts() {
+    let mut templates = Templates::new();
+    templates
+        .add(
+            "base.html",
+            "<title>{% block title %}Site{% endblock %}</title>\n\
+             {% include \"partials/footer.html\" %}\n\
+             <main>{% block content %}{% endblock %}</main>",
+        )
+        .unwrap();
+    templates
+        .add("partials/footer.html", "by {{ site.name }}\n")
+        .unwrap();
+    templates
+        .add(
+            "section.html",
+            "{% extends \"base.html\" %}\
+             {% block title %}Section{% endblock %}\
+             {% block content %}[{% block inner %}default{% endblock %}]{% endblock %}",
+        )
+        .unwrap();
+    templates
+        .add(
+            "page.html",
+            "{% extends \"section.html\" %}\
+             {% block inner %}{{ page.title }}{% endblock %}\
+             ignored outside blocks",
+        )
+        .unwrap();
+    let context =
+        json!({"site": {"name": "wiserone"}, "page": {"title": "Hi"}});
+
+    assert_eq!(
+        templates.render("page.html", &context).unwrap(),
+        "<title>Section</title>\nby wiserone\n<main>[Hi]</main>"
+    );
+    assert_eq!(
+        templates.render("base.html", &context).unwrap(),
+        "<title>Site</title>\nby wiserone\n<main></main>"
+    );
+    assert!(templates.contains("partials/footer.html"));
+}
+
+/// Test that whitespace around tags can be trimmed.
+#[test]
+fn test_render_whitespace() {
+    let context = json!({"tags": ["a", "b"]});
+    assert_eq!(
+        render(
+            "<ul>\n  {% for tag in tags %}\n  <li>{{ tag }}</li>\n  {% endfor %}\n</ul>\n",
+            &context
+        )
+        .unwrap(),
+        "<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>\n"
+    );
+    assert_eq!(
+        render("a  {%- if tags -%}  b  {%- endif %}", &context)
+            .unwrap(),
+        "ab"
+    );
+    assert_eq!(
+        render("x {{- \"y\" -}} \n z", &context).unwrap(),
+        "xyz"
+    );
+}
+
+/// Test that mistakes are reported with their template and line.
+#[test]
+fn test_template_errors() {
+    let context =
+        json!({"quote": {"author": "A", "date_added": "soon"}});
+
+    for (source, expected) in [
+        (
+            "a\n{{ quote.author | shout }}",
+            "`page.html` line 2: unknown filter `shout`",
+        ),
+        (
+            "{% if quote.author %}open",
+            "`page.html` line 1: `{% if %}` is never closed",
+        ),
+        (
+            "{% endfor %}",
+            "`page.html` line 1: unexpected `{% endfor %}`",
+        ),
+        (
+            "\n\n{{ quote.author",
+            "`page.html` line 3: `{{` is never closed",
+        ),
+        ("{% repeat %}", "`page.html` line 1: unknown tag `repeat`"),
+        (
+            "{% for 1x in quote %}{% endfor %}",
+            "`page.html` line 1: loop names must be identifiers",
+        ),
+    ] {
+        let mut templates = Templates::new();
+        assert_eq!(
+            error_message(
+                templates
+                    .add("page.html", source)
+                    .map(|_| String::new())
+            ),
+            expected,
+            "{}",
+            source
+        );
+    }
+
+    assert_eq!(
+        error_message(render("x\n{{ quote.title }}", &context)),
+        "`page.html` line 2: `quote.title` is not defined"
+    );
+    assert_eq!(
+        error_message(render(
+            "{{ quote.date_added | date(\"[year]\") }}",
+            &context
+        )),
+        "`page.html` line 1: `soon` is not a valid date"
+    );
+    assert_eq!(
+        error_message(render("{% include \"missing.html\" %}", &context)),
+        "`page.html` line 1: in `missing.html`: no template has this name"
+    );
+    assert_eq!(
+        error_message(render(
+            "{% for c in quote.author %}{% endfor %}",
+            &context
+        )),
+        "`page.html` line 1: cannot loop over `\"A\"`"
+    );
+    assert!(error_message(render(
+        "{% include \"page.html\" %}",
+        &context
+    ))
+    .contains("nested too deeply"));
+    assert!(error_message(
+        Templates::new().render("page.html", &context)
+    )
+    .contains("no template has this name"));
+}
+
+/// Test that the shipped layouts render a quote page.
+#[test]
+fn test_shipped_layouts_render() {
+    let templates = Templates::load("_layouts").unwrap();
+    assert!(templates.contains("base.html"));
+    assert!(templates.contains("partials/meta.html"));
+
+    let mut site = SiteConfig::default();
+    site.analytics.measurement_id = Some("G-TEST".to_string());
+    let mut context = json!({
+        "site": site,
+        "page": {
+            "alternates": [
+                {"hreflang": "en", "url": "https://wiserone.com/2024_01_01.html"},
+                {"hreflang": "x-default", "url": "https://wiserone.com/2024_01_01.html"}
+            ],
+            "author": "The Wiser One",
+            "home": "https://wiserone.com",
+            "language": "en",
+            "locale": "en",
+            "title": "Know thyself.",
+            "url": "https://wiserone.com/2024_01_01.html"
+        },
+        "quote": {
+            "id": "q-1",
+            "quote_text": "Know thyself.",
+            "author": "The Wiser One",
+            "date_added": "2024-01-01T06:06:06Z",
+            "image_url": "https://kura.pro/banner.webp"
+        }
+    });
+    let html = templates.render("quote.html", &context).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">"));
+    assert!(html.contains(
+        "<h1 class=\"position-relative fs-2 text-theme\">Know thyself.</h1>"
+    ));
+    assert!(html.contains("hreflang=\"x-default\""));
+    assert!(html.contains("\"copyrightYear\":\"2024\""));
+    assert!(html.contains(
+        "\"@id\":\"https://wiserone.com/2024_01_01.html#q-1\""
+    ));
+    assert!(html.contains("2024-01-01</span>"));
+    assert!(html.contains("gtag('config', 'G-TEST')"));
+    assert!(html.contains(
+        "<meta content=\"@wwdseb\" name=\"twitter:creator\">"
+    ));
+
+    // Sites without analytics or social accounts leave them out
+    site.analytics.measurement_id = None;
+    site.social.twitter = None;
+    context["site"] = json!(site);
+    let html = templates.render("quote.html", &context).unwrap();
+    assert!(!html.contains("gtag"));
+    assert!(!html.contains("twitter:creator"));
+    assert!(!html.contains("{{") && !html.contains("{%"));
+}
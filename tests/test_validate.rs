@@ -0,0 +1,273 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+mod common;
+
+use common::write_temp_file;
+use wiserone::quotes::{Format, Quote, Quotes, Translation};
+use wiserone::validate::{validate_file, validate_str, Severity};
+
+/// Builds a quote that passes every check.
+fn valid_quote(day: u32) -> Quote {
+    Quote {
+        quote_text: format!("Test quote {}", day),
+        author: "Test author".to_string(),
+        date_added: Some(
+            format!("2024-01-{:02}T06:06:06Z", day).parse().unwrap(),
+        ),
+        image_url: "https://example.com/image.jpg".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Test that a clean dataset and the bundled datasets have no findings.
+#[test]
+fn test_validate_clean_dataset() {
+    let quotes = Quotes::new(vec![valid_quote(1), valid_quote(2)]);
+    assert!(quotes.validate().is_empty());
+
+    let report = validate_file("./quotes/01-quotes.json").unwrap();
+    assert!(!report.has_errors(), "{:?}", report.findings);
+}
+
+/// Test that every error check is reported against the right record.
+#[test]
+fn test_validate_reports_errors() {
+    let quotes = Quotes::new(vec![
+        valid_quote(1),
+        Quote { quote_text: " ".to_string(), ..valid_quote(2) },
+        Quote {
+            date_added: Some("2024-01-01T09:00:00Z".parse().unwrap()),
+            ..valid_quote(4)
+        },
+        Quote {
+            image_url: "kura.pro/image.jpg".to_string(),
+            ..valid_quote(5)
+        },
+        Quote { id: Some("same".to_string()), ..valid_quote(6) },
+        Quote { id: Some("same".to_string()), ..valid_quote(7) },
+        Quote { weight: Some(-1.0), ..valid_quote(8) },
+        Quote {
+            translations: [(
+                "../fr".to_string(),
+                Translation {
+                    quote_text: "Citation".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..valid_quote(9)
+        },
+    ]);
+    let report = quotes.validate();
+    assert!(report.has_errors());
+
+    let errors: Vec<_> = report
+        .by_severity(Severity::Error)
+        .map(|finding| (finding.index, finding.field))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (1, "quote_text"),
+            (2, "date_added"),
+            (3, "image_url"),
+            (5, "id"),
+            (6, "weight"),
+            (7, "translations"),
+        ]
+    );
+}
+
+/// Test that cosmetic problems are only warnings.
+#[test]
+fn test_validate_reports_warnings() {
+    let quotes = Quotes::new(vec![
+        Quote { author: "Test author ".to_string(), ..valid_quote(1) },
+        Quote { author: String::new(), ..valid_quote(2) },
+        Quote {
+            date_added: Some("2024-01-03T06:06:06Z".parse().unwrap()),
+            ..valid_quote(1)
+        },
+    ]);
+    let report = quotes.validate();
+    assert!(!report.has_errors(), "{:?}", report.findings);
+
+    let warnings: Vec<_> = report
+        .by_severity(Severity::Warning)
+        .map(|finding| (finding.index, finding.field))
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![(0, "author"), (1, "author"), (2, "quote_text")]
+    );
+}
+
+/// Test that findings from a file carry the line of their record.
+#[test]
+fn test_validate_file_reports_lines() {
+    let json = r#"{
+  "quotes": [
+    {
+      "quote_text": "Fine",
+      "author": "Test author",
+      "date_added": "2024-01-01T06:06:06Z",
+      "image_url": "https://example.com/image.jpg"
+    },
+    {
+      "quote_text": "",
+      "author": "Test author",
+      "date_added": "2024-01-02T06:06:06Z",
+      "image_url": "https://example.com/image.jpg"
+    }
+  ]
+}"#;
+    let path = write_temp_file("lines.json", json);
+    let report = validate_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].line, Some(10));
+    assert_eq!(
+        report.findings[0].to_string(),
+        "error: record 2 (line 10): quote_text: is empty"
+    );
+
+    let csv = "quote_text,author,date_added,image_url\n\
+               Fine,Test author,2024-01-01T06:06:06Z,https://example.com/a.jpg\n\
+               \"Multi\nline\",Test author,2024-01-02T06:06:06Z,https://example.com/b.jpg\n\
+               Bad,Test author,not-a-date,https://example.com/c.jpg\n";
+    let path = write_temp_file("lines.csv", csv);
+    let report = validate_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].index, 2);
+    assert_eq!(report.findings[0].line, Some(5));
+}
+
+/// Test that the `quote_text` keys of translations are not mistaken
+/// for records when locating findings.
+#[test]
+fn test_validate_file_skips_translated_keys() {
+    let json = r#"{
+  "quotes": [
+    {
+      "quote_text": "Fine",
+      "author": "Test author",
+      "date_added": "2024-01-01T06:06:06Z",
+      "image_url": "https://example.com/image.jpg",
+      "translations": {
+        "fr": { "quote_text": "Bien" }
+      }
+    },
+    {
+      "quote_text": "",
+      "author": "Test author",
+      "date_added": "2024-01-02T06:06:06Z",
+      "image_url": "https://example.com/image.jpg"
+    }
+  ]
+}"#;
+    let path = write_temp_file("translated.json", json);
+    let report = validate_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].line, Some(13));
+
+    let yaml = "quotes:\n\
+                - quote_text: Fine\n\
+                \x20 author: Test author\n\
+                \x20 date_added: 2024-01-01T06:06:06Z\n\
+                \x20 image_url: https://example.com/image.jpg\n\
+                \x20 translations:\n\
+                \x20   fr:\n\
+                \x20     quote_text: Bien\n\
+                - quote_text: ''\n\
+                \x20 author: Test author\n\
+                \x20 date_added: 2024-01-02T06:06:06Z\n\
+                \x20 image_url: https://example.com/image.jpg\n";
+    let path = write_temp_file("translated.yaml", yaml);
+    let report = validate_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].line, Some(9));
+}
+
+/// Test that a record with an invalid date is reported without hiding
+/// problems in the other records.
+#[test]
+fn test_validate_file_reports_invalid_dates() {
+    let json = r#"{"quotes": [
+      {"quote_text": "One", "author": "A", "date_added": "2024-13-45", "image_url": "https://example.com/a.jpg"},
+      {"quote_text": "", "author": "A", "date_added": "2024-01-02", "image_url": "https://example.com/b.jpg"},
+      {"quote_text": "Three", "author": "A", "date_added": "yesterday", "image_url": "https://example.com/c.jpg"}
+    ]}"#;
+    let path = write_temp_file("dates.json", json);
+    let report = validate_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let errors: Vec<_> = report
+        .by_severity(Severity::Error)
+        .map(|finding| (finding.index, finding.field, finding.line))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (0, "date_added", Some(2)),
+            (1, "quote_text", Some(3)),
+            (2, "date_added", Some(4)),
+        ]
+    );
+}
+
+/// Test validating a dataset held in memory.
+#[test]
+fn test_validate_str() {
+    let yaml = "quotes:\n  - quote_text: \"\"\n    author: A\n    \
+                date_added: 2024-01-01\n    image_url: not-a-url\n";
+    let report = validate_str(yaml, Format::Yaml).unwrap();
+    let fields: Vec<_> =
+        report.findings.iter().map(|f| (f.field, f.line)).collect();
+    assert_eq!(
+        fields,
+        [("quote_text", Some(2)), ("image_url", Some(2))]
+    );
+
+    let json = r#"{"quotes": [
+        {"quote_text": "A", "author": "B", "date_added": "2024-01-01",
+         "image_url": "http://x", "id": "../escaped"}
+    ]}"#;
+    let report = validate_str(json, Format::Json).unwrap();
+    assert!(report.has_errors());
+    assert_eq!(
+        report.findings[0].to_string(),
+        "error: record 1 (line 2): id: `../escaped` may only hold ASCII \
+         letters, digits, `-` and `_`"
+    );
+}
+
+/// Test that fortune files, which hold no dates or images, only get
+/// warnings for their empty images.
+#[test]
+fn test_validate_fortune() {
+    let fortune = "Know thyself.\n\t\t-- Socrates\n%\nBe brief.\n%\n";
+    let report = validate_str(fortune, Format::Fortune).unwrap();
+    assert!(!report.has_errors());
+    let findings: Vec<_> = report
+        .findings
+        .iter()
+        .map(|f| (f.severity, f.field, f.line))
+        .collect();
+    assert_eq!(
+        findings,
+        [
+            (Severity::Warning, "image_url", Some(1)),
+            (Severity::Warning, "author", Some(4)),
+            (Severity::Warning, "image_url", Some(4)),
+        ]
+    );
+
+    let mut quotes = Quotes::new(vec![valid_quote(1)]);
+    quotes.quotes[0].image_url = String::new();
+    assert!(quotes.validate().has_errors());
+}
@@ -0,0 +1,171 @@
+// Copyright notice and licensing information.
+// Copyright © 2024 The Wiser One. All rights reserved.
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeMap;
+use wiserone::quotes::{
+    read_quotes_from_file, Format, Quote, Quotes, Source, SourceKind,
+    Translation,
+};
+use wiserone::writers::write_quotes_to_file;
+
+/// Builds a collection that uses every field.
+fn rich_quotes() -> Quotes {
+    let mut extra = BTreeMap::new();
+    let _ = extra.insert("mood".to_string(), "calm".into());
+    let mut translations = BTreeMap::new();
+    let _ = translations.insert(
+        "fr".to_string(),
+        Translation {
+            quote_text: "Connais-toi toi-même.".to_string(),
+            author: Some("Socrate".to_string()),
+            image_alt: Some("Un buste".to_string()),
+        },
+    );
+    let _ = translations.insert(
+        "la".to_string(),
+        Translation {
+            quote_text: "Nosce te ipsum.".to_string(),
+            ..Default::default()
+        },
+    );
+    Quotes::new(vec![
+        Quote {
+            quote_text: "Know thyself, \"always\".".to_string(),
+            author: "Socrates".to_string(),
+            date_added: Some("2024-01-01".parse().unwrap()),
+            image_url: "https://example.com/a.jpg".to_string(),
+            id: Some("socrates-1".to_string()),
+            tags: vec!["wisdom".to_string(), "self".to_string()],
+            source: Some(Source {
+                kind: Some(SourceKind::Speech),
+                title: Some("Apology".to_string()),
+                url: None,
+            }),
+            language: Some("en".to_string()),
+            image_alt: Some("A bust".to_string()),
+            notes: Some("Line one,\nline two".to_string()),
+            weight: Some(2.5),
+            translations,
+            extra,
+        },
+        Quote {
+            quote_text: "Carpe diem.".to_string(),
+            author: "Horace".to_string(),
+            date_added: Some(
+                "2024-01-02T06:06:06+02:00".parse().unwrap(),
+            ),
+            image_url: "https://example.com/b.jpg".to_string(),
+            ..Default::default()
+        },
+    ])
+}
+
+/// Test that every format round-trips every field, and unscheduled
+/// quotes.
+#[test]
+fn test_write_round_trip() {
+    let mut quotes = rich_quotes();
+    quotes.quotes.push(Quote {
+        quote_text: "Not scheduled yet.".to_string(),
+        author: "Anonymous".to_string(),
+        image_url: "https://example.com/c.jpg".to_string(),
+        ..Default::default()
+    });
+    for format in [
+        Format::Json,
+        Format::Csv,
+        Format::Yaml,
+        Format::Toml,
+        Format::JsonLines,
+    ] {
+        let mut buffer = Vec::new();
+        quotes.write_to(&mut buffer, format).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let read = Quotes::from_str(&text, format).unwrap();
+        assert_eq!(read.quotes, quotes.quotes, "{}:\n{}", format, text);
+        assert_eq!(
+            read.quotes[1].date_added.unwrap().to_string(),
+            "2024-01-02T06:06:06+02:00"
+        );
+    }
+}
+
+/// Test that compact JSON is written on a single line.
+#[test]
+fn test_write_compact_json() {
+    let mut buffer = Vec::new();
+    rich_quotes().write_compact_to(&mut buffer, Format::Json).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert_eq!(text.lines().count(), 1);
+    assert_eq!(
+        Quotes::from_str(&text, Format::Json).unwrap(),
+        rich_quotes()
+    );
+}
+
+/// Test that converting the bundled JSON reproduces the bundled CSV.
+#[test]
+fn test_write_quotes_to_file_matches_bundled_csv() {
+    let quotes =
+        read_quotes_from_file("./quotes/01-quotes.json").unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("wiserone-{}-convert.csv", std::process::id()));
+    write_quotes_to_file(&quotes, &path.to_string_lossy()).unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let bundled =
+        std::fs::read_to_string("./quotes/01-quotes.csv").unwrap();
+    assert_eq!(written, bundled);
+}
+
+/// Test that extra fields round-trip in CSV and TOML when the format
+/// can hold them, and are refused with a clear error when it cannot.
+#[test]
+fn test_write_extra_fields() {
+    let with_extra = |key: &str, value: serde_json::Value| {
+        let mut quotes = rich_quotes();
+        let _ = quotes.quotes[1].extra.insert(key.to_string(), value);
+        quotes
+    };
+
+    // Strings that look like numbers stay strings in CSV
+    let quotes = with_extra("rating", "3".into());
+    let mut buffer = Vec::new();
+    quotes.write_to(&mut buffer, Format::Csv).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let read = Quotes::from_str(&text, Format::Csv).unwrap();
+    assert_eq!(read.quotes, quotes.quotes);
+
+    // TOML keeps the type of other values
+    let quotes = with_extra("rating", serde_json::json!([3, true]));
+    let mut buffer = Vec::new();
+    quotes.write_to(&mut buffer, Format::Toml).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let read = Quotes::from_str(&text, Format::Toml).unwrap();
+    assert_eq!(read.quotes, quotes.quotes);
+
+    let err = with_extra("rating", 3.into())
+        .write_to(Vec::new(), Format::Csv)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Write Error: `{}` has a `rating` field of `3`, which CSV \
+             cannot hold",
+            rich_quotes().quotes[1].stable_id()
+        )
+    );
+
+    let err = with_extra("rating", serde_json::Value::Null)
+        .write_to(Vec::new(), Format::Toml)
+        .unwrap_err();
+    assert!(
+        err.to_string().ends_with(
+            "has a `rating` field of `null`, which TOML cannot hold"
+        ),
+        "{}",
+        err
+    );
+}